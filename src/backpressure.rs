@@ -1,76 +1,369 @@
 use std::sync::Arc;
 use std::sync::Mutex;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use futures::channel::oneshot;
+use serde::Serialize;
 
 #[derive(Clone)]
 pub struct CreditManager {
-    credits: Arc<Mutex<u32>>,
+    credits: Arc<Mutex<CreditLedger>>,
     initial_credits: u32,
+    refill: Option<Arc<Mutex<AutoRefill>>>,
+    watermarks: Option<(u32, u32)>,
+    paused: Arc<Mutex<bool>>,
+    waiters: Arc<Mutex<VecDeque<CreditWaiter>>>,
+}
+
+// Balance and counters live under one lock so recording stats costs a few
+// integer increments on the consume/add path.
+struct CreditLedger {
+    available: u32,
+    stats: CreditStats,
+}
+
+impl CreditLedger {
+    fn take(&mut self, amount: u32) {
+        self.available -= amount;
+        self.stats.total_consumed += u64::from(amount);
+        if self.available == 0 && amount > 0 {
+            self.stats.exhaustion_events += 1;
+        }
+    }
+
+    fn give(&mut self, amount: u32) {
+        self.available = self.available.saturating_add(amount);
+        self.stats.total_added += u64::from(amount);
+    }
+}
+
+/// Lifetime counters for a `CreditManager`, shared by its clones.
+///
+/// `consume_failures` counts synchronous `consume` calls refused for lack of
+/// credits; `exhaustion_events` counts how often the balance hit zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreditStats {
+    pub total_consumed: u64,
+    pub total_added: u64,
+    pub consume_failures: u64,
+    pub exhaustion_events: u64,
+}
+
+// A pending `consume_async`; `grant` fires once its credits are deducted.
+struct CreditWaiter {
+    amount: u32,
+    grant: oneshot::Sender<()>,
+}
+
+/// Flow-control edge reported by `CreditManager::pressure_signal`; maps onto
+/// `ControlMessage::Pause`/`Resume`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PressureSignal {
+    Pause,
+    Resume,
+    None,
+}
+
+// Periodic top-up state for `CreditManager::with_auto_refill`, shared by
+// clones like the credits themselves.
+struct AutoRefill {
+    amount: u32,
+    interval_ms: u64,
+    cap: Option<u32>,
+    last_refill_ms: Option<u64>,
 }
 
 impl CreditManager {
     pub fn new(initial_credits: u32) -> Self {
         Self {
-            credits: Arc::new(Mutex::new(initial_credits)),
+            credits: Arc::new(Mutex::new(CreditLedger {
+                available: initial_credits,
+                stats: CreditStats::default(),
+            })),
             initial_credits,
+            refill: None,
+            watermarks: None,
+            paused: Arc::new(Mutex::new(false)),
+            waiters: Arc::new(Mutex::new(VecDeque::new())),
         }
     }
 
+    /// Enables `pressure_signal`: pause at or below `low` credits, resume at
+    /// or above `high`. Panics unless `low < high`.
+    pub fn with_watermarks(mut self, low: u32, high: u32) -> Self {
+        assert!(low < high, "low watermark must be below the high watermark");
+        self.watermarks = Some((low, high));
+        self
+    }
+
+    /// Reports a pause/resume edge since the previous call.
+    ///
+    /// Hysteresis keeps the signal from flapping: once paused, dipping and
+    /// recovering within the band between the watermarks yields `None`; only
+    /// reaching `high` resumes, and only falling to `low` pauses again. Each
+    /// edge is reported once. Without watermarks this is always `None`.
+    pub fn pressure_signal(&mut self) -> PressureSignal {
+        let Some((low, high)) = self.watermarks else {
+            return PressureSignal::None;
+        };
+        let credits = self.available();
+        let mut paused = self.paused.lock().unwrap();
+        if !*paused && credits <= low {
+            *paused = true;
+            PressureSignal::Pause
+        } else if *paused && credits >= high {
+            *paused = false;
+            PressureSignal::Resume
+        } else {
+            PressureSignal::None
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.lock().unwrap()
+    }
+
+    /// A manager that gains `refill_amount` credits per elapsed `interval_ms`,
+    /// applied by `tick`. The first `tick` starts the clock.
+    pub fn with_auto_refill(initial_credits: u32, refill_amount: u32, interval_ms: u64) -> Self {
+        Self {
+            refill: Some(Arc::new(Mutex::new(AutoRefill {
+                amount: refill_amount,
+                interval_ms: interval_ms.max(1),
+                cap: None,
+                last_refill_ms: None,
+            }))),
+            ..Self::new(initial_credits)
+        }
+    }
+
+    /// Upper bound for automatic refills; manual `add` is not capped.
+    pub fn with_refill_cap(self, cap: u32) -> Self {
+        if let Some(refill) = &self.refill {
+            refill.lock().unwrap().cap = Some(cap);
+        }
+        self
+    }
+
+    /// Applies every refill interval elapsed since the last one, so a late
+    /// tick grants several intervals' worth at once. Returns the credits added.
+    pub fn tick(&mut self, now_ms: u64) -> u32 {
+        let Some(refill) = &self.refill else {
+            return 0;
+        };
+        let mut refill = refill.lock().unwrap();
+        let Some(last) = refill.last_refill_ms else {
+            refill.last_refill_ms = Some(now_ms);
+            return 0;
+        };
+
+        let intervals = now_ms.saturating_sub(last) / refill.interval_ms;
+        if intervals == 0 {
+            return 0;
+        }
+        refill.last_refill_ms = Some(last + intervals * refill.interval_ms);
+
+        let mut ledger = self.credits.lock().unwrap();
+        let grant = u32::try_from(intervals).unwrap_or(u32::MAX).saturating_mul(refill.amount);
+        let cap = refill.cap.unwrap_or(u32::MAX);
+        let available = ledger.available;
+        let added = available.saturating_add(grant).min(cap).max(available) - available;
+        ledger.give(added);
+        self.grant_waiters(&mut ledger);
+        added
+    }
+
     pub fn consume(&mut self, amount: u32) -> bool {
-        let mut credits = self.credits.lock().unwrap();
-        if *credits >= amount {
-            *credits -= amount;
+        let mut ledger = self.credits.lock().unwrap();
+        if ledger.available >= amount {
+            ledger.take(amount);
             true
         } else {
+            ledger.stats.consume_failures += 1;
             false
         }
     }
 
+    /// Takes up to `max` credits, as many as are available, and returns how
+    /// many were taken. Never counts as a consume failure.
+    pub fn consume_up_to(&mut self, max: u32) -> u32 {
+        let mut ledger = self.credits.lock().unwrap();
+        let amount = max.min(ledger.available);
+        ledger.take(amount);
+        amount
+    }
+
+    /// Takes every available credit, returning how many; counted in `stats`
+    /// like any other consumption.
+    pub fn consume_all(&mut self) -> u32 {
+        self.consume_up_to(u32::MAX)
+    }
+
+    /// Zeroes the balance and returns what it was. Unlike `consume_all` the
+    /// discarded credits are not counted as consumed.
+    pub fn drain(&mut self) -> u32 {
+        std::mem::take(&mut self.credits.lock().unwrap().available)
+    }
+
+    /// Waits until `amount` credits can be taken, then takes them. Resolves to
+    /// `false` only if every handle to this manager is dropped first.
+    ///
+    /// Waiters are served strictly in call order: a large request at the head
+    /// holds back smaller ones behind it, so no waiter starves. The place in
+    /// line is taken when this is called, not when the future is first
+    /// polled; dropping the future gives it up. Synchronous `consume` does not
+    /// queue and may take credits ahead of waiters.
+    pub fn consume_async(&self, amount: u32) -> impl Future<Output = bool> + 'static {
+        let pending = {
+            let mut ledger = self.credits.lock().unwrap();
+            let mut waiters = self.waiters.lock().unwrap();
+            if waiters.is_empty() && ledger.available >= amount {
+                ledger.take(amount);
+                None
+            } else {
+                let (grant, granted) = oneshot::channel();
+                waiters.push_back(CreditWaiter { amount, grant });
+                Some(granted)
+            }
+        };
+        async move {
+            match pending {
+                Some(granted) => granted.await.is_ok(),
+                None => true,
+            }
+        }
+    }
+
+    /// Number of `consume_async` calls still waiting for credits.
+    pub fn pending_waiters(&self) -> usize {
+        let mut waiters = self.waiters.lock().unwrap();
+        waiters.retain(|waiter| !waiter.grant.is_canceled());
+        waiters.len()
+    }
+
+    // Hands credits to queued waiters in FIFO order until the head no longer
+    // fits. Callers hold the credits lock, which keeps the lock order
+    // credits -> waiters everywhere.
+    fn grant_waiters(&self, ledger: &mut CreditLedger) {
+        let mut waiters = self.waiters.lock().unwrap();
+        while let Some(waiter) = waiters.front() {
+            if waiter.grant.is_canceled() {
+                waiters.pop_front();
+                continue;
+            }
+            if ledger.available < waiter.amount {
+                break;
+            }
+            let waiter = waiters.pop_front().unwrap();
+            if waiter.grant.send(()).is_ok() {
+                ledger.take(waiter.amount);
+            }
+        }
+    }
+
     pub fn add(&mut self, amount: u32) {
-        let mut credits = self.credits.lock().unwrap();
-        *credits = (*credits).saturating_add(amount);
+        let mut ledger = self.credits.lock().unwrap();
+        ledger.give(amount);
+        self.grant_waiters(&mut ledger);
     }
 
     pub fn available(&self) -> u32 {
-        *self.credits.lock().unwrap()
+        self.credits.lock().unwrap().available
     }
 
+    /// Restores the initial balance; the counters in `stats` keep running.
     pub fn reset(&mut self) {
-        let mut credits = self.credits.lock().unwrap();
-        *credits = self.initial_credits;
+        let mut ledger = self.credits.lock().unwrap();
+        ledger.available = self.initial_credits;
+        self.grant_waiters(&mut ledger);
+    }
+
+    pub fn stats(&self) -> CreditStats {
+        self.credits.lock().unwrap().stats
     }
 
     pub fn is_exhausted(&self) -> bool {
-        *self.credits.lock().unwrap() == 0
+        self.available() == 0
     }
 
     pub fn has_credits(&self) -> bool {
-        *self.credits.lock().unwrap() > 0
+        self.available() > 0
     }
 }
 
 pub struct MultiStreamCreditManager {
     streams: Arc<Mutex<HashMap<String, CreditManager>>>,
+    weights: Arc<Mutex<HashMap<String, u32>>>,
     default_credits: u32,
+    rebalance_threshold: u32,
 }
 
 impl MultiStreamCreditManager {
     pub fn new(default_credits: u32) -> Self {
         Self {
             streams: Arc::new(Mutex::new(HashMap::new())),
+            weights: Arc::new(Mutex::new(HashMap::new())),
             default_credits,
+            rebalance_threshold: default_credits,
         }
     }
 
+    /// Registers a stream with weight 1.
     pub fn register_stream(&mut self, stream_id: String, initial_credits: Option<u32>) {
         let mut streams = self.streams.lock().unwrap();
         let credits = initial_credits.unwrap_or(self.default_credits);
+        self.weights.lock().unwrap().insert(stream_id.clone(), 1);
         streams.insert(stream_id, CreditManager::new(credits));
     }
 
+    /// Registers a stream with the default credits and a `distribute` weight.
+    pub fn register_stream_weighted(&mut self, stream_id: String, weight: u32) {
+        self.register_stream(stream_id.clone(), None);
+        self.weights.lock().unwrap().insert(stream_id, weight);
+    }
+
     pub fn unregister_stream(&mut self, stream_id: &str) {
         let mut streams = self.streams.lock().unwrap();
         streams.remove(stream_id);
+        self.weights.lock().unwrap().remove(stream_id);
+    }
+
+    pub fn total_weight(&self) -> u64 {
+        self.weights.lock().unwrap().values().map(|&w| u64::from(w)).sum()
+    }
+
+    /// Splits `total_credits` across registered streams in proportion to
+    /// their weights and returns each stream's grant. Rounding leftovers go
+    /// one at a time to the heaviest streams (ties by id), so the grants
+    /// always sum to `total_credits` unless every weight is zero.
+    pub fn distribute(&mut self, total_credits: u32) -> HashMap<String, u32> {
+        let mut streams = self.streams.lock().unwrap();
+        let weights = self.weights.lock().unwrap();
+        let total_weight: u64 = weights.values().map(|&w| u64::from(w)).sum();
+        if total_weight == 0 {
+            return HashMap::new();
+        }
+
+        let mut grants: HashMap<String, u32> = weights
+            .iter()
+            .map(|(id, &w)| (id.clone(), (u64::from(total_credits) * u64::from(w) / total_weight) as u32))
+            .collect();
+
+        let mut by_weight: Vec<(&String, u32)> =
+            weights.iter().filter(|(_, &w)| w > 0).map(|(id, &w)| (id, w)).collect();
+        by_weight.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        let remainder = total_credits - grants.values().sum::<u32>();
+        for (id, _) in by_weight.iter().cycle().take(remainder as usize) {
+            *grants.get_mut(*id).unwrap() += 1;
+        }
+
+        for (id, grant) in &grants {
+            if let Some(manager) = streams.get_mut(id) {
+                manager.add(*grant);
+            }
+        }
+        grants
     }
 
     pub fn consume(&mut self, stream_id: &str, amount: u32) -> bool {
@@ -89,6 +382,72 @@ impl MultiStreamCreditManager {
         }
     }
 
+    /// Moves `amount` credits from one stream to another. Fails, changing
+    /// nothing, if either stream is unknown or `from_id` lacks the credits.
+    pub fn borrow(&mut self, from_id: &str, to_id: &str, amount: u32) -> bool {
+        let mut streams = self.streams.lock().unwrap();
+        if from_id == to_id || !streams.contains_key(to_id) {
+            return false;
+        }
+        let consumed = streams.get_mut(from_id).is_some_and(|from| from.consume(amount));
+        if consumed {
+            streams.get_mut(to_id).unwrap().add(amount);
+        }
+        consumed
+    }
+
+    /// Credits a stream may hold before `rebalance` treats the excess as
+    /// surplus. Defaults to `default_credits`.
+    pub fn set_rebalance_threshold(&mut self, threshold: u32) {
+        self.rebalance_threshold = threshold;
+    }
+
+    /// Moves surplus credits (above the rebalance threshold) from the richest
+    /// streams to exhausted ones, splitting it evenly and filling no stream
+    /// past the threshold. The total number of credits is unchanged. Returns
+    /// the number of credits moved.
+    pub fn rebalance(&mut self) -> u32 {
+        let mut streams = self.streams.lock().unwrap();
+        let threshold = self.rebalance_threshold;
+
+        let mut exhausted: Vec<String> = streams
+            .iter()
+            .filter(|(_, m)| m.is_exhausted())
+            .map(|(id, _)| id.clone())
+            .collect();
+        let surplus: u64 = streams
+            .values()
+            .map(|m| u64::from(m.available().saturating_sub(threshold)))
+            .sum();
+        if exhausted.is_empty() || surplus == 0 {
+            return 0;
+        }
+        exhausted.sort();
+
+        let mut to_move = surplus.min(u64::from(threshold) * exhausted.len() as u64) as u32;
+        let total = to_move;
+
+        let mut donors: Vec<(String, u32)> = streams
+            .iter()
+            .filter(|(_, m)| m.available() > threshold)
+            .map(|(id, m)| (id.clone(), m.available() - threshold))
+            .collect();
+        donors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        for (id, surplus) in donors {
+            let take = surplus.min(to_move);
+            streams.get_mut(&id).unwrap().consume(take);
+            to_move -= take;
+        }
+
+        let share = total / exhausted.len() as u32;
+        let extra = total as usize % exhausted.len();
+        for (i, id) in exhausted.iter().enumerate() {
+            let grant = share + u32::from(i < extra);
+            streams.get_mut(id).unwrap().add(grant);
+        }
+        total
+    }
+
     pub fn available_credits(&self, stream_id: &str) -> Option<u32> {
         let streams = self.streams.lock().unwrap();
         streams.get(stream_id).map(|m| m.available())
@@ -96,7 +455,7 @@ impl MultiStreamCreditManager {
 
     pub fn is_stream_exhausted(&self, stream_id: &str) -> bool {
         let streams = self.streams.lock().unwrap();
-        streams.get(stream_id).map_or(true, |m| m.is_exhausted())
+        streams.get(stream_id).is_none_or(|m| m.is_exhausted())
     }
 
     pub fn active_streams(&self) -> Vec<String> {
@@ -108,6 +467,157 @@ impl MultiStreamCreditManager {
         let streams = self.streams.lock().unwrap();
         streams.values().map(|m| m.available()).sum()
     }
+
+    /// Counters for every registered stream. Credits moved by `borrow` and
+    /// `rebalance` count as consumed by the donor and added to the receiver.
+    pub fn stats_per_stream(&self) -> HashMap<String, CreditStats> {
+        let streams = self.streams.lock().unwrap();
+        streams.iter().map(|(id, m)| (id.clone(), m.stats())).collect()
+    }
+
+    /// A `WeightedFairScheduler` over the registered streams and their
+    /// current weights, visited in id order.
+    pub fn fair_scheduler(&self, quantum: u32) -> WeightedFairScheduler {
+        let mut weights: Vec<(String, u32)> =
+            self.weights.lock().unwrap().iter().map(|(id, &w)| (id.clone(), w)).collect();
+        weights.sort();
+        let mut scheduler = WeightedFairScheduler::new(quantum);
+        for (id, weight) in weights {
+            scheduler.register(&id, weight);
+        }
+        scheduler
+    }
+}
+
+/// Deficit round-robin over weighted streams: each visit grants a stream
+/// `weight * quantum` of budget, and it stays the pick while budget remains.
+/// Service is proportional to weight in the long run, and every stream with
+/// a non-zero weight is reached once per round, so none starves.
+///
+/// `next_stream` keeps returning the same stream until `record_served`
+/// spends its budget; cost is in whatever unit `quantum` is (items, bytes).
+pub struct WeightedFairScheduler {
+    streams: Vec<FairShare>,
+    // The stream being served; `None` before the first pick, so the first
+    // round starts at stream 0.
+    cursor: Option<usize>,
+    quantum: u32,
+}
+
+struct FairShare {
+    id: String,
+    weight: u32,
+    deficit: i64,
+}
+
+impl WeightedFairScheduler {
+    /// `quantum` is clamped to at least 1.
+    pub fn new(quantum: u32) -> Self {
+        Self {
+            streams: Vec::new(),
+            cursor: None,
+            quantum: quantum.max(1),
+        }
+    }
+
+    /// Adds a stream, or updates the weight of a registered one. Weight 0
+    /// streams are never picked.
+    pub fn register(&mut self, stream_id: &str, weight: u32) {
+        match self.streams.iter_mut().find(|s| s.id == stream_id) {
+            Some(share) => share.weight = weight,
+            None => self.streams.push(FairShare { id: stream_id.to_string(), weight, deficit: 0 }),
+        }
+    }
+
+    pub fn unregister(&mut self, stream_id: &str) {
+        if let Some(index) = self.streams.iter().position(|s| s.id == stream_id) {
+            self.streams.remove(index);
+            // Removing the stream being served hands the turn to the one
+            // after it, which then gets its budget like any other visit.
+            if let Some(cursor) = self.cursor.filter(|&cursor| index <= cursor) {
+                self.cursor = cursor.checked_sub(1);
+            }
+        }
+    }
+
+    /// The stream to serve next, or `None` if no stream has a weight.
+    pub fn next_stream(&mut self) -> Option<String> {
+        if self.streams.iter().all(|s| s.weight == 0) {
+            return None;
+        }
+        loop {
+            let current = self.cursor.and_then(|cursor| self.streams.get(cursor));
+            if let Some(share) = current.filter(|s| s.weight > 0 && s.deficit > 0) {
+                return Some(share.id.clone());
+            }
+            let next = self.cursor.map_or(0, |cursor| (cursor + 1) % self.streams.len());
+            self.cursor = Some(next);
+            let share = &mut self.streams[next];
+            share.deficit += i64::from(share.weight) * i64::from(self.quantum);
+        }
+    }
+
+    /// Charges `cost` to the stream's budget. Overspending carries into its
+    /// next rounds; unknown ids are ignored.
+    pub fn record_served(&mut self, stream_id: &str, cost: u32) {
+        if let Some(share) = self.streams.iter_mut().find(|s| s.id == stream_id) {
+            share.deficit -= i64::from(cost);
+        }
+    }
+}
+
+/// Credits tracked per resource (e.g. "bytes" and "messages") with
+/// all-or-nothing consumption across resources.
+#[derive(Clone, Default)]
+pub struct TypedCreditManager {
+    pools: Arc<Mutex<HashMap<String, u32>>>,
+}
+
+impl TypedCreditManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a resource pool, replacing any existing balance for `label`.
+    pub fn register(&mut self, label: &str, initial_credits: u32) {
+        self.pools.lock().unwrap().insert(label.to_string(), initial_credits);
+    }
+
+    /// Takes every requested amount, or nothing if any resource is unknown or
+    /// short. Repeated labels are summed before checking, so the request is
+    /// validated as a whole under a single lock.
+    pub fn consume(&mut self, request: &[(&str, u32)]) -> bool {
+        let mut pools = self.pools.lock().unwrap();
+        let mut needed: HashMap<&str, u64> = HashMap::new();
+        for &(label, amount) in request {
+            *needed.entry(label).or_insert(0) += u64::from(amount);
+        }
+        let sufficient = needed
+            .iter()
+            .all(|(label, &amount)| pools.get(*label).is_some_and(|&credits| u64::from(credits) >= amount));
+        if !sufficient {
+            return false;
+        }
+        for (label, amount) in needed {
+            *pools.get_mut(label).unwrap() -= amount as u32;
+        }
+        true
+    }
+
+    /// Credits an existing resource; unknown labels are ignored.
+    pub fn add(&mut self, label: &str, amount: u32) {
+        if let Some(credits) = self.pools.lock().unwrap().get_mut(label) {
+            *credits = credits.saturating_add(amount);
+        }
+    }
+
+    pub fn available(&self, label: &str) -> Option<u32> {
+        self.pools.lock().unwrap().get(label).copied()
+    }
+
+    pub fn resources(&self) -> Vec<String> {
+        self.pools.lock().unwrap().keys().cloned().collect()
+    }
 }
 
 pub struct AdaptiveBackpressure {
@@ -116,6 +626,7 @@ pub struct AdaptiveBackpressure {
     min_rate: f64,
     max_rate: f64,
     alpha: f64,
+    circuit_breaker: Option<Arc<Mutex<CircuitBreaker>>>,
 }
 
 impl AdaptiveBackpressure {
@@ -131,9 +642,22 @@ impl AdaptiveBackpressure {
             min_rate,
             max_rate,
             alpha: 0.2,
+            circuit_breaker: None,
         }
     }
 
+    /// Gates `should_throttle` on `breaker`: while it is open every send is
+    /// throttled. Record outcomes and call `allow_request` through
+    /// `circuit_breaker()` so it can move to half-open and close again.
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(Arc::new(Mutex::new(breaker)));
+        self
+    }
+
+    pub fn circuit_breaker(&self) -> Option<Arc<Mutex<CircuitBreaker>>> {
+        self.circuit_breaker.clone()
+    }
+
     pub fn update(&mut self, observed_latency_ms: f64) {
         let mut rate = self.current_rate.lock().unwrap();
         
@@ -157,45 +681,155 @@ impl AdaptiveBackpressure {
         }
     }
 
+    pub fn should_throttle(&self, current_throughput: f64) -> bool {
+        let circuit_open = self.circuit_breaker.as_ref()
+            .is_some_and(|breaker| breaker.lock().unwrap().state() == CircuitState::Open);
+        circuit_open || current_throughput > self.get_rate()
+    }
+}
+
+/// PID alternative to `AdaptiveBackpressure` with the same `update`/`get_rate`
+/// interface.
+///
+/// The error is the latency shortfall normalized by the target, as in
+/// `AdaptiveBackpressure`. The controller output moves the rate away from
+/// `initial_rate` in units of the rate range, so the gains do not depend on
+/// the absolute rate: `rate = initial + (max - min) * (kp*e + ki*∫e + kd*Δe)`.
+/// The integral is clamped to `±integral_limit` so a long saturated period
+/// does not wind it up.
+pub struct PidBackpressure {
+    current_rate: Arc<Mutex<f64>>,
+    initial_rate: f64,
+    target_latency_ms: f64,
+    min_rate: f64,
+    max_rate: f64,
+    kp: f64,
+    ki: f64,
+    kd: f64,
+    integral_limit: f64,
+    integral: f64,
+    previous_error: Option<f64>,
+}
+
+impl PidBackpressure {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        initial_rate: f64,
+        target_latency_ms: f64,
+        min_rate: f64,
+        max_rate: f64,
+        kp: f64,
+        ki: f64,
+        kd: f64,
+    ) -> Self {
+        Self {
+            current_rate: Arc::new(Mutex::new(initial_rate)),
+            initial_rate,
+            target_latency_ms,
+            min_rate,
+            max_rate,
+            kp,
+            ki,
+            kd,
+            integral_limit: 10.0,
+            integral: 0.0,
+            previous_error: None,
+        }
+    }
+
+    pub fn with_integral_limit(mut self, integral_limit: f64) -> Self {
+        self.integral_limit = integral_limit;
+        self
+    }
+
+    pub fn update(&mut self, observed_latency_ms: f64) {
+        let error = (self.target_latency_ms - observed_latency_ms) / self.target_latency_ms;
+        self.integral = (self.integral + error).clamp(-self.integral_limit, self.integral_limit);
+        let derivative = self.previous_error.map_or(0.0, |previous| error - previous);
+        self.previous_error = Some(error);
+
+        let output = self.kp * error + self.ki * self.integral + self.kd * derivative;
+        let new_rate = self.initial_rate + (self.max_rate - self.min_rate) * output;
+        *self.current_rate.lock().unwrap() = new_rate.max(self.min_rate).min(self.max_rate);
+    }
+
+    pub fn get_rate(&self) -> f64 {
+        *self.current_rate.lock().unwrap()
+    }
+
+    pub fn get_delay_ms(&self) -> u64 {
+        let rate = self.get_rate();
+        if rate > 0.0 {
+            (1000.0 / rate) as u64
+        } else {
+            u64::MAX
+        }
+    }
+
     pub fn should_throttle(&self, current_throughput: f64) -> bool {
         current_throughput > self.get_rate()
     }
+
+    /// Clears the integral and derivative history and restores `initial_rate`.
+    pub fn reset(&mut self) {
+        self.integral = 0.0;
+        self.previous_error = None;
+        *self.current_rate.lock().unwrap() = self.initial_rate;
+    }
 }
 
+/// Admits at most `max_events` per sliding window of `window_size_ms`.
+///
+/// Timestamps should not go backwards; one earlier than the latest admitted
+/// event is treated as happening at that event's time, so a late or skewed
+/// clock reading never reopens an old window.
 pub struct WindowedRateLimiter {
     window_size_ms: u64,
     max_events: u32,
+    // admitted timestamps, oldest first
     events: Arc<Mutex<Vec<u64>>>,
 }
 
 impl WindowedRateLimiter {
-    pub fn new(window_size_ms: u64, max_events: u32) -> Self {
-        Self {
+    /// `window_size_ms` must be positive.
+    pub fn new(window_size_ms: u64, max_events: u32) -> Result<Self, String> {
+        if window_size_ms == 0 {
+            return Err("window_size_ms must be greater than 0".to_string());
+        }
+        Ok(Self {
             window_size_ms,
             max_events,
             events: Arc::new(Mutex::new(Vec::new())),
-        }
+        })
+    }
+
+    // `timestamp_ms`, or the latest event if that is later.
+    fn now(events: &[u64], timestamp_ms: u64) -> u64 {
+        events.last().map_or(timestamp_ms, |&latest| timestamp_ms.max(latest))
     }
 
     pub fn try_acquire(&mut self, timestamp_ms: u64) -> bool {
         let mut events = self.events.lock().unwrap();
-        
-        let cutoff = timestamp_ms.saturating_sub(self.window_size_ms);
+        let now = Self::now(&events, timestamp_ms);
+
+        let cutoff = now.saturating_sub(self.window_size_ms);
         events.retain(|&t| t > cutoff);
         
         if events.len() < self.max_events as usize {
-            events.push(timestamp_ms);
+            events.push(now);
             true
         } else {
             false
         }
     }
 
+    /// Events per second over the window ending at `timestamp_ms`.
     pub fn current_rate(&self, timestamp_ms: u64) -> f64 {
         let events = self.events.lock().unwrap();
-        let cutoff = timestamp_ms.saturating_sub(self.window_size_ms);
+        let cutoff = Self::now(&events, timestamp_ms).saturating_sub(self.window_size_ms);
         let recent_events = events.iter().filter(|&&t| t > cutoff).count();
         
+        // `new` rejects a zero window, so this never divides by zero.
         (recent_events as f64 * 1000.0) / self.window_size_ms as f64
     }
 
@@ -203,4 +837,223 @@ impl WindowedRateLimiter {
         let mut events = self.events.lock().unwrap();
         events.clear();
     }
-}
\ No newline at end of file
+}
+
+/// Leaky-bucket shaper: each submission adds one unit to a bucket that
+/// drains at `rate_per_sec` and holds at most `capacity`. Unlike
+/// `AdaptiveBackpressure` it answers with an exact wait, giving a
+/// deterministic emission schedule. Starts empty; drains lazily on each call.
+pub struct LeakyBucket {
+    rate_per_sec: f64,
+    capacity: f64,
+    level: f64,
+    last_leak_ms: u64,
+}
+
+impl LeakyBucket {
+    pub fn new(rate_per_sec: f64, capacity: f64) -> Self {
+        Self {
+            rate_per_sec,
+            capacity,
+            level: 0.0,
+            last_leak_ms: 0,
+        }
+    }
+
+    fn leak(&mut self, now_ms: u64) {
+        let elapsed_ms = now_ms.saturating_sub(self.last_leak_ms);
+        self.level = (self.level - elapsed_ms as f64 * self.rate_per_sec / 1000.0).max(0.0);
+        self.last_leak_ms = self.last_leak_ms.max(now_ms);
+    }
+
+    /// Admits one item, or returns the milliseconds to wait before it fits.
+    pub fn submit(&mut self, now_ms: u64) -> Result<(), u64> {
+        self.submit_burst(now_ms, 1)
+    }
+
+    /// Admits `items` at once, or none of them: the `Err` is the wait until
+    /// all fit, `u64::MAX` if they never will (more than `capacity`, or no
+    /// drain).
+    pub fn submit_burst(&mut self, now_ms: u64, items: u32) -> Result<(), u64> {
+        self.leak(now_ms);
+        let needed = f64::from(items);
+        let overflow = self.level + needed - self.capacity;
+        if overflow <= 0.0 {
+            self.level += needed;
+            Ok(())
+        } else if needed > self.capacity || self.rate_per_sec <= 0.0 {
+            Err(u64::MAX)
+        } else {
+            Err((overflow * 1000.0 / self.rate_per_sec).ceil() as u64)
+        }
+    }
+
+    pub fn level(&mut self, now_ms: u64) -> f64 {
+        self.leak(now_ms);
+        self.level
+    }
+}
+
+/// Token-bucket limiter: up to `capacity` tokens, refilled continuously at
+/// `refill_per_sec`. Allows bursts of `capacity` while pacing the long-run
+/// rate. Starts full; refills lazily on each call.
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_sec: f64,
+    tokens: f64,
+    last_refill_ms: u64,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: f64, refill_per_sec: f64) -> Self {
+        Self {
+            capacity,
+            refill_per_sec,
+            tokens: capacity,
+            last_refill_ms: 0,
+        }
+    }
+
+    fn refill(&mut self, timestamp_ms: u64) {
+        let elapsed_ms = timestamp_ms.saturating_sub(self.last_refill_ms);
+        self.tokens = (self.tokens + elapsed_ms as f64 * self.refill_per_sec / 1000.0).min(self.capacity);
+        self.last_refill_ms = self.last_refill_ms.max(timestamp_ms);
+    }
+
+    pub fn try_acquire(&mut self, timestamp_ms: u64, cost: f64) -> bool {
+        self.refill(timestamp_ms);
+        if self.tokens >= cost {
+            self.tokens -= cost;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn available_tokens(&mut self, timestamp_ms: u64) -> f64 {
+        self.refill(timestamp_ms);
+        self.tokens
+    }
+
+    /// Milliseconds after the last refill until `cost` tokens are available;
+    /// `u64::MAX` if they never will be (cost above capacity, or no refill).
+    pub fn time_until_available(&self, cost: f64) -> u64 {
+        let missing = cost - self.tokens;
+        if missing <= 0.0 {
+            0
+        } else if cost > self.capacity || self.refill_per_sec <= 0.0 {
+            u64::MAX
+        } else {
+            (missing * 1000.0 / self.refill_per_sec).ceil() as u64
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests flow; consecutive failures are counted.
+    Closed,
+    /// Requests are refused until the cooldown has elapsed.
+    Open,
+    /// A limited number of probe requests decide whether to close or reopen.
+    HalfOpen,
+}
+
+/// Closed → Open after `failure_threshold` consecutive failures; Open →
+/// HalfOpen once `cooldown_ms` has passed; HalfOpen admits
+/// `half_open_probes` requests and closes when all of them succeed, or
+/// reopens on the first failure.
+///
+/// The cooldown starts at the time of the last `allow_request` call, since
+/// outcomes are recorded without a timestamp.
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    cooldown_ms: u64,
+    half_open_probes: u32,
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at_ms: u64,
+    probes_started: u32,
+    probes_succeeded: u32,
+    last_request_ms: u64,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, cooldown_ms: u64, half_open_probes: u32) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            cooldown_ms,
+            half_open_probes: half_open_probes.max(1),
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at_ms: 0,
+            probes_started: 0,
+            probes_succeeded: 0,
+            last_request_ms: 0,
+        }
+    }
+
+    pub fn allow_request(&mut self, now_ms: u64) -> bool {
+        self.last_request_ms = now_ms;
+        if self.state == CircuitState::Open
+            && now_ms.saturating_sub(self.opened_at_ms) >= self.cooldown_ms
+        {
+            self.state = CircuitState::HalfOpen;
+            self.probes_started = 0;
+            self.probes_succeeded = 0;
+        }
+
+        match self.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen => {
+                if self.probes_started < self.half_open_probes {
+                    self.probes_started += 1;
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        match self.state {
+            CircuitState::Closed => self.consecutive_failures = 0,
+            CircuitState::HalfOpen => {
+                self.probes_succeeded += 1;
+                if self.probes_succeeded >= self.half_open_probes {
+                    self.state = CircuitState::Closed;
+                    self.consecutive_failures = 0;
+                }
+            }
+            CircuitState::Open => {}
+        }
+    }
+
+    pub fn record_failure(&mut self) {
+        match self.state {
+            CircuitState::Closed => {
+                self.consecutive_failures += 1;
+                if self.consecutive_failures >= self.failure_threshold {
+                    self.open();
+                }
+            }
+            CircuitState::HalfOpen => self.open(),
+            CircuitState::Open => {}
+        }
+    }
+
+    fn open(&mut self) {
+        self.state = CircuitState::Open;
+        self.opened_at_ms = self.last_request_ms;
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.state
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+}
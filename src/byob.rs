@@ -1,6 +1,11 @@
 use wasm_bindgen::prelude::*;
 use js_sys::{Uint8Array, ArrayBuffer, Function, Object};
-use web_sys::{ReadableStream};
+use js_sys::{Float32Array, Float64Array, Int32Array, WebAssembly};
+use wasm_bindgen_futures::JsFuture;
+use futures::future::{select, Either};
+use gloo_timers::future::TimeoutFuture;
+use web_sys::{QueuingStrategy, ReadableByteStreamController, ReadableStream, ReadableStreamByobReader};
+use std::collections::VecDeque;
 use std::sync::Arc;
 use std::sync::Mutex;
 
@@ -8,9 +13,13 @@ use std::sync::Mutex;
 pub struct BYOBStreamReader {
     stream: ReadableStream,
     buffer_size: usize,
-    reusable_buffer: Arc<Mutex<Option<ArrayBuffer>>>,
+    pool: Arc<Mutex<BufferPool<ArrayBuffer>>>,
+    read_timeout_ms: Option<u32>,
 }
 
+// Buffers kept by a reader's pool when nothing else is configured.
+const READER_POOL_BUFFERS: usize = 4;
+
 #[wasm_bindgen]
 impl BYOBStreamReader {
     #[wasm_bindgen(constructor)]
@@ -18,31 +27,120 @@ impl BYOBStreamReader {
         Ok(BYOBStreamReader {
             stream,
             buffer_size,
-            reusable_buffer: Arc::new(Mutex::new(None)),
+            pool: Arc::new(Mutex::new(BufferPool::new(buffer_size, READER_POOL_BUFFERS))),
+            read_timeout_ms: None,
         })
     }
 
+    /// Reads directly into `buffer` and resolves to `{ value, done }`.
+    ///
+    /// The read transfers `buffer`'s `ArrayBuffer`, so the passed view is
+    /// detached afterwards; `value` is a new view over the same memory whose
+    /// `byteLength` is the number of bytes actually written, which may be less
+    /// than requested. The stream must be a byte stream (`type: 'bytes'`).
+    /// Uses the timeout set with `setReadTimeout`, if any.
     #[wasm_bindgen(js_name = readInto)]
     pub async fn read_into(&mut self, buffer: Uint8Array) -> Result<JsValue, JsValue> {
-        // Simplified implementation without BYOB reader
-        let reader = self.stream.get_reader();
-        let result = reader.read().await?;
+        self.read_into_with_timeout(buffer, self.read_timeout_ms).await
+    }
+
+    /// `readInto` that rejects if no chunk arrives within `timeout_ms`
+    /// (`undefined` waits forever). On timeout the reader lock is released so
+    /// the stream can still be read, but the bytes of `buffer` are lost.
+    #[wasm_bindgen(js_name = readIntoWithTimeout)]
+    pub async fn read_into_with_timeout(
+        &mut self,
+        buffer: Uint8Array,
+        timeout_ms: Option<u32>,
+    ) -> Result<JsValue, JsValue> {
+        if self.stream.locked() {
+            return Err(JsValue::from_str("stream is locked by another reader"));
+        }
+        let reader = ReadableStreamByobReader::new(&self.stream).map_err(|_| {
+            JsValue::from_str("BYOB reads require a byte stream (underlying source type: 'bytes')")
+        })?;
+        let read = JsFuture::from(reader.read_with_array_buffer_view(&buffer));
+        let result = match timeout_ms {
+            None => read.await,
+            Some(ms) => match select(read, TimeoutFuture::new(ms)).await {
+                Either::Left((result, _)) => result,
+                Either::Right(_) => Err(JsValue::from_str(&format!("read timed out after {} ms", ms))),
+            },
+        };
         reader.release_lock();
-        Ok(result)
+        result
     }
 
+    /// `readInto` with an internal buffer that is recycled between reads. The
+    /// returned `value` aliases that buffer, so consume it before the next read.
     #[wasm_bindgen(js_name = readWithReusableBuffer)]
     pub async fn read_with_reusable_buffer(&mut self) -> Result<JsValue, JsValue> {
-        let buffer = {
-            let mut buf_lock = self.reusable_buffer.lock().unwrap();
-            if buf_lock.is_none() {
-                *buf_lock = Some(ArrayBuffer::new(self.buffer_size as u32));
+        let buffer = self.take_buffer(self.buffer_size);
+        let view = Uint8Array::new_with_byte_offset_and_length(&buffer, 0, self.buffer_size as u32);
+        let result = self.read_into(view).await?;
+
+        // The read transferred the buffer; return the one now backing `value`
+        // to the pool. After cancellation `value` is undefined and the pool
+        // allocates a fresh buffer next time.
+        let value = js_sys::Reflect::get(&result, &"value".into())?;
+        if let Some(view) = value.dyn_ref::<Uint8Array>() {
+            self.recycle_buffer(view.buffer());
+        }
+        Ok(result)
+    }
+
+    /// Reads exactly `n` bytes, issuing as many BYOB reads as needed, and
+    /// rejects with "unexpected EOF" if the stream closes first.
+    ///
+    /// Bytes accumulate in a pooled buffer and the result is a copy, so
+    /// it stays valid across later reads. Each iteration awaits the stream's
+    /// next chunk rather than polling, so a slow producer is never spun on.
+    #[wasm_bindgen(js_name = readExact)]
+    pub async fn read_exact(&mut self, n: usize) -> Result<Uint8Array, JsValue> {
+        let mut buffer = self.take_buffer(n);
+        let mut filled = 0;
+
+        while filled < n {
+            let view = Uint8Array::new_with_byte_offset_and_length(
+                &buffer,
+                filled as u32,
+                (n - filled) as u32,
+            );
+            let result = self.read_into(view).await?;
+            let done = js_sys::Reflect::get(&result, &"done".into())?
+                .as_bool()
+                .unwrap_or(true);
+            let value = js_sys::Reflect::get(&result, &"value".into())?;
+
+            if let Some(view) = value.dyn_ref::<Uint8Array>() {
+                buffer = view.buffer();
+                filled += view.byte_length() as usize;
             }
-            buf_lock.clone().unwrap()
-        };
+            if done && filled < n {
+                return Err(JsValue::from_str(&format!(
+                    "unexpected EOF: read {} of {} bytes",
+                    filled, n
+                )));
+            }
+        }
 
-        let view = Uint8Array::new(&buffer);
-        self.read_into(view).await
+        let bytes = Uint8Array::new_with_byte_offset_and_length(&buffer, 0, n as u32).slice(0, n as u32);
+        self.recycle_buffer(buffer);
+        Ok(bytes)
+    }
+
+    /// Default timeout for `readInto`, `readWithReusableBuffer` and
+    /// `readExact` reads; `undefined` (the default) disables it.
+    #[wasm_bindgen(js_name = setReadTimeout)]
+    pub fn set_read_timeout(&mut self, timeout_ms: Option<u32>) {
+        self.read_timeout_ms = timeout_ms;
+    }
+
+    /// Makes this reader draw from and return to `other`'s buffer pool, so
+    /// several readers share one set of allocations.
+    #[wasm_bindgen(js_name = sharePoolWith)]
+    pub fn share_pool_with(&mut self, other: &BYOBStreamReader) {
+        self.pool = other.pool.clone();
     }
 
     #[wasm_bindgen]
@@ -52,11 +150,76 @@ impl BYOBStreamReader {
     }
 }
 
+impl BYOBStreamReader {
+    // A pooled buffer if it holds at least `min_len` bytes, else a new one.
+    fn take_buffer(&self, min_len: usize) -> ArrayBuffer {
+        let buffer = self.pool.lock().unwrap().acquire();
+        if buffer.byte_length() as usize >= min_len {
+            buffer
+        } else {
+            ArrayBuffer::new(min_len as u32)
+        }
+    }
+
+    fn recycle_buffer(&self, buffer: ArrayBuffer) {
+        self.pool.lock().unwrap().release(buffer);
+    }
+}
+
 #[wasm_bindgen]
 pub struct BYOBStreamController {
     chunk_size: usize,
     high_water_mark: usize,
-    pending_buffers: Arc<Mutex<Vec<ArrayBuffer>>>,
+    source: Arc<Mutex<SourceState>>,
+}
+
+// State shared between the controller and the stream's underlying source.
+#[derive(Default)]
+struct SourceState {
+    pending_buffers: VecDeque<Vec<u8>>,
+    close_requested: bool,
+    // Set once the stream starts; cleared after it closes or is cancelled.
+    controller: Option<ReadableByteStreamController>,
+}
+
+impl SourceState {
+    // Moves pending bytes into the stream: straight into the reader's buffer
+    // when a BYOB request is outstanding, otherwise onto the stream's queue
+    // while it is below its high-water mark. Returns whether anything moved.
+    fn deliver(&mut self) -> Result<bool, JsValue> {
+        let Some(controller) = self.controller.clone() else {
+            return Ok(false);
+        };
+        let mut delivered = false;
+
+        while let Some(chunk) = self.pending_buffers.front_mut() {
+            if let Some(request) = controller.byob_request() {
+                let view: Uint8Array = request.view().unwrap_throw().unchecked_into();
+                let n = chunk.len().min(view.length() as usize);
+                view.subarray(0, n as u32).copy_from(&chunk[..n]);
+                request.respond_with_u32(n as u32)?;
+                chunk.drain(..n);
+                if chunk.is_empty() {
+                    self.pending_buffers.pop_front();
+                }
+            } else if controller.desired_size().unwrap_or(0.0) > 0.0 {
+                let chunk = self.pending_buffers.pop_front().unwrap_throw();
+                controller.enqueue_with_js_u8_array(&Uint8Array::from(&chunk[..]))?;
+            } else {
+                break;
+            }
+            delivered = true;
+        }
+
+        if self.close_requested && self.pending_buffers.is_empty() {
+            self.controller = None;
+            controller.close()?;
+            if let Some(request) = controller.byob_request() {
+                request.respond_with_u32(0)?;
+            }
+        }
+        Ok(delivered)
+    }
 }
 
 #[wasm_bindgen]
@@ -66,12 +229,18 @@ impl BYOBStreamController {
         Self {
             chunk_size,
             high_water_mark,
-            pending_buffers: Arc::new(Mutex::new(Vec::new())),
+            source: Arc::new(Mutex::new(SourceState::default())),
         }
     }
 
+    /// Creates a byte stream fed from this controller.
+    ///
+    /// Each `pull` first drains bytes queued with `enqueue`, filling the
+    /// reader's BYOB buffer directly. Only when nothing is queued is
+    /// `pull_fn(controller)` called, to fill the request itself; its return
+    /// value (e.g. a promise) is handed back to the stream.
     #[wasm_bindgen(js_name = createReadableStream)]
-    pub fn create_readable_stream(&self, _pull_fn: Function) -> ReadableStream {
+    pub fn create_readable_stream(&self, pull_fn: Function) -> ReadableStream {
         let underlying_source = Object::new();
         
         js_sys::Reflect::set(
@@ -85,8 +254,69 @@ impl BYOBStreamController {
             &"autoAllocateChunkSize".into(),
             &JsValue::from(self.chunk_size as u32),
         ).unwrap();
-        
-        ReadableStream::new_with_underlying_source(&underlying_source).unwrap()
+
+        let source = self.source.clone();
+        let start = Closure::once_into_js(move |controller: ReadableByteStreamController| {
+            source.lock().unwrap().controller = Some(controller);
+        });
+        js_sys::Reflect::set(&underlying_source, &"start".into(), &start).unwrap();
+
+        let source = self.source.clone();
+        let pull = Closure::<dyn FnMut(ReadableByteStreamController) -> JsValue>::new(
+            move |controller: ReadableByteStreamController| {
+                // Responding to a BYOB request can call `pull` synchronously;
+                // the outer `deliver` is still draining, so there is nothing to do.
+                let Ok(mut state) = source.try_lock() else {
+                    return JsValue::UNDEFINED;
+                };
+                match state.deliver() {
+                    Ok(true) => JsValue::UNDEFINED,
+                    Ok(false) if state.close_requested || state.controller.is_none() => JsValue::UNDEFINED,
+                    Ok(false) => {
+                        drop(state);
+                        pull_fn.call1(&JsValue::NULL, &controller)
+                            .unwrap_or_else(|err| js_sys::Promise::reject(&err).into())
+                    }
+                    Err(err) => js_sys::Promise::reject(&err).into(),
+                }
+            },
+        );
+        js_sys::Reflect::set(&underlying_source, &"pull".into(), &pull.into_js_value()).unwrap();
+
+        let source = self.source.clone();
+        let cancel = Closure::<dyn FnMut(JsValue)>::new(move |_reason: JsValue| {
+            let mut state = source.lock().unwrap();
+            state.pending_buffers.clear();
+            state.controller = None;
+        });
+        js_sys::Reflect::set(&underlying_source, &"cancel".into(), &cancel.into_js_value()).unwrap();
+
+        let strategy = QueuingStrategy::new();
+        strategy.set_high_water_mark(self.high_water_mark as f64);
+        ReadableStream::new_with_underlying_source_and_strategy(&underlying_source, &strategy).unwrap()
+    }
+
+    /// Queues `chunk` for the stream, delivering it at once if a reader is
+    /// waiting.
+    pub fn enqueue(&self, chunk: &[u8]) -> Result<(), JsValue> {
+        let mut state = self.source.lock().unwrap();
+        if state.close_requested {
+            return Err(JsValue::from_str("stream is closing"));
+        }
+        state.pending_buffers.push_back(chunk.to_vec());
+        state.deliver().map(|_| ())
+    }
+
+    /// Closes the stream once every queued chunk has been read.
+    pub fn close(&self) -> Result<(), JsValue> {
+        let mut state = self.source.lock().unwrap();
+        state.close_requested = true;
+        state.deliver().map(|_| ())
+    }
+
+    #[wasm_bindgen(js_name = pendingBytes)]
+    pub fn pending_bytes(&self) -> usize {
+        self.source.lock().unwrap().pending_buffers.iter().map(Vec::len).sum()
     }
 
     #[wasm_bindgen(js_name = getChunkSize)]
@@ -100,59 +330,192 @@ impl BYOBStreamController {
     }
 }
 
-#[wasm_bindgen]
+fn memory_buffer() -> JsValue {
+    wasm_bindgen::memory()
+        .unchecked_into::<WebAssembly::Memory>()
+        .buffer()
+}
+
+/// A `Uint8Array` aliasing `buffer` inside WASM linear memory; nothing is copied.
+/// These helpers are for Rust code handing its own buffers to JS; they are not
+/// exported, since a slice passed in from JS is a temporary copy.
+///
+/// The view is only valid until the next call into WASM: any allocation may
+/// grow the memory, which detaches the old `ArrayBuffer` (the view then has
+/// length 0) and the slice itself may be freed or reused. Consume or copy the
+/// view immediately, or use `create_float32_view_checked` to detect staleness.
 pub fn create_zero_copy_view(buffer: &[u8]) -> Uint8Array {
-    unsafe {
-        let ptr = buffer.as_ptr() as u32;
-        let len = buffer.len() as u32;
-        
-        let memory = wasm_bindgen::memory().unchecked_ref::<js_sys::WebAssembly::Memory>();
-        let buffer = memory.buffer();
-        
-        Uint8Array::new_with_byte_offset_and_length(&buffer, ptr, len)
+    let ptr = buffer.as_ptr() as u32;
+    let len = buffer.len() as u32;
+    Uint8Array::new_with_byte_offset_and_length(&memory_buffer(), ptr, len)
+}
+
+/// A `Float32Array` aliasing `buffer` inside WASM linear memory; nothing is
+/// copied. Subject to the same lifetime rules as `create_zero_copy_view`;
+/// prefer `copy_float32_out` unless the copy is measurably expensive.
+pub fn create_float32_view(buffer: &[f32]) -> Float32Array {
+    let ptr = buffer.as_ptr() as u32;
+    let len = buffer.len() as u32;
+    Float32Array::new_with_byte_offset_and_length(&memory_buffer(), ptr, len)
+}
+
+/// A `Float64Array` aliasing `buffer` inside WASM linear memory. The byte
+/// offset is the slice's address; only the length is counted in elements.
+/// Subject to the same lifetime rules as `create_zero_copy_view`.
+pub fn create_float64_view(buffer: &[f64]) -> Float64Array {
+    let ptr = buffer.as_ptr() as u32;
+    let len = buffer.len() as u32;
+    Float64Array::new_with_byte_offset_and_length(&memory_buffer(), ptr, len)
+}
+
+/// An `Int32Array` aliasing `buffer` inside WASM linear memory. Subject to the
+/// same lifetime rules as `create_zero_copy_view`.
+pub fn create_int32_view(buffer: &[i32]) -> Int32Array {
+    let ptr = buffer.as_ptr() as u32;
+    let len = buffer.len() as u32;
+    Int32Array::new_with_byte_offset_and_length(&memory_buffer(), ptr, len)
+}
+
+/// Copies `data` into a freshly allocated JS `Float32Array`. The result owns
+/// its buffer, so it survives memory growth and can be transferred to a worker.
+pub fn copy_float32_out(data: &[f32]) -> Float32Array {
+    Float32Array::from(data)
+}
+
+/// Like `create_float32_view`, but wrapped with the byte offset and length
+/// captured at creation so the view can be checked before use.
+pub fn create_float32_view_checked(buffer: &[f32]) -> Float32View {
+    Float32View {
+        array: create_float32_view(buffer),
+        byte_offset: buffer.as_ptr() as u32,
+        length: buffer.len() as u32,
     }
 }
 
 #[wasm_bindgen]
-pub fn create_float32_view(buffer: &[f32]) -> js_sys::Float32Array {
-    unsafe {
-        let ptr = buffer.as_ptr() as u32;
-        let len = buffer.len() as u32;
-        
-        let memory = wasm_bindgen::memory().unchecked_ref::<js_sys::WebAssembly::Memory>();
-        let buffer = memory.buffer();
-        
-        js_sys::Float32Array::new_with_byte_offset_and_length(
-            &buffer,
-            ptr * 4,
-            len,
-        )
+pub struct Float32View {
+    array: Float32Array,
+    byte_offset: u32,
+    length: u32,
+}
+
+#[wasm_bindgen]
+impl Float32View {
+    /// False once WASM memory has grown and detached the view's buffer. This
+    /// cannot detect the slice being freed and reused without growth.
+    #[wasm_bindgen(js_name = isValid)]
+    pub fn is_valid(&self) -> bool {
+        Object::is(&self.array.buffer(), &memory_buffer())
+            && self.array.byte_offset() == self.byte_offset
+            && self.array.length() == self.length
+    }
+
+    /// The underlying view, or an error if it has been invalidated.
+    pub fn get(&self) -> Result<Float32Array, JsValue> {
+        if !self.is_valid() {
+            return Err(JsValue::from_str("view detached by WASM memory growth"));
+        }
+        Ok(self.array.clone())
     }
+
+    /// A detached copy of the view's contents; errors if it is already stale.
+    #[wasm_bindgen(js_name = toArray)]
+    pub fn to_array(&self) -> Result<Float32Array, JsValue> {
+        Ok(self.get()?.slice(0, self.length))
+    }
+
+    #[wasm_bindgen(getter, js_name = byteOffset)]
+    pub fn byte_offset(&self) -> u32 {
+        self.byte_offset
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+}
+
+/// A buffer type `BufferPool` can hand out and take back.
+pub trait PoolBuffer: Sized {
+    fn allocate(size: usize) -> Self;
+    /// Prepares a released buffer for reuse, or returns `false` to drop it.
+    fn recycle(&mut self, size: usize) -> bool;
 }
 
-pub struct BufferPool {
-    buffers: Vec<Vec<u8>>,
+impl PoolBuffer for Vec<u8> {
+    fn allocate(size: usize) -> Self {
+        vec![0u8; size]
+    }
+
+    // Contents are left as they are: readers overwrite them, so only a
+    // truncated tail is zero-filled back to `size`.
+    fn recycle(&mut self, size: usize) -> bool {
+        self.resize(size, 0);
+        true
+    }
+}
+
+impl PoolBuffer for ArrayBuffer {
+    fn allocate(size: usize) -> Self {
+        ArrayBuffer::new(size as u32)
+    }
+
+    // A transferred (detached) buffer reports a length of 0 and is dropped.
+    fn recycle(&mut self, size: usize) -> bool {
+        self.byte_length() as usize >= size
+    }
+}
+
+pub struct BufferPool<B = Vec<u8>> {
+    buffers: Vec<B>,
     buffer_size: usize,
     max_buffers: usize,
+    stats: PoolStats,
+}
+
+/// How often `BufferPool::acquire` reused a buffer (`hits`) or had to
+/// allocate one because the pool was empty (`misses`). Frequent misses mean
+/// `max_buffers` is too small for the number of buffers in flight.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize)]
+pub struct PoolStats {
+    pub hits: u64,
+    pub misses: u64,
 }
 
-impl BufferPool {
+impl<B: PoolBuffer> BufferPool<B> {
     pub fn new(buffer_size: usize, max_buffers: usize) -> Self {
         Self {
             buffers: Vec::with_capacity(max_buffers),
             buffer_size,
             max_buffers,
+            stats: PoolStats::default(),
         }
     }
 
-    pub fn acquire(&mut self) -> Vec<u8> {
-        self.buffers.pop().unwrap_or_else(|| vec![0u8; self.buffer_size])
+    pub fn acquire(&mut self) -> B {
+        match self.buffers.pop() {
+            Some(buffer) => {
+                self.stats.hits += 1;
+                buffer
+            }
+            None => {
+                self.stats.misses += 1;
+                B::allocate(self.buffer_size)
+            }
+        }
     }
 
-    pub fn release(&mut self, mut buffer: Vec<u8>) {
-        if self.buffers.len() < self.max_buffers {
-            buffer.clear();
-            buffer.resize(self.buffer_size, 0);
+    /// Allocates buffers until `n` (at most `capacity`) are available, so
+    /// the first acquires don't miss.
+    pub fn preallocate(&mut self, n: usize) {
+        let target = n.min(self.max_buffers);
+        while self.buffers.len() < target {
+            self.buffers.push(B::allocate(self.buffer_size));
+        }
+    }
+
+    pub fn release(&mut self, mut buffer: B) {
+        if self.buffers.len() < self.max_buffers && buffer.recycle(self.buffer_size) {
             self.buffers.push(buffer);
         }
     }
@@ -160,4 +523,78 @@ impl BufferPool {
     pub fn available(&self) -> usize {
         self.buffers.len()
     }
-}
\ No newline at end of file
+
+    /// The most buffers kept for reuse; extra releases are dropped.
+    pub fn capacity(&self) -> usize {
+        self.max_buffers
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        self.stats
+    }
+}
+
+/// `BufferPool` for JavaScript. Buffers live in WASM linear memory and are
+/// handed out as `Uint8Array` views, which are detached if WASM memory grows;
+/// copy out anything that must outlive the next call into WASM.
+#[wasm_bindgen]
+pub struct WasmBufferPool {
+    pool: BufferPool,
+    // Each leased buffer with the view handed out for it.
+    leased: Vec<(Vec<u8>, Uint8Array)>,
+}
+
+#[wasm_bindgen]
+impl WasmBufferPool {
+    #[wasm_bindgen(constructor)]
+    pub fn new(buffer_size: usize, max_buffers: usize) -> Self {
+        Self {
+            pool: BufferPool::new(buffer_size, max_buffers),
+            leased: Vec::new(),
+        }
+    }
+
+    pub fn acquire(&mut self) -> Uint8Array {
+        let buffer = self.pool.acquire();
+        // Growing `leased` after the view exists could grow memory and detach
+        // it before it is returned, so make room first.
+        self.leased.reserve(1);
+        let view = create_zero_copy_view(&buffer);
+        self.leased.push((buffer, view.clone()));
+        view
+    }
+
+    /// Returns a view obtained from `acquire` to the pool. The view is matched
+    /// by identity, so one detached by memory growth (reporting offset 0) is
+    /// still released; another live view over the same bytes works too.
+    pub fn release(&mut self, view: &Uint8Array) -> Result<(), JsValue> {
+        let offset = view.byte_offset() as usize;
+        let index = self
+            .leased
+            .iter()
+            .position(|(buffer, leased)| {
+                Object::is(leased, view) || (view.byte_length() > 0 && buffer.as_ptr() as usize == offset)
+            })
+            .ok_or_else(|| JsValue::from_str("view was not acquired from this pool"))?;
+        let (buffer, _) = self.leased.swap_remove(index);
+        self.pool.release(buffer);
+        Ok(())
+    }
+
+    pub fn available(&self) -> usize {
+        self.pool.available()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.pool.capacity()
+    }
+
+    pub fn preallocate(&mut self, n: usize) {
+        self.pool.preallocate(n);
+    }
+
+    /// `{ hits, misses }` of `acquire`.
+    pub fn stats(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.pool.stats()).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
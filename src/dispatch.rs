@@ -0,0 +1,549 @@
+//! Runtime selection between the SIMD kernels in `simd_ops` and scalar fallbacks.
+//!
+//! WebAssembly has no CPUID-style instruction, so a module cannot ask the engine
+//! which proposals it implements. Detection instead hands a tiny probe module
+//! (one function executing `i8x16.splat` + `i8x16.popcnt`) to
+//! `WebAssembly.validate`: engines without SIMD reject it. The answer is cached
+//! after the first call.
+//!
+//! The crate is not compiled with a global `+simd128`: only the kernels in
+//! `simd_ops` enable it, per function, so a build without the `simd` feature
+//! contains no SIMD instructions and loads on engines without SIMD.
+//!
+//! Validation is per module, though, so a build *with* the `simd` feature
+//! still fails to instantiate on such engines, even though the dispatcher
+//! would never call its kernels there. One `.wasm` that runs everywhere is not
+//! possible; hosts that must support older engines ship both builds and pick
+//! one, and the probe is then most useful for reporting.
+//! `set_simd_enabled(false)` forces the scalar paths, which is handy for
+//! benchmarking and for ruling out kernel bugs.
+
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+const UNKNOWN: u8 = 0;
+const UNAVAILABLE: u8 = 1;
+const AVAILABLE: u8 = 2;
+
+static SIMD_SUPPORT: AtomicU8 = AtomicU8::new(UNKNOWN);
+static SIMD_ENABLED: AtomicBool = AtomicBool::new(true);
+
+#[cfg(all(feature = "simd", target_arch = "wasm32"))]
+const SIMD_PROBE: [u8; 31] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic + version
+    0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7b, // type: () -> v128
+    0x03, 0x02, 0x01, 0x00, // func 0 uses type 0
+    0x0a, 0x0a, 0x01, 0x08, 0x00, 0x41, 0x00, 0xfd, 0x0f, 0xfd, 0x62, 0x0b, // i32.const 0; i8x16.splat; i8x16.popcnt
+];
+
+#[cfg(all(feature = "simd", target_arch = "wasm32"))]
+fn probe_simd() -> bool {
+    let bytes = js_sys::Uint8Array::from(&SIMD_PROBE[..]);
+    js_sys::WebAssembly::validate(&bytes.into()).unwrap_or(false)
+}
+
+// Native builds run the scalar fallbacks inside `simd_ops`, and builds without
+// the `simd` feature have no kernels to dispatch to.
+#[cfg(not(all(feature = "simd", target_arch = "wasm32")))]
+fn probe_simd() -> bool {
+    false
+}
+
+/// Whether the SIMD kernels are compiled in, supported by the engine and not
+/// disabled via `set_simd_enabled`.
+pub fn simd_available() -> bool {
+    if !SIMD_ENABLED.load(Ordering::Relaxed) {
+        return false;
+    }
+
+    match SIMD_SUPPORT.load(Ordering::Relaxed) {
+        AVAILABLE => true,
+        UNAVAILABLE => false,
+        _ => {
+            let supported = probe_simd();
+            let state = if supported { AVAILABLE } else { UNAVAILABLE };
+            SIMD_SUPPORT.store(state, Ordering::Relaxed);
+            supported
+        }
+    }
+}
+
+/// Force the scalar paths (`false`) or re-enable SIMD dispatch (`true`).
+pub fn set_simd_enabled(enabled: bool) {
+    SIMD_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn map_mul_add(data: &[f32], a: f32, b: f32) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_map_mul_add(data, a, b);
+    }
+
+    data.iter().map(|x| x * a + b).collect()
+}
+
+pub fn scalar_add(data: &[f32], scalar: f32) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_scalar_add(data, scalar);
+    }
+
+    data.iter().map(|x| x + scalar).collect()
+}
+
+pub fn scalar_sub(data: &[f32], scalar: f32) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_scalar_sub(data, scalar);
+    }
+
+    data.iter().map(|x| x - scalar).collect()
+}
+
+pub fn scalar_mul(data: &[f32], scalar: f32) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_scalar_mul(data, scalar);
+    }
+
+    data.iter().map(|x| x * scalar).collect()
+}
+
+pub fn scalar_div(data: &[f32], scalar: f32) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_scalar_div(data, scalar);
+    }
+
+    data.iter().map(|x| x / scalar).collect()
+}
+
+/// Sum accumulated in f64 on the scalar path; see `simd_ops::f32x_sum` for
+/// the SIMD accuracy bound.
+pub fn sum(data: &[f32]) -> f32 {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_sum(data);
+    }
+
+    data.iter().map(|&x| x as f64).sum::<f64>() as f32
+}
+
+/// NaN for an empty slice.
+pub fn mean(data: &[f32]) -> f32 {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_mean(data);
+    }
+
+    (data.iter().map(|&x| x as f64).sum::<f64>() / data.len() as f64) as f32
+}
+
+pub fn dot_product(a: &[f32], b: &[f32]) -> f32 {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_dot_product(a, b);
+    }
+
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
+pub fn vector_add(a: &[f32], b: &[f32]) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_vector_add(a, b);
+    }
+
+    a.iter().zip(b.iter()).map(|(x, y)| x + y).collect()
+}
+
+pub fn rolling_mean(data: &[f32], window_size: usize) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_rolling_mean(data, window_size);
+    }
+
+    if window_size == 0 || window_size > data.len() {
+        return vec![];
+    }
+    let inv_window = 1.0 / window_size as f32;
+    data.windows(window_size)
+        .map(|window| window.iter().sum::<f32>() * inv_window)
+        .collect()
+}
+
+pub fn min_max(data: &[f32]) -> (f32, f32) {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_min_max(data);
+    }
+
+    if data.is_empty() {
+        return (0.0, 0.0);
+    }
+    let min = data.iter().cloned().fold(f32::INFINITY, f32::min);
+    let max = data.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    (min, max)
+}
+
+pub fn cumsum(data: &[f32]) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_cumsum(data);
+    }
+
+    let mut sum = 0.0f32;
+    data.iter().map(|x| {
+        sum += x;
+        sum
+    }).collect()
+}
+
+/// First differences `data[i] - data[i - 1]`; one element shorter than the
+/// input (empty for fewer than two elements).
+pub fn diff(data: &[f32]) -> Vec<f32> {
+    if data.len() < 2 {
+        return vec![];
+    }
+    let (current, previous) = (&data[1..], &data[..data.len() - 1]);
+
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_vector_sub(current, previous);
+    }
+
+    current.iter().zip(previous).map(|(x, y)| x - y).collect()
+}
+
+// Every element equal. The float mean of such an input can still be off by an
+// ulp (e.g. `[0.1; 9]`, in f32 or f64), leaving a spurious variance, so
+// statistics that must come out 0 for constant input check this rather than
+// the variance.
+pub(crate) fn is_constant<T: PartialEq>(data: &[T]) -> bool {
+    data.iter().all(|x| *x == data[0])
+}
+
+/// Z-score normalization dividing by `std + epsilon`. A constant input yields
+/// all zeros.
+pub fn normalize(data: &[f32], epsilon: f32) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_normalize(data, epsilon);
+    }
+
+    if is_constant(data) {
+        return vec![0.0; data.len()];
+    }
+    let mean = data.iter().sum::<f32>() / data.len() as f32;
+    let variance = data.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / data.len() as f32;
+    let std_dev = variance.sqrt() + epsilon;
+    if std_dev == 0.0 {
+        return vec![0.0; data.len()];
+    }
+    data.iter().map(|x| (x - mean) / std_dev).collect()
+}
+
+/// `max(x, 0)`; NaN maps to 0.
+pub fn relu(data: &[f32]) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_relu(data);
+    }
+
+    data.iter().map(|x| x.max(0.0)).collect()
+}
+
+pub fn leaky_relu(data: &[f32], alpha: f32) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_leaky_relu(data, alpha);
+    }
+
+    data.iter().map(|&x| if x > 0.0 { x } else { x * alpha }).collect()
+}
+
+/// Requires `lo <= hi`; callers validate the bounds.
+pub fn clamp(data: &[f32], lo: f32, hi: f32) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_clamp(data, lo, hi);
+    }
+
+    data.iter().map(|x| x.clamp(lo, hi)).collect()
+}
+
+pub fn sigmoid(data: &[f32]) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_sigmoid(data);
+    }
+
+    data.iter().map(|x| 1.0 / (1.0 + (-x).exp())).collect()
+}
+
+pub fn tanh(data: &[f32]) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_tanh(data);
+    }
+
+    data.iter().map(|x| x.tanh()).collect()
+}
+
+pub fn abs(data: &[f32]) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_abs(data);
+    }
+
+    data.iter().map(|x| x.abs()).collect()
+}
+
+// The first index whose element is `better` than every earlier one; 0 for
+// an empty slice. Also the native-build body of the SIMD arg kernels.
+pub(crate) fn arg_extremum_scalar(data: &[f32], better: impl Fn(f32, f32) -> bool) -> usize {
+    let mut best = 0;
+    for (i, &x) in data.iter().enumerate().skip(1) {
+        if better(x, data[best]) {
+            best = i;
+        }
+    }
+    best
+}
+
+/// Index of the largest element, lowest index on ties; 0 for an empty slice.
+pub fn argmax(data: &[f32]) -> usize {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_argmax(data);
+    }
+
+    arg_extremum_scalar(data, |x, best| x > best)
+}
+
+/// Index of the smallest element, lowest index on ties; 0 for an empty slice.
+pub fn argmin(data: &[f32]) -> usize {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_argmin(data);
+    }
+
+    arg_extremum_scalar(data, |x, best| x < best)
+}
+
+/// Normalized autocorrelation at lags `0..=max_lag`; see
+/// `simd_ops::f32x_autocorr` for the normalization.
+pub fn autocorr(data: &[f32], max_lag: usize) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_autocorr(data, max_lag);
+    }
+
+    if data.is_empty() {
+        return vec![];
+    }
+    let len = data.len();
+    let max_lag = max_lag.min(len - 1);
+    let mean = data.iter().sum::<f32>() / len as f32;
+    let centered: Vec<f32> = data.iter().map(|x| x - mean).collect();
+    let dot = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>();
+
+    let energy = dot(&centered, &centered);
+    if energy == 0.0 || is_constant(data) {
+        return vec![0.0; max_lag + 1];
+    }
+    (0..=max_lag)
+        .map(|lag| dot(&centered[lag..], &centered[..len - lag]) / energy)
+        .collect()
+}
+
+/// Int8 quantization; see `simd_ops::f32x_quantize_i8`.
+pub fn quantize_i8(data: &[f32]) -> (Vec<i8>, f32) {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_quantize_i8(data);
+    }
+
+    let max_abs = data.iter().fold(0.0f32, |m, x| m.max(x.abs()));
+    let scale = max_abs / 127.0;
+    if scale == 0.0 {
+        return (vec![0; data.len()], 0.0);
+    }
+    let inv_scale = 1.0 / scale;
+    let values = data
+        .iter()
+        .map(|x| (x * inv_scale).round_ties_even().clamp(-127.0, 127.0) as i8)
+        .collect();
+    (values, scale)
+}
+
+pub fn dequantize_i8(data: &[i8], scale: f32) -> Vec<f32> {
+    data.iter().map(|&q| f32::from(q) * scale).collect()
+}
+
+/// Output size policy for `conv1d`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConvMode {
+    /// Only positions where the kernel fully overlaps the signal: `n - m + 1` outputs.
+    Valid,
+    /// Zero-padded so the output has the signal's length, centred like NumPy's `"same"`.
+    Same,
+}
+
+impl ConvMode {
+    pub fn parse(mode: &str) -> Option<ConvMode> {
+        match mode {
+            "valid" => Some(ConvMode::Valid),
+            "same" => Some(ConvMode::Same),
+            _ => None,
+        }
+    }
+}
+
+/// Zero-pads `signal` so a "valid" convolution yields the "same"-mode output.
+pub(crate) fn pad_for_same(signal: &[f32], kernel_len: usize) -> Vec<f32> {
+    let left = kernel_len / 2;
+    let right = kernel_len.saturating_sub(1) - left;
+    let mut padded = Vec::with_capacity(signal.len() + left + right);
+    padded.resize(left, 0.0);
+    padded.extend_from_slice(signal);
+    padded.resize(signal.len() + left + right, 0.0);
+    padded
+}
+
+/// Discrete convolution (the kernel is flipped, as in `numpy.convolve`).
+/// "valid" with a kernel longer than the signal yields an empty vector.
+pub fn conv1d(signal: &[f32], kernel: &[f32], mode: ConvMode) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_conv1d(signal, kernel, mode);
+    }
+
+    if kernel.is_empty() {
+        return vec![];
+    }
+    let padded;
+    let input = match mode {
+        ConvMode::Valid => signal,
+        ConvMode::Same => {
+            padded = pad_for_same(signal, kernel.len());
+            &padded
+        }
+    };
+    if kernel.len() > input.len() {
+        return vec![];
+    }
+    input.windows(kernel.len())
+        .map(|window| window.iter().zip(kernel.iter().rev()).map(|(x, k)| x * k).sum())
+        .collect()
+}
+
+/// Row-major `m×k` by `k×n` matrix product. Panics if the slice lengths do
+/// not match the dimensions.
+pub fn matmul(a: &[f32], b: &[f32], m: usize, k: usize, n: usize) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_matmul(a, b, m, k, n);
+    }
+
+    assert_eq!(a.len(), m * k);
+    assert_eq!(b.len(), k * n);
+    let mut result = vec![0f32; m * n];
+    for i in 0..m {
+        for p in 0..k {
+            let a_ip = a[i * k + p];
+            for j in 0..n {
+                result[i * n + j] += a_ip * b[p * n + j];
+            }
+        }
+    }
+    result
+}
+
+pub fn vector_sub(a: &[f32], b: &[f32]) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_vector_sub(a, b);
+    }
+
+    a.iter().zip(b.iter()).map(|(x, y)| x - y).collect()
+}
+
+pub fn vector_mul(a: &[f32], b: &[f32]) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_vector_mul(a, b);
+    }
+
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).collect()
+}
+
+pub fn vector_div(a: &[f32], b: &[f32]) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_vector_div(a, b);
+    }
+
+    a.iter().zip(b.iter()).map(|(x, y)| x / y).collect()
+}
+
+/// Elementwise minimum. Like `f32::min`, a NaN in one operand yields the
+/// other; only two NaNs give NaN.
+pub fn vector_min(a: &[f32], b: &[f32]) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_vector_min(a, b);
+    }
+
+    a.iter().zip(b.iter()).map(|(x, y)| x.min(*y)).collect()
+}
+
+/// Elementwise maximum, treating NaN like `vector_min`.
+pub fn vector_max(a: &[f32], b: &[f32]) -> Vec<f32> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f32x_vector_max(a, b);
+    }
+
+    a.iter().zip(b.iter()).map(|(x, y)| x.max(*y)).collect()
+}
+
+pub fn square_f64(data: &[f64]) -> Vec<f64> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f64x_square(data);
+    }
+
+    data.iter().map(|x| x * x).collect()
+}
+
+pub fn cumsum_f64(data: &[f64]) -> Vec<f64> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f64x_cumsum(data);
+    }
+
+    let mut sum = 0.0f64;
+    data.iter().map(|x| {
+        sum += x;
+        sum
+    }).collect()
+}
+
+/// `normalize` for f64 data.
+pub fn normalize_f64(data: &[f64], epsilon: f64) -> Vec<f64> {
+    #[cfg(feature = "simd")]
+    if simd_available() {
+        return crate::simd_ops::f64x_normalize(data, epsilon);
+    }
+
+    if is_constant(data) {
+        return vec![0.0; data.len()];
+    }
+    let mean = data.iter().sum::<f64>() / data.len() as f64;
+    let variance = data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / data.len() as f64;
+    let std_dev = variance.sqrt() + epsilon;
+    if std_dev == 0.0 {
+        return vec![0.0; data.len()];
+    }
+    data.iter().map(|x| (x - mean) / std_dev).collect()
+}
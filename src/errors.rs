@@ -0,0 +1,76 @@
+//! `ErrorInfo`, the error shape shared by the wire format and rivers, and the
+//! codes rivers classify errors with.
+//!
+//! River errors reach JS as an `Error` named `"RiverError"` whose `message`
+//! is the original error's, with `code` and `recoverable` from `ErrorInfo`
+//! and the original value as `cause`.
+
+use js_sys::Reflect;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ErrorInfo {
+    pub code: u32,
+    pub message: String,
+    pub recoverable: bool,
+}
+
+/// A `map`, `filter` or other operator callback threw, or an async mapper
+/// rejected. Not recoverable: the same value would throw again.
+pub const OPERATOR_THREW: u32 = 2001;
+/// The source failed to produce a value (a stream read or iterator error).
+pub const READ_ERROR: u32 = 2002;
+/// Any error named `TimeoutError`, e.g. from `AbortSignal.timeout`.
+pub const TIMEOUT: u32 = 2003;
+/// Credits for the next value could not be granted.
+pub const CREDIT_EXHAUSTED: u32 = 2004;
+
+const RIVER_ERROR_NAME: &str = "RiverError";
+
+impl ErrorInfo {
+    /// Classifies `error` caught where `code` applies. A `TimeoutError` is
+    /// always `TIMEOUT`, wherever it was caught.
+    pub fn classify(code: u32, error: &JsValue) -> Self {
+        let code = if error_name(error).as_deref() == Some("TimeoutError") { TIMEOUT } else { code };
+        Self { code, message: error_message(error), recoverable: code != OPERATOR_THREW }
+    }
+
+    /// Reads back an error made by `into_js`.
+    pub fn from_js(error: &JsValue) -> Option<Self> {
+        if error_name(error).as_deref() != Some(RIVER_ERROR_NAME) {
+            return None;
+        }
+        let code = Reflect::get(error, &"code".into()).ok()?.as_f64()?;
+        let recoverable = Reflect::get(error, &"recoverable".into()).ok()?.as_bool()?;
+        Some(Self { code: code as u32, message: error_message(error), recoverable })
+    }
+
+    pub fn into_js(self, cause: &JsValue) -> JsValue {
+        let error = js_sys::Error::new(&self.message);
+        error.set_name(RIVER_ERROR_NAME);
+        let _ = Reflect::set(&error, &"code".into(), &JsValue::from(self.code));
+        let _ = Reflect::set(&error, &"recoverable".into(), &JsValue::from_bool(self.recoverable));
+        let _ = Reflect::set(&error, &"cause".into(), cause);
+        error.into()
+    }
+}
+
+/// `error` as a classified river error; one that already is passes through
+/// unchanged, so errors from inner rivers keep their code.
+pub fn river_error(code: u32, error: JsValue) -> JsValue {
+    if ErrorInfo::from_js(&error).is_some() {
+        return error;
+    }
+    ErrorInfo::classify(code, &error).into_js(&error)
+}
+
+fn error_name(error: &JsValue) -> Option<String> {
+    error.is_object().then(|| Reflect::get(error, &"name".into()).ok()?.as_string()).flatten()
+}
+
+// `message` of Errors and DOMExceptions alike, or the value itself.
+fn error_message(error: &JsValue) -> String {
+    let message = error.is_object().then(|| Reflect::get(error, &"message".into()).ok()?.as_string()).flatten();
+    message.or_else(|| error.as_string()).unwrap_or_else(|| format!("{:?}", error))
+}
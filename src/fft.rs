@@ -0,0 +1,58 @@
+//! Magnitude spectra via an in-place iterative radix-2 FFT.
+//!
+//! Kept dependency-free so the same code runs in the browser and natively.
+//! Inputs must have a power-of-two length; they are never padded, because
+//! zero-padding changes the bin spacing and a caller should opt into that.
+
+use std::f32::consts::PI;
+
+/// One-sided magnitude spectrum `|X[k]|` for `k in 0..=n/2` of a real signal
+/// of power-of-two length `n`. Bin `k` is frequency `k * sample_rate / n`.
+/// Magnitudes are unscaled: a unit sine in bin `k` (0 < k < n/2) peaks at `n/2`.
+pub fn magnitude_spectrum(data: &[f32]) -> Result<Vec<f32>, String> {
+    let n = data.len();
+    if !n.is_power_of_two() {
+        return Err(format!("FFT length must be a power of two, got {}", n));
+    }
+
+    let mut re = data.to_vec();
+    let mut im = vec![0.0f32; n];
+    fft_in_place(&mut re, &mut im);
+    Ok((0..=n / 2).map(|k| re[k].hypot(im[k])).collect())
+}
+
+fn fft_in_place(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    if n < 2 {
+        return;
+    }
+
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if j > i {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                // Recomputing each twiddle keeps the error from accumulating
+                // the way a running product would.
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * cos - im[b] * sin;
+                let t_im = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len *= 2;
+    }
+}
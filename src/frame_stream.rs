@@ -0,0 +1,74 @@
+//! Glue between `serialization` and `river`: a byte `ReadableStream` of
+//! `FrameCodec` output decoded into a river of frames.
+
+use futures::channel::mpsc;
+use futures::SinkExt;
+use js_sys::Uint8Array;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{ReadableStream, ReadableStreamDefaultReader};
+
+use crate::river::{RiverCore, RiverValue};
+use crate::serialization::{frame_to_js, FrameDecoder};
+
+/// Reads a byte stream through a `FrameDecoder` into a bounded channel
+/// backing a river. Reads pause while `capacity` decoded frames are waiting
+/// to be observed, so a slow observer holds back the stream rather than
+/// growing a queue.
+pub struct FrameStream;
+
+impl FrameStream {
+    /// Starts reading `stream` and returns the river of its frames, as
+    /// `{ sequence, timestamp, payload }` objects like `decodeFrames`. A
+    /// chunk that isn't a `Uint8Array`, a frame that fails to decode, a read
+    /// error, a length prefix too long for a payload of `max_payload_bytes`
+    /// or a partial frame left at the end is reported as an error and ends
+    /// the river, cancelling the stream. The river's subscriptions share
+    /// one reader, so each frame reaches only one of them.
+    pub fn pipe(stream: &ReadableStream, capacity: usize, max_payload_bytes: usize) -> RiverCore {
+        let (sender, receiver) = mpsc::channel(capacity);
+        let reader: ReadableStreamDefaultReader = stream.get_reader().unchecked_into();
+        wasm_bindgen_futures::spawn_local(Self::pump(reader, sender, FrameDecoder::with_max_payload_bytes(max_payload_bytes)));
+        RiverCore::from_bounded_channel(receiver)
+    }
+
+    async fn pump(
+        reader: ReadableStreamDefaultReader,
+        mut sender: mpsc::Sender<Result<RiverValue, JsValue>>,
+        mut decoder: FrameDecoder,
+    ) {
+        let failure = loop {
+            let chunk = match JsFuture::from(reader.read()).await {
+                Ok(chunk) => chunk,
+                Err(e) => break Some(e),
+            };
+            let done = js_sys::Reflect::get(&chunk, &"done".into()).ok().and_then(|d| d.as_bool()).unwrap_or(false);
+            if done {
+                break match decoder.buffered() {
+                    0 => None,
+                    n => Some(JsValue::from_str(&format!("Truncated frame: {} bytes left at end of stream", n))),
+                };
+            }
+            let value = js_sys::Reflect::get(&chunk, &"value".into()).unwrap_or(JsValue::UNDEFINED);
+            let Ok(bytes) = value.dyn_into::<Uint8Array>() else {
+                break Some(JsValue::from_str("Frame stream chunks must be Uint8Arrays"));
+            };
+            // Frames decoded ahead of a corrupt one are still delivered.
+            let (frames, error) = decoder.push(&bytes.to_vec());
+            for frame in frames {
+                // Waits while the channel is full; fails once the river is gone.
+                if sender.send(Ok(RiverValue::JsValue(frame_to_js(&frame)))).await.is_err() {
+                    let _ = reader.cancel();
+                    return;
+                }
+            }
+            if let Some(e) = error {
+                break Some(JsValue::from_str(&e));
+            }
+        };
+        if let Some(e) = failure {
+            let _ = reader.cancel();
+            let _ = sender.send(Err(e)).await;
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! Named `Float32Array` kernels for `map_wasm`. Built-in kernels are
+//! registered under stable `f32x_*` names; more can be added at runtime with
+//! `register_kernel` without touching the river's operator code.
+//!
+//! Each kernel declares the parameters it reads from the JS object passed to
+//! `map_wasm`, and `validate_params` checks them when the operator is built,
+//! so a missing or mistyped parameter is reported up front instead of
+//! failing every value. Checks that depend on the value itself (an operand's
+//! length) still fail per value, as an error.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use js_sys::Float32Array;
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::dispatch;
+use crate::operators::NORMALIZE_EPSILON;
+
+/// What a kernel emits in place of its input array.
+#[derive(Clone, Debug, PartialEq)]
+pub enum KernelOutput {
+    Array(Vec<f32>),
+    Number(f64),
+}
+
+pub type Kernel = fn(&[f32], &JsValue) -> Result<KernelOutput, String>;
+
+/// The type a kernel parameter must have.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParamKind {
+    Number,
+    /// A non-negative integer.
+    Count,
+    /// An integer of at least 1, such as a window size.
+    PositiveCount,
+    Float32Array,
+}
+
+/// Parameters a kernel reads, by name.
+pub type ParamSchema = &'static [(&'static str, ParamKind)];
+
+#[derive(Clone, Copy)]
+struct Registered {
+    kernel: Kernel,
+    params: ParamSchema,
+}
+
+thread_local! {
+    static REGISTRY: RefCell<BTreeMap<String, Registered>> = RefCell::new(builtins());
+}
+
+/// Registers `kernel` under `name` with the parameters it reads, returning
+/// the kernel it replaced.
+pub fn register_kernel(name: &str, kernel: Kernel, params: ParamSchema) -> Option<Kernel> {
+    REGISTRY.with(|registry| {
+        let previous = registry.borrow_mut().insert(name.to_string(), Registered { kernel, params });
+        previous.map(|previous| previous.kernel)
+    })
+}
+
+pub fn kernel(name: &str) -> Option<Kernel> {
+    REGISTRY.with(|registry| registry.borrow().get(name).map(|registered| registered.kernel))
+}
+
+/// Registered kernel names in lexicographic order.
+pub fn kernel_names() -> Vec<String> {
+    REGISTRY.with(|registry| registry.borrow().keys().cloned().collect())
+}
+
+/// Looks `name` up and checks `params` against its schema, naming the
+/// kernel and the offending parameter on failure.
+pub fn validate_params(name: &str, params: &JsValue) -> Result<Kernel, String> {
+    let registered = REGISTRY
+        .with(|registry| registry.borrow().get(name).copied())
+        .ok_or_else(|| format!("Unknown kernel: {}", name))?;
+    for &(param, kind) in registered.params {
+        let checked = match kind {
+            ParamKind::Number => number(params, param).map(drop),
+            ParamKind::Count => count(params, param).map(drop),
+            ParamKind::PositiveCount => positive_count(params, param).map(drop),
+            ParamKind::Float32Array => array(params, param).map(drop),
+        };
+        checked.map_err(|e| format!("{}: {}", name, e))?;
+    }
+    Ok(registered.kernel)
+}
+
+fn param(params: &JsValue, name: &str) -> Option<JsValue> {
+    if !params.is_object() {
+        return None;
+    }
+    js_sys::Reflect::get(params, &name.into()).ok().filter(|value| !value.is_undefined())
+}
+
+/// `params[name]` as a number; for kernels reading their own parameters.
+pub fn number(params: &JsValue, name: &str) -> Result<f32, String> {
+    let value = param(params, name).ok_or_else(|| format!("missing parameter `{}`", name))?;
+    value.as_f64().map(|n| n as f32).ok_or_else(|| format!("parameter `{}` must be a number", name))
+}
+
+/// `params[name]` as a non-negative integer.
+pub fn count(params: &JsValue, name: &str) -> Result<usize, String> {
+    let value = param(params, name).ok_or_else(|| format!("missing parameter `{}`", name))?;
+    value
+        .as_f64()
+        .filter(|n| *n >= 0.0 && n.fract() == 0.0)
+        .map(|n| n as usize)
+        .ok_or_else(|| format!("parameter `{}` must be a non-negative integer", name))
+}
+
+/// `params[name]` as an integer of at least 1.
+pub fn positive_count(params: &JsValue, name: &str) -> Result<usize, String> {
+    count(params, name)
+        .ok()
+        .filter(|&n| n > 0)
+        .ok_or_else(|| format!("parameter `{}` must be a positive integer", name))
+}
+
+/// `params[name]` as the contents of a Float32Array.
+pub fn array(params: &JsValue, name: &str) -> Result<Vec<f32>, String> {
+    let value = param(params, name).ok_or_else(|| format!("missing parameter `{}`", name))?;
+    value
+        .dyn_into::<Float32Array>()
+        .map(|array| array.to_vec())
+        .map_err(|_| format!("parameter `{}` must be a Float32Array", name))
+}
+
+// `params[name]` as a Float32Array of the same length as `data`.
+fn operand(params: &JsValue, name: &str, data: &[f32]) -> Result<Vec<f32>, String> {
+    let other = array(params, name)?;
+    if other.len() != data.len() {
+        return Err(format!("`{}` has {} elements, expected {}", name, other.len(), data.len()));
+    }
+    Ok(other)
+}
+
+fn builtins() -> BTreeMap<String, Registered> {
+    use KernelOutput::{Array, Number};
+    use ParamKind::{Count, Float32Array, PositiveCount};
+
+    const NONE: ParamSchema = &[];
+    const SCALAR: ParamSchema = &[("scalar", ParamKind::Number)];
+    const OTHER: ParamSchema = &[("other", Float32Array)];
+
+    let kernels: [(&str, Kernel, ParamSchema); 28] = [
+        (
+            "f32x_map_mul_add",
+            |data, params| Ok(Array(dispatch::map_mul_add(data, number(params, "a")?, number(params, "b")?))),
+            &[("a", ParamKind::Number), ("b", ParamKind::Number)],
+        ),
+        ("f32x_scalar_add", |data, params| Ok(Array(dispatch::scalar_add(data, number(params, "scalar")?))), SCALAR),
+        ("f32x_scalar_sub", |data, params| Ok(Array(dispatch::scalar_sub(data, number(params, "scalar")?))), SCALAR),
+        ("f32x_scalar_mul", |data, params| Ok(Array(dispatch::scalar_mul(data, number(params, "scalar")?))), SCALAR),
+        ("f32x_scalar_div", |data, params| Ok(Array(dispatch::scalar_div(data, number(params, "scalar")?))), SCALAR),
+        ("f32x_vector_add", |data, params| Ok(Array(dispatch::vector_add(data, &operand(params, "other", data)?))), OTHER),
+        ("f32x_vector_sub", |data, params| Ok(Array(dispatch::vector_sub(data, &operand(params, "other", data)?))), OTHER),
+        ("f32x_vector_mul", |data, params| Ok(Array(dispatch::vector_mul(data, &operand(params, "other", data)?))), OTHER),
+        ("f32x_vector_div", |data, params| Ok(Array(dispatch::vector_div(data, &operand(params, "other", data)?))), OTHER),
+        ("f32x_vector_min", |data, params| Ok(Array(dispatch::vector_min(data, &operand(params, "other", data)?))), OTHER),
+        ("f32x_vector_max", |data, params| Ok(Array(dispatch::vector_max(data, &operand(params, "other", data)?))), OTHER),
+        (
+            "f32x_dot_product",
+            |data, params| Ok(Number(dispatch::dot_product(data, &operand(params, "other", data)?) as f64)),
+            OTHER,
+        ),
+        (
+            "f32x_rolling_mean",
+            |data, params| Ok(Array(dispatch::rolling_mean(data, positive_count(params, "window")?))),
+            &[("window", PositiveCount)],
+        ),
+        (
+            "f32x_min_max",
+            |data, _| {
+                let (min, max) = dispatch::min_max(data);
+                Ok(Array(vec![min, max]))
+            },
+            NONE,
+        ),
+        ("f32x_sum", |data, _| Ok(Number(dispatch::sum(data) as f64)), NONE),
+        ("f32x_mean", |data, _| Ok(Number(dispatch::mean(data) as f64)), NONE),
+        (
+            "f32x_argmax",
+            |data, _| match data.is_empty() {
+                true => Err("argmax of an empty array".to_string()),
+                false => Ok(Number(dispatch::argmax(data) as f64)),
+            },
+            NONE,
+        ),
+        (
+            "f32x_argmin",
+            |data, _| match data.is_empty() {
+                true => Err("argmin of an empty array".to_string()),
+                false => Ok(Number(dispatch::argmin(data) as f64)),
+            },
+            NONE,
+        ),
+        ("f32x_cumsum", |data, _| Ok(Array(dispatch::cumsum(data))), NONE),
+        ("f32x_diff", |data, _| Ok(Array(dispatch::diff(data))), NONE),
+        (
+            "f32x_autocorr",
+            |data, params| Ok(Array(dispatch::autocorr(data, count(params, "max_lag")?))),
+            &[("max_lag", Count)],
+        ),
+        ("f32x_abs", |data, _| Ok(Array(dispatch::abs(data))), NONE),
+        ("f32x_normalize", |data, _| Ok(Array(dispatch::normalize(data, NORMALIZE_EPSILON as f32))), NONE),
+        ("f32x_relu", |data, _| Ok(Array(dispatch::relu(data))), NONE),
+        (
+            "f32x_clamp",
+            |data, params| {
+                let (lo, hi) = (number(params, "lo")?, number(params, "hi")?);
+                if lo.is_nan() || hi.is_nan() || lo > hi {
+                    return Err(format!("clamp requires lo <= hi, got {} > {}", lo, hi));
+                }
+                Ok(Array(dispatch::clamp(data, lo, hi)))
+            },
+            &[("lo", ParamKind::Number), ("hi", ParamKind::Number)],
+        ),
+        (
+            "f32x_leaky_relu",
+            |data, params| Ok(Array(dispatch::leaky_relu(data, number(params, "alpha")?))),
+            &[("alpha", ParamKind::Number)],
+        ),
+        ("f32x_sigmoid", |data, _| Ok(Array(dispatch::sigmoid(data))), NONE),
+        ("f32x_tanh", |data, _| Ok(Array(dispatch::tanh(data))), NONE),
+    ];
+    kernels
+        .into_iter()
+        .map(|(name, kernel, params)| (name.to_string(), Registered { kernel, params }))
+        .collect()
+}
@@ -1,17 +1,24 @@
 use wasm_bindgen::prelude::*;
-use js_sys::{Uint8Array, Float32Array, Float64Array, ArrayBuffer};
+use js_sys::{Uint8Array, Int8Array, Float32Array, Float64Array, Int32Array, ArrayBuffer};
 
 // Heavy modules are behind feature flags; minimal web build does not compile them
 #[cfg(feature = "river")]
 pub mod river;
 pub mod operators;
 pub mod backpressure;
+pub mod dispatch;
+pub mod errors;
+pub mod kernels;
 #[cfg(feature = "byob")]
 pub mod byob;
 #[cfg(feature = "serialization")]
 pub mod serialization;
+#[cfg(all(feature = "river", feature = "serialization"))]
+pub mod frame_stream;
 #[cfg(feature = "simd")]
 pub mod simd_ops;
+#[cfg(feature = "fft")]
+pub mod fft;
 
 // console logging bindings/macroは未使用のため削除
 
@@ -24,27 +31,487 @@ pub fn init() {
 
 // No Subscription in minimal build
 
+#[wasm_bindgen]
+pub fn simd_available() -> bool {
+    dispatch::simd_available()
+}
+
+#[wasm_bindgen]
+pub fn set_simd_enabled(enabled: bool) {
+    dispatch::set_simd_enabled(enabled);
+}
+
 #[wasm_bindgen]
 pub fn process_float32_batch(data: &Float32Array, operation: &str) -> Float32Array {
     // Delegate to operators module (pure Rust)
     operators::process_float32_batch(data, operation)
 }
 
+#[wasm_bindgen]
+pub fn process_float32_batch_checked(data: &Float32Array, operation: &str) -> Result<Float32Array, JsValue> {
+    operators::process_float32_batch_checked(data, operation)
+}
+
+#[wasm_bindgen]
+pub fn process_float32_batch_with_params(data: &Float32Array, operation: &str, params: JsValue) -> Result<Float32Array, JsValue> {
+    operators::process_float32_batch_with_params(data, operation, &params)
+}
+
+#[wasm_bindgen]
+pub fn supported_operations() -> js_sys::Array {
+    operators::supported_operations()
+}
+
 #[wasm_bindgen]
 pub fn process_float64_batch(data: &Float64Array, operation: &str) -> Float64Array {
     operators::process_float64_batch(data, operation)
 }
 
+#[wasm_bindgen]
+pub fn process_float64_batch_checked(data: &Float64Array, operation: &str) -> Result<Float64Array, JsValue> {
+    operators::process_float64_batch_checked(data, operation)
+}
+
+#[wasm_bindgen]
+pub fn normalize_float32(data: &Float32Array, epsilon: Option<f32>) -> Float32Array {
+    operators::normalize_float32(data, epsilon.unwrap_or(operators::NORMALIZE_EPSILON as f32))
+}
+
+#[wasm_bindgen]
+pub fn normalize_float64(data: &Float64Array, epsilon: Option<f64>) -> Float64Array {
+    operators::normalize_float64(data, epsilon.unwrap_or(operators::NORMALIZE_EPSILON))
+}
+
+#[wasm_bindgen]
+pub fn combine_float32(a: &Float32Array, b: &Float32Array, op: &str) -> Result<Float32Array, JsValue> {
+    operators::combine_float32(a, b, op)
+}
+
+#[wasm_bindgen]
+pub fn process_int32_batch(data: &Int32Array, operation: &str, params: JsValue) -> Result<Int32Array, JsValue> {
+    operators::process_int32_batch(data, operation, &params)
+}
+
+/// Kernel names accepted by `map_wasm`, sorted.
+#[wasm_bindgen(js_name = listKernels)]
+pub fn list_kernels() -> js_sys::Array {
+    kernels::kernel_names().into_iter().map(JsValue::from).collect()
+}
+
+#[wasm_bindgen(js_name = f32xSum)]
+pub fn f32x_sum(data: &Float32Array) -> f32 {
+    dispatch::sum(&data.to_vec())
+}
+
+/// NaN for an empty array.
+#[wasm_bindgen(js_name = f32xMean)]
+pub fn f32x_mean(data: &Float32Array) -> f32 {
+    dispatch::mean(&data.to_vec())
+}
+
+/// Limits each value to `[lo, hi]`. Throws if `lo > hi` (or either is NaN).
+#[wasm_bindgen(js_name = f32xClamp)]
+pub fn f32x_clamp(data: &Float32Array, lo: f32, hi: f32) -> Result<Float32Array, JsValue> {
+    if lo.is_nan() || hi.is_nan() || lo > hi {
+        return Err(JsValue::from_str("clamp requires lo <= hi"));
+    }
+    let output = dispatch::clamp(&data.to_vec(), lo, hi);
+    let result = Float32Array::new_with_length(output.len() as u32);
+    result.copy_from(&output);
+    Ok(result)
+}
+
+/// Index of the largest element, lowest on ties. Throws on an empty array,
+/// which has no index to report.
+#[wasm_bindgen(js_name = f32xArgmax)]
+pub fn f32x_argmax(data: &Float32Array) -> Result<usize, JsValue> {
+    match data.length() {
+        0 => Err(JsValue::from_str("argmax of an empty array")),
+        _ => Ok(dispatch::argmax(&data.to_vec())),
+    }
+}
+
+/// Index of the smallest element, lowest on ties. Throws on an empty array.
+#[wasm_bindgen(js_name = f32xArgmin)]
+pub fn f32x_argmin(data: &Float32Array) -> Result<usize, JsValue> {
+    match data.length() {
+        0 => Err(JsValue::from_str("argmin of an empty array")),
+        _ => Ok(dispatch::argmin(&data.to_vec())),
+    }
+}
+
+#[wasm_bindgen(js_name = f32xAutocorr)]
+pub fn f32x_autocorr(data: &Float32Array, max_lag: usize) -> Float32Array {
+    let output = dispatch::autocorr(&data.to_vec(), max_lag);
+    let result = Float32Array::new_with_length(output.len() as u32);
+    result.copy_from(&output);
+    result
+}
+
+/// `{ values: Int8Array, scale }`; see `dequantize_i8` for the inverse.
+#[wasm_bindgen(js_name = f32xQuantizeI8)]
+pub fn f32x_quantize_i8(data: &Float32Array) -> Result<JsValue, JsValue> {
+    let (values, scale) = dispatch::quantize_i8(&data.to_vec());
+    let result = js_sys::Object::new();
+    js_sys::Reflect::set(&result, &"values".into(), &Int8Array::from(&values[..]))?;
+    js_sys::Reflect::set(&result, &"scale".into(), &JsValue::from_f64(scale as f64))?;
+    Ok(result.into())
+}
+
+#[wasm_bindgen(js_name = dequantizeI8)]
+pub fn dequantize_i8(data: &Int8Array, scale: f32) -> Float32Array {
+    let output = dispatch::dequantize_i8(&data.to_vec(), scale);
+    let result = Float32Array::new_with_length(output.len() as u32);
+    result.copy_from(&output);
+    result
+}
+
+#[wasm_bindgen(js_name = f32xConv1d)]
+pub fn f32x_conv1d(signal: &Float32Array, kernel: &Float32Array, mode: &str) -> Result<Float32Array, JsValue> {
+    let mode = dispatch::ConvMode::parse(mode)
+        .ok_or_else(|| JsValue::from_str("mode must be \"valid\" or \"same\""))?;
+    if kernel.length() == 0 {
+        return Err(JsValue::from_str("kernel must not be empty"));
+    }
+    if mode == dispatch::ConvMode::Same && kernel.length() > signal.length() {
+        return Err(JsValue::from_str("kernel is longer than the signal"));
+    }
+
+    let output = dispatch::conv1d(&signal.to_vec(), &kernel.to_vec(), mode);
+    let result = Float32Array::new_with_length(output.len() as u32);
+    result.copy_from(&output);
+    Ok(result)
+}
+
+#[wasm_bindgen(js_name = f32xMatmul)]
+pub fn f32x_matmul(
+    a: &Float32Array,
+    b: &Float32Array,
+    m: usize,
+    k: usize,
+    n: usize,
+) -> Result<Float32Array, JsValue> {
+    if a.length() as usize != m * k {
+        return Err(JsValue::from_str(&format!("a has {} elements, expected {}x{}", a.length(), m, k)));
+    }
+    if b.length() as usize != k * n {
+        return Err(JsValue::from_str(&format!("b has {} elements, expected {}x{}", b.length(), k, n)));
+    }
+
+    let output = dispatch::matmul(&a.to_vec(), &b.to_vec(), m, k, n);
+    let result = Float32Array::new_with_length(output.len() as u32);
+    result.copy_from(&output);
+    Ok(result)
+}
+
+#[cfg(feature = "fft")]
+#[wasm_bindgen]
+pub fn fft_magnitude(data: &Float32Array) -> Result<Float32Array, JsValue> {
+    let output = fft::magnitude_spectrum(&data.to_vec()).map_err(|e| JsValue::from_str(&e))?;
+    let result = Float32Array::new_with_length(output.len() as u32);
+    result.copy_from(&output);
+    Ok(result)
+}
+
+#[cfg(feature = "serialization")]
+#[wasm_bindgen]
+pub fn encode_postcard(value: JsValue) -> Result<Uint8Array, JsValue> {
+    serialization::encode_postcard(value)
+}
+
+#[cfg(feature = "serialization")]
+#[wasm_bindgen]
+pub fn encode_postcard_v2(value: JsValue) -> Result<Uint8Array, JsValue> {
+    serialization::encode_postcard_v2(value)
+}
+
+#[cfg(feature = "compression")]
+#[wasm_bindgen]
+pub fn encode_postcard_compressed(value: JsValue, algo: &str) -> Result<Uint8Array, JsValue> {
+    serialization::encode_postcard_compressed(value, algo)
+}
+
+#[cfg(feature = "serialization")]
+#[wasm_bindgen]
+pub fn decode_postcard(bytes: &Uint8Array) -> Result<JsValue, JsValue> {
+    serialization::decode_postcard(bytes)
+}
+
+#[cfg(feature = "serialization")]
+#[wasm_bindgen]
+pub fn decode_postcard_with_limit(bytes: &Uint8Array, max_payload_bytes: usize) -> Result<JsValue, JsValue> {
+    serialization::decode_postcard_with_limit(bytes, max_payload_bytes)
+}
+
+#[cfg(feature = "serialization")]
+#[wasm_bindgen]
+pub fn encode_frames(frames: &js_sys::Array) -> Result<Uint8Array, JsValue> {
+    serialization::encode_frames(frames)
+}
+
+#[cfg(feature = "serialization")]
+#[wasm_bindgen]
+pub fn decode_frames(bytes: &Uint8Array) -> Result<js_sys::Array, JsValue> {
+    serialization::decode_frames(bytes)
+}
+
+#[cfg(feature = "serialization")]
+#[wasm_bindgen]
+pub fn decode_frames_with_limit(bytes: &Uint8Array, max_payload_bytes: usize) -> Result<js_sys::Array, JsValue> {
+    serialization::decode_frames_with_limit(bytes, max_payload_bytes)
+}
+
+#[cfg(feature = "json")]
+#[wasm_bindgen]
+pub fn encode_json(value: JsValue) -> Result<String, JsValue> {
+    serialization::encode_json(value)
+}
+
+#[cfg(feature = "json")]
+#[wasm_bindgen]
+pub fn decode_json(s: &str) -> Result<JsValue, JsValue> {
+    serialization::decode_json(s)
+}
+
+// Without the codec, fail loudly rather than returning empty bytes/undefined.
+#[cfg(not(feature = "serialization"))]
 #[wasm_bindgen]
 pub fn encode_postcard(_value: JsValue) -> Result<Uint8Array, JsValue> {
-    // Minimal stub: return empty bytes in web_min
-    Ok(Uint8Array::new_with_length(0))
+    Err(JsValue::from_str("encode_postcard: serialization feature not enabled"))
 }
 
+#[cfg(not(feature = "serialization"))]
 #[wasm_bindgen]
 pub fn decode_postcard(_bytes: &Uint8Array) -> Result<JsValue, JsValue> {
-    // Minimal stub: return undefined
-    Ok(JsValue::UNDEFINED)
+    Err(JsValue::from_str("decode_postcard: serialization feature not enabled"))
+}
+
+/// JS handle to a `river::RiverCore`.
+#[cfg(feature = "river")]
+#[wasm_bindgen(js_name = River)]
+pub struct WasmRiver {
+    inner: river::RiverCore,
+}
+
+#[cfg(feature = "river")]
+#[wasm_bindgen(js_class = River)]
+impl WasmRiver {
+    #[wasm_bindgen(js_name = fromArray)]
+    pub fn from_array(values: js_sys::Array) -> WasmRiver {
+        Self { inner: river::RiverCore::from_js_array(values) }
+    }
+
+    /// Emits `values` as Float32Array chunks of `chunk_size` elements, or
+    /// whole when `chunk_size` is omitted.
+    #[wasm_bindgen(js_name = fromFloat32Array)]
+    pub fn from_float32_array(values: &Float32Array, chunk_size: Option<usize>) -> WasmRiver {
+        Self { inner: river::RiverCore::from_float32_array(values, chunk_size) }
+    }
+
+    /// Decodes a byte stream of length-prefixed frames, reading ahead at
+    /// most `capacity` frames (default 16) of what has been observed. Frames
+    /// declaring a payload over `max_payload_bytes` (default 64 MiB) end the
+    /// river with an error.
+    #[cfg(feature = "serialization")]
+    #[wasm_bindgen(js_name = fromFrameStream)]
+    pub fn from_frame_stream(
+        stream: &web_sys::ReadableStream,
+        capacity: Option<usize>,
+        max_payload_bytes: Option<usize>,
+    ) -> WasmRiver {
+        let max_payload_bytes = max_payload_bytes.unwrap_or(serialization::DEFAULT_MAX_PAYLOAD_BYTES);
+        Self { inner: frame_stream::FrameStream::pipe(stream, capacity.unwrap_or(16), max_payload_bytes) }
+    }
+
+    #[wasm_bindgen(js_name = fromAsyncIterator)]
+    pub fn from_async_iterator(iterable: JsValue) -> WasmRiver {
+        Self { inner: river::RiverCore::from_async_iterator(iterable) }
+    }
+
+    pub fn interval(period_ms: u32) -> WasmRiver {
+        Self { inner: river::RiverCore::interval(period_ms) }
+    }
+
+    /// An `AbortSignal` for `observe` that aborts after `ms`.
+    #[wasm_bindgen(js_name = withTimeout)]
+    pub fn with_timeout(ms: u32) -> Result<web_sys::AbortSignal, JsValue> {
+        river::RiverCore::timeout_signal(ms)
+    }
+
+    pub fn range(start: f64, end: f64, step: Option<f64>) -> Result<WasmRiver, JsValue> {
+        let inner = river::RiverCore::range(start, end, step.unwrap_or(1.0))?;
+        Ok(Self { inner })
+    }
+
+    /// `map` for async mappers. `concurrency` is `"concat"` (default: one at
+    /// a time, in order) or `"merge"` (up to `maxInFlight` at once, default
+    /// 16, emitted as they resolve).
+    #[wasm_bindgen(js_name = mapAsync)]
+    pub fn map_async(
+        &self,
+        mapper: js_sys::Function,
+        concurrency: Option<String>,
+        max_in_flight: Option<usize>,
+    ) -> Result<WasmRiver, JsValue> {
+        let order = match concurrency {
+            Some(concurrency) => concurrency.parse().map_err(|e: String| JsValue::from_str(&e))?,
+            None => river::AsyncMapOrder::Concat,
+        };
+        let order = match (order, max_in_flight) {
+            (river::AsyncMapOrder::Merge(_), Some(limit)) => river::AsyncMapOrder::Merge(limit),
+            (order, _) => order,
+        };
+        Ok(Self { inner: self.inner.map_async(mapper, order) })
+    }
+
+    /// Times each per-value operator in every subscription; see
+    /// `RiverSubscription.profile`.
+    #[wasm_bindgen(js_name = enableProfiling)]
+    pub fn enable_profiling(&self) -> WasmRiver {
+        Self { inner: self.inner.enable_profiling() }
+    }
+
+    /// Runs a registered kernel (see `listKernels`) on each Float32Array
+    /// value. Throws if the kernel is unknown or `params` does not match it.
+    #[wasm_bindgen(js_name = mapWasm)]
+    pub fn map_wasm(&self, kernel: &str, params: JsValue) -> Result<WasmRiver, JsValue> {
+        Ok(Self { inner: self.inner.map_wasm(kernel, params)? })
+    }
+
+    /// Resamples `{ t, value }` objects onto a grid every `interval_ms` using
+    /// `"nearest"`, `"linear"` or `"hold"` interpolation.
+    pub fn resample(&self, interval_ms: u32, method: &str) -> Result<WasmRiver, JsValue> {
+        Ok(Self { inner: self.inner.resample(interval_ms, method)? })
+    }
+
+    /// Forwards values only while `credits` has credit, one per value;
+    /// `onNoCredit` is `"drop"` or `"buffer"`. Top `credits` up with
+    /// `addCredits` to let more through.
+    pub fn gate(&self, credits: &CreditController, on_no_credit: &str) -> Result<WasmRiver, JsValue> {
+        Ok(Self { inner: self.inner.gate(&credits.inner, on_no_credit)? })
+    }
+
+    /// Buffers up to `buffer_size` values for a slow consumer; `overflow` is
+    /// `"drop_oldest"`, `"drop_newest"` or `"block"`.
+    #[wasm_bindgen(js_name = pipeThrough)]
+    pub fn pipe_through(&self, buffer_size: usize, overflow: &str) -> Result<WasmRiver, JsValue> {
+        Ok(Self { inner: self.inner.pipe_through(buffer_size, overflow)? })
+    }
+
+    pub fn observe(
+        &self,
+        next: js_sys::Function,
+        error: Option<js_sys::Function>,
+        complete: Option<js_sys::Function>,
+        signal: Option<web_sys::AbortSignal>,
+    ) -> RiverSubscription {
+        RiverSubscription { inner: self.inner.observe(next, error, complete, signal) }
+    }
+
+    /// `observe` paced by `credits`: each value takes one credit, and the
+    /// river waits while none are left. Call `credits.addCredits(n)` to ask
+    /// for `n` more values.
+    #[wasm_bindgen(js_name = observeWithCredits)]
+    pub fn observe_with_credits(
+        &self,
+        credits: &CreditController,
+        next: js_sys::Function,
+        error: Option<js_sys::Function>,
+        complete: Option<js_sys::Function>,
+        signal: Option<web_sys::AbortSignal>,
+    ) -> RiverSubscription {
+        let inner = self.inner.observe_with_credits(&credits.inner, next, error, complete, signal);
+        RiverSubscription { inner }
+    }
+}
+
+/// A river pushed into imperatively. Each value goes to one observer, like
+/// any channel source, so attach a single observer or `share()` the river.
+///
+/// `complete()` and `error()` end the river and are idempotent: only the
+/// first call counts. Pushing afterwards is a silent no-op. As with a failed
+/// `ReadableStream` read, observers see the error and then complete unless
+/// the river uses `terminate_on_error`.
+#[cfg(feature = "river")]
+#[wasm_bindgen]
+pub struct Subject {
+    river: river::RiverCore,
+    sender: river::ChannelSender,
+}
+
+#[cfg(feature = "river")]
+#[wasm_bindgen]
+impl Subject {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Subject {
+        let (river, sender) = river::RiverCore::channel();
+        Self { river, sender }
+    }
+
+    pub fn next(&self, value: JsValue) {
+        self.sender.push(river::RiverValue::JsValue(value));
+    }
+
+    pub fn complete(&self) {
+        self.sender.close();
+    }
+
+    pub fn error(&self, error: JsValue) {
+        self.sender.fail(error);
+    }
+
+    #[wasm_bindgen(getter, js_name = isClosed)]
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    #[wasm_bindgen(js_name = asRiver)]
+    pub fn as_river(&self) -> WasmRiver {
+        WasmRiver { inner: self.river.clone() }
+    }
+
+    pub fn observe(
+        &self,
+        next: js_sys::Function,
+        error: Option<js_sys::Function>,
+        complete: Option<js_sys::Function>,
+        signal: Option<web_sys::AbortSignal>,
+    ) -> RiverSubscription {
+        RiverSubscription { inner: self.river.observe(next, error, complete, signal) }
+    }
+}
+
+#[cfg(feature = "river")]
+impl Default for Subject {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "river")]
+#[wasm_bindgen]
+pub struct RiverSubscription {
+    inner: river::SubscriptionHandle,
+}
+
+#[cfg(feature = "river")]
+#[wasm_bindgen]
+impl RiverSubscription {
+    pub fn unsubscribe(&self) {
+        self.inner.unsubscribe();
+    }
+
+    #[wasm_bindgen(getter, js_name = isActive)]
+    pub fn is_active(&self) -> bool {
+        self.inner.is_active()
+    }
+
+    /// `{ "map#0": { totalMs, calls }, ... }` for a river built with
+    /// `enableProfiling`, otherwise `{}`.
+    pub fn profile(&self) -> Result<JsValue, JsValue> {
+        let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+        serde::Serialize::serialize(&self.inner.profile(), &serializer).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
 }
 
 #[wasm_bindgen]
@@ -66,6 +533,22 @@ impl CreditController {
         self.inner.consume(amount)
     }
 
+    /// Takes as many of `max` credits as are available; returns the count.
+    #[wasm_bindgen(js_name = consumeUpTo)]
+    pub fn consume_up_to(&mut self, max: u32) -> u32 {
+        self.inner.consume_up_to(max)
+    }
+
+    #[wasm_bindgen(js_name = consumeAll)]
+    pub fn consume_all(&mut self) -> u32 {
+        self.inner.consume_all()
+    }
+
+    /// Zeroes the balance without counting it as consumed; returns the old one.
+    pub fn drain(&mut self) -> u32 {
+        self.inner.drain()
+    }
+
     #[wasm_bindgen(js_name = addCredits)]
     pub fn add_credits(&mut self, amount: u32) {
         self.inner.add(amount);
@@ -75,6 +558,45 @@ impl CreditController {
     pub fn available_credits(&self) -> u32 {
         self.inner.available()
     }
+
+    /// `{ totalConsumed, totalAdded, consumeFailures, exhaustionEvents }`.
+    pub fn stats(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.inner.stats()).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+}
+
+#[wasm_bindgen]
+pub struct ExponentialMovingAverage {
+    inner: operators::EmaOperator,
+}
+
+#[wasm_bindgen]
+impl ExponentialMovingAverage {
+    #[wasm_bindgen(constructor)]
+    pub fn new(alpha: f32) -> Result<ExponentialMovingAverage, JsValue> {
+        let inner = operators::EmaOperator::new(alpha).map_err(|e| JsValue::from_str(&e))?;
+        Ok(Self { inner })
+    }
+
+    pub fn push(&mut self, value: f32) -> f32 {
+        self.inner.push(value)
+    }
+
+    #[wasm_bindgen(js_name = pushBatch)]
+    pub fn push_batch(&mut self, data: &Float32Array) -> Float32Array {
+        let output: Vec<f32> = data.to_vec().into_iter().map(|x| self.inner.push(x)).collect();
+        let result = Float32Array::new_with_length(output.len() as u32);
+        result.copy_from(&output);
+        result
+    }
+
+    pub fn current(&self) -> Option<f32> {
+        self.inner.current()
+    }
+
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
 }
 
 // BYOB helpers (minimal) used by TS BYOB utilities
@@ -92,3 +614,8 @@ pub fn create_float32_view(buffer: &ArrayBuffer) -> Float32Array {
 pub fn create_float64_view(buffer: &ArrayBuffer) -> Float64Array {
     js_sys::Float64Array::new(buffer)
 }
+
+#[wasm_bindgen]
+pub fn create_int32_view(buffer: &ArrayBuffer) -> Int32Array {
+    js_sys::Int32Array::new(buffer)
+}
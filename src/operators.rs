@@ -1,72 +1,487 @@
-use js_sys::{Float32Array, Float64Array};
+use js_sys::{Array, Float32Array, Float64Array, Int32Array};
+use wasm_bindgen::JsValue;
+use crate::dispatch;
 use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
 
-pub fn process_float32_batch(data: &Float32Array, operation: &str) -> Float32Array {
-    let input = data.to_vec();
-    
-    let output = match operation {
-        "square" => input.iter().map(|x| x * x).collect(),
-        "sqrt" => input.iter().map(|x| x.sqrt()).collect(),
-        "normalize" => {
-            let sum: f32 = input.iter().sum();
-            let mean = sum / input.len() as f32;
-            let variance: f32 = input.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / input.len() as f32;
-            let std_dev = variance.sqrt();
-            input.iter().map(|x| (x - mean) / std_dev).collect()
+/// Added to the standard deviation by "normalize" so constant (and
+/// single-element) inputs map to zeros instead of NaN.
+pub const NORMALIZE_EPSILON: f64 = 1e-8;
+
+/// A batch or window operation. Elementwise operations ("square" through
+/// "reciprocal") apply to batches; reductions ("mean" through percentiles)
+/// apply to `WindowedOperator`. Parses from and displays as the operation
+/// names the string-based JS entry points accept.
+///
+/// Out-of-domain inputs follow IEEE 754 rather than erroring, like "sqrt":
+/// "log"/"log2" give -inf at 0 and NaN below it, "reciprocal" gives ±inf at
+/// ±0, and "sign" maps ±0 to itself and NaN to NaN.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operation {
+    Square,
+    Sqrt,
+    Normalize,
+    /// `Normalize` with the mean and variance from `mean_variance_stable`.
+    NormalizeStable,
+    /// Min-max scaling to `[0, 1]`; a constant input maps to zeros.
+    MinMax,
+    CumSum,
+    Diff,
+    DiffPrependZero,
+    Relu,
+    Sigmoid,
+    Tanh,
+    Abs,
+    Sign,
+    Log,
+    Log2,
+    Exp,
+    Reciprocal,
+    Mean,
+    Max,
+    Min,
+    Sum,
+    Variance,
+    /// `Variance` from `mean_variance_stable`.
+    VarianceStable,
+    Std,
+    Median,
+    /// `p0`..=`p100`.
+    Percentile(f64),
+}
+
+impl Operation {
+    pub fn is_elementwise(&self) -> bool {
+        matches!(
+            self,
+            Operation::Square
+                | Operation::Sqrt
+                | Operation::Normalize
+                | Operation::NormalizeStable
+                | Operation::MinMax
+                | Operation::CumSum
+                | Operation::Diff
+                | Operation::DiffPrependZero
+                | Operation::Relu
+                | Operation::Sigmoid
+                | Operation::Tanh
+                | Operation::Abs
+                | Operation::Sign
+                | Operation::Log
+                | Operation::Log2
+                | Operation::Exp
+                | Operation::Reciprocal
+        )
+    }
+}
+
+impl FromStr for Operation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "square" => Operation::Square,
+            "sqrt" => Operation::Sqrt,
+            "normalize" => Operation::Normalize,
+            "normalize_stable" => Operation::NormalizeStable,
+            "minmax" => Operation::MinMax,
+            "cumsum" => Operation::CumSum,
+            "diff" => Operation::Diff,
+            "diff_prepend_zero" => Operation::DiffPrependZero,
+            "relu" => Operation::Relu,
+            "sigmoid" => Operation::Sigmoid,
+            "tanh" => Operation::Tanh,
+            "abs" => Operation::Abs,
+            "sign" => Operation::Sign,
+            "log" => Operation::Log,
+            "log2" => Operation::Log2,
+            "exp" => Operation::Exp,
+            "reciprocal" => Operation::Reciprocal,
+            "mean" => Operation::Mean,
+            "max" => Operation::Max,
+            "min" => Operation::Min,
+            "sum" => Operation::Sum,
+            "variance" => Operation::Variance,
+            "variance_stable" => Operation::VarianceStable,
+            "std" => Operation::Std,
+            "median" => Operation::Median,
+            _ => s
+                .strip_prefix('p')
+                .and_then(|p| p.parse::<f64>().ok())
+                .filter(|p| (0.0..=100.0).contains(p))
+                .map(Operation::Percentile)
+                .ok_or_else(|| format!("Unsupported operation: {}", s))?,
+        })
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Operation::Square => "square",
+            Operation::Sqrt => "sqrt",
+            Operation::Normalize => "normalize",
+            Operation::NormalizeStable => "normalize_stable",
+            Operation::MinMax => "minmax",
+            Operation::CumSum => "cumsum",
+            Operation::Diff => "diff",
+            Operation::DiffPrependZero => "diff_prepend_zero",
+            Operation::Relu => "relu",
+            Operation::Sigmoid => "sigmoid",
+            Operation::Tanh => "tanh",
+            Operation::Abs => "abs",
+            Operation::Sign => "sign",
+            Operation::Log => "log",
+            Operation::Log2 => "log2",
+            Operation::Exp => "exp",
+            Operation::Reciprocal => "reciprocal",
+            Operation::Mean => "mean",
+            Operation::Max => "max",
+            Operation::Min => "min",
+            Operation::Sum => "sum",
+            Operation::Variance => "variance",
+            Operation::VarianceStable => "variance_stable",
+            Operation::Std => "std",
+            Operation::Median => "median",
+            Operation::Percentile(p) => return write!(f, "p{}", p),
+        };
+        f.write_str(name)
+    }
+}
+
+/// Operation names accepted by `process_float32_batch` and
+/// `process_float64_batch`.
+pub const FLOAT32_OPERATIONS: &[&str] = &[
+    "square", "sqrt", "normalize", "normalize_stable", "minmax", "cumsum", "diff", "diff_prepend_zero",
+    "relu", "sigmoid", "tanh", "abs", "sign", "log", "log2", "exp", "reciprocal",
+];
+
+/// Applies an elementwise `operation` to a batch. Every operation preserves
+/// length except `Diff`, whose output has one element fewer than the input
+/// (`DiffPrependZero` keeps the length by starting with 0).
+pub fn apply_float32(input: &[f32], operation: Operation) -> Result<Vec<f32>, String> {
+    Ok(match operation {
+        Operation::Square => input.iter().map(|x| x * x).collect(),
+        Operation::Sqrt => input.iter().map(|x| x.sqrt()).collect(),
+        Operation::Normalize => dispatch::normalize(input, NORMALIZE_EPSILON as f32),
+        Operation::NormalizeStable => {
+            let wide: Vec<f64> = input.iter().map(|&x| x as f64).collect();
+            normalize_stable_f64(&wide, NORMALIZE_EPSILON).into_iter().map(|x| x as f32).collect()
         }
-        "cumsum" => {
-            let mut sum = 0.0f32;
-            input.iter().map(|x| {
-                sum += x;
-                sum
-            }).collect()
+        Operation::MinMax => min_max_scale(input, 0.0, 1.0),
+        Operation::CumSum => dispatch::cumsum(input),
+        Operation::Diff => dispatch::diff(input),
+        Operation::DiffPrependZero => prepend_zero(dispatch::diff(input), input.len()),
+        Operation::Relu => dispatch::relu(input),
+        Operation::Sigmoid => dispatch::sigmoid(input),
+        Operation::Tanh => dispatch::tanh(input),
+        Operation::Abs => dispatch::abs(input),
+        // Unlike `signum`, ±0 and NaN map to themselves.
+        Operation::Sign => input.iter().map(|&x| if x == 0.0 || x.is_nan() { x } else { x.signum() }).collect(),
+        Operation::Log => input.iter().map(|x| x.ln()).collect(),
+        Operation::Log2 => input.iter().map(|x| x.log2()).collect(),
+        Operation::Exp => input.iter().map(|x| x.exp()).collect(),
+        Operation::Reciprocal => input.iter().map(|x| x.recip()).collect(),
+        op => return Err(format!("{} is not an elementwise operation", op)),
+    })
+}
+
+/// `apply_float32` for f64 batches. "square", "cumsum" and "normalize" use
+/// the f64x2 kernels when SIMD is available; the rest are scalar.
+pub fn apply_float64(input: &[f64], operation: Operation) -> Result<Vec<f64>, String> {
+    Ok(match operation {
+        Operation::Square => dispatch::square_f64(input),
+        Operation::Sqrt => input.iter().map(|x| x.sqrt()).collect(),
+        Operation::Normalize => dispatch::normalize_f64(input, NORMALIZE_EPSILON),
+        Operation::NormalizeStable => normalize_stable_f64(input, NORMALIZE_EPSILON),
+        Operation::MinMax => {
+            let min = input.iter().cloned().fold(f64::INFINITY, f64::min);
+            let max = input.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            if min == max {
+                vec![0.0; input.len()]
+            } else {
+                input.iter().map(|x| (x - min) / (max - min)).collect()
+            }
         }
-        _ => input,
+        Operation::CumSum => dispatch::cumsum_f64(input),
+        Operation::Diff => diff_f64(input),
+        Operation::DiffPrependZero => prepend_zero(diff_f64(input), input.len()),
+        Operation::Relu => input.iter().map(|x| x.max(0.0)).collect(),
+        Operation::Sigmoid => input.iter().map(|x| 1.0 / (1.0 + (-x).exp())).collect(),
+        Operation::Tanh => input.iter().map(|x| x.tanh()).collect(),
+        Operation::Abs => input.iter().map(|x| x.abs()).collect(),
+        Operation::Sign => input.iter().map(|&x| if x == 0.0 || x.is_nan() { x } else { x.signum() }).collect(),
+        Operation::Log => input.iter().map(|x| x.ln()).collect(),
+        Operation::Log2 => input.iter().map(|x| x.log2()).collect(),
+        Operation::Exp => input.iter().map(|x| x.exp()).collect(),
+        Operation::Reciprocal => input.iter().map(|x| x.recip()).collect(),
+        op => return Err(format!("{} is not an elementwise operation", op)),
+    })
+}
+
+/// String form of `apply_float32`. Unknown operations return a copy of the
+/// input; prefer `process_float32_batch_checked`, which reports them.
+pub fn process_float32_batch(data: &Float32Array, operation: &str) -> Float32Array {
+    process_float32_batch_checked(data, operation).unwrap_or_else(|_| Float32Array::new(data))
+}
+
+pub fn process_float32_batch_checked(data: &Float32Array, operation: &str) -> Result<Float32Array, JsValue> {
+    let output = operation
+        .parse::<Operation>()
+        .and_then(|op| apply_float32(&data.to_vec(), op))
+        .map_err(|_| unsupported_operation(operation, FLOAT32_OPERATIONS))?;
+
+    let result = Float32Array::new_with_length(output.len() as u32);
+    result.copy_from(&output);
+    Ok(result)
+}
+
+// Maps `[min, max]` of `input` onto `[lo, hi]`, with the bounds found by
+// `dispatch::min_max` (SIMD when enabled). A constant input has no range to
+// scale and maps to `lo`.
+fn min_max_scale(input: &[f32], lo: f32, hi: f32) -> Vec<f32> {
+    let (min, max) = dispatch::min_max(input);
+    if min == max {
+        return vec![lo; input.len()];
+    }
+    let factor = (hi - lo) / (max - min);
+    input.iter().map(|x| lo + (x - min) * factor).collect()
+}
+
+// Restores the length `diff` dropped; empty input stays empty.
+fn prepend_zero<T: Default + Clone>(diff: Vec<T>, len: usize) -> Vec<T> {
+    if len == 0 {
+        return diff;
+    }
+    let mut output = Vec::with_capacity(len);
+    output.push(T::default());
+    output.extend(diff);
+    output
+}
+
+fn unsupported_operation(operation: &str, supported: &[&str]) -> JsValue {
+    JsValue::from_str(&format!(
+        "Unsupported operation: {} (expected one of: {})",
+        operation,
+        supported.join(", ")
+    ))
+}
+
+/// `FLOAT32_OPERATIONS` as a JS array of strings.
+pub fn supported_operations() -> Array {
+    FLOAT32_OPERATIONS.iter().map(|op| JsValue::from_str(op)).collect()
+}
+
+/// String form of `apply_float64`. Unknown operations return a copy of the
+/// input; prefer `process_float64_batch_checked`, which reports them.
+pub fn process_float64_batch(data: &Float64Array, operation: &str) -> Float64Array {
+    process_float64_batch_checked(data, operation).unwrap_or_else(|_| Float64Array::new(data))
+}
+
+pub fn process_float64_batch_checked(data: &Float64Array, operation: &str) -> Result<Float64Array, JsValue> {
+    let output = operation
+        .parse::<Operation>()
+        .and_then(|op| apply_float64(&data.to_vec(), op))
+        .map_err(|_| unsupported_operation(operation, FLOAT32_OPERATIONS))?;
+
+    let result = Float64Array::new_with_length(output.len() as u32);
+    result.copy_from(&output);
+    Ok(result)
+}
+
+fn diff_f64(input: &[f64]) -> Vec<f64> {
+    input.windows(2).map(|w| w[1] - w[0]).collect()
+}
+
+/// Population mean and variance in one pass with Welford's algorithm,
+/// accumulated in f64. Unlike summing first and dividing, it does not lose
+/// the small spread of values sitting on a large offset. `None` when empty.
+pub fn mean_variance_stable(values: impl IntoIterator<Item = f64>) -> Option<(f64, f64)> {
+    let (mut count, mut mean, mut m2) = (0u64, 0.0f64, 0.0f64);
+    for x in values {
+        count += 1;
+        let delta = x - mean;
+        mean += delta / count as f64;
+        m2 += delta * (x - mean);
+    }
+    (count > 0).then(|| (mean, m2 / count as f64))
+}
+
+fn normalize_stable_f64(input: &[f64], epsilon: f64) -> Vec<f64> {
+    let Some((mean, variance)) = mean_variance_stable(input.iter().copied()) else {
+        return vec![];
     };
-    
+    let std_dev = variance.sqrt() + epsilon;
+    if std_dev == 0.0 {
+        return vec![0.0; input.len()];
+    }
+    input.iter().map(|x| (x - mean) / std_dev).collect()
+}
+
+/// Z-score normalization with a caller-chosen epsilon (see `NORMALIZE_EPSILON`).
+pub fn normalize_float32(data: &Float32Array, epsilon: f32) -> Float32Array {
+    let output = dispatch::normalize(&data.to_vec(), epsilon);
     let result = Float32Array::new_with_length(output.len() as u32);
     result.copy_from(&output);
     result
 }
 
-pub fn process_float64_batch(data: &Float64Array, operation: &str) -> Float64Array {
+pub fn normalize_float64(data: &Float64Array, epsilon: f64) -> Float64Array {
+    let output = dispatch::normalize_f64(&data.to_vec(), epsilon);
+    let result = Float64Array::new_with_length(output.len() as u32);
+    result.copy_from(&output);
+    result
+}
+
+/// Elementwise combination of two equal-length arrays: "add", "sub", "mul",
+/// "div", "min" or "max". "min"/"max" ignore a NaN in one operand.
+pub fn combine_float32(a: &Float32Array, b: &Float32Array, op: &str) -> Result<Float32Array, JsValue> {
+    if a.length() != b.length() {
+        return Err(JsValue::from_str(&format!(
+            "length mismatch: {} vs {}",
+            a.length(),
+            b.length()
+        )));
+    }
+
+    let left = a.to_vec();
+    let right = b.to_vec();
+    let output = match op {
+        "add" => dispatch::vector_add(&left, &right),
+        "sub" => dispatch::vector_sub(&left, &right),
+        "mul" => dispatch::vector_mul(&left, &right),
+        "div" => dispatch::vector_div(&left, &right),
+        "min" => dispatch::vector_min(&left, &right),
+        "max" => dispatch::vector_max(&left, &right),
+        _ => return Err(JsValue::from_str(&format!("Unknown combine operation: {}", op))),
+    };
+
+    let result = Float32Array::new_with_length(output.len() as u32);
+    result.copy_from(&output);
+    Ok(result)
+}
+
+fn float_param(params: &JsValue, name: &str) -> Result<f64, JsValue> {
+    js_sys::Reflect::get(params, &name.into())
+        .ok()
+        .and_then(|v| v.as_f64())
+        .ok_or_else(|| JsValue::from_str(&format!("{} must be a number", name)))
+}
+
+// Rejects fractions, NaN/infinities and values outside `i32` instead of
+// truncating or saturating them.
+fn int_param(params: &JsValue, name: &str) -> Result<i32, JsValue> {
+    let value = float_param(params, name)?;
+    if value.fract() != 0.0 || value < i32::MIN as f64 || value > i32::MAX as f64 {
+        return Err(JsValue::from_str(&format!("{} must be an integer in the i32 range", name)));
+    }
+    Ok(value as i32)
+}
+
+/// Parameterized float32 operations: "clamp" reads `min`/`max`, "scale"
+/// multiplies by `value`, "offset" adds `value` and "minmax_range" scales
+/// like "minmax" onto `[lo, hi]` (a constant input maps to `lo`). Any other
+/// operation is handled by `process_float32_batch_checked` and ignores
+/// `params`.
+pub fn process_float32_batch_with_params(
+    data: &Float32Array,
+    operation: &str,
+    params: &JsValue,
+) -> Result<Float32Array, JsValue> {
     let input = data.to_vec();
-    let output = match operation {
-        "square" => input.iter().map(|x| x * x).collect(),
-        "sqrt" => input.iter().map(|x| x.sqrt()).collect(),
-        "normalize" => {
-            let sum: f64 = input.iter().sum();
-            let mean = sum / input.len() as f64;
-            let variance: f64 = input.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / input.len() as f64;
-            let std_dev = variance.sqrt();
-            input.iter().map(|x| (x - mean) / std_dev).collect()
+    let output: Vec<f32> = match operation {
+        "clamp" => {
+            let min = float_param(params, "min")? as f32;
+            let max = float_param(params, "max")? as f32;
+            if min.is_nan() || max.is_nan() || min > max {
+                return Err(JsValue::from_str("clamp requires min <= max"));
+            }
+            dispatch::clamp(&input, min, max)
         }
+        "scale" => {
+            let value = float_param(params, "value")? as f32;
+            dispatch::scalar_mul(&input, value)
+        }
+        "offset" => {
+            let value = float_param(params, "value")? as f32;
+            dispatch::scalar_add(&input, value)
+        }
+        "minmax_range" => {
+            let lo = float_param(params, "lo")? as f32;
+            let hi = float_param(params, "hi")? as f32;
+            if !lo.is_finite() || !hi.is_finite() || lo > hi {
+                return Err(JsValue::from_str("minmax_range requires finite lo <= hi"));
+            }
+            min_max_scale(&input, lo, hi)
+        }
+        _ => return process_float32_batch_checked(data, operation),
+    };
+    let result = Float32Array::new_with_length(output.len() as u32);
+    result.copy_from(&output);
+    Ok(result)
+}
+
+/// Integer batch operations. All arithmetic saturates at `i32::MIN`/`i32::MAX`
+/// rather than wrapping, so `abs(i32::MIN)` is `i32::MAX` and a running sum
+/// sticks at the bound instead of flipping sign.
+///
+/// "clamp" reads `min`/`max` and "saturating_add_scalar" reads `value` from
+/// `params`; both must be integers. Any other operation is an error.
+pub fn process_int32_batch(data: &Int32Array, operation: &str, params: &JsValue) -> Result<Int32Array, JsValue> {
+    let input = data.to_vec();
+    let output: Vec<i32> = match operation {
+        "abs" => input.iter().map(|x| x.saturating_abs()).collect(),
         "cumsum" => {
-            let mut sum = 0.0f64;
-            input.iter().map(|x| { sum += x; sum }).collect()
+            let mut sum = 0i32;
+            input.iter().map(|x| { sum = sum.saturating_add(*x); sum }).collect()
         }
-        _ => input,
+        "clamp" => {
+            let min = int_param(params, "min")?;
+            let max = int_param(params, "max")?;
+            if min > max {
+                return Err(JsValue::from_str("clamp requires min <= max"));
+            }
+            input.iter().map(|x| (*x).clamp(min, max)).collect()
+        }
+        "saturating_add_scalar" => {
+            let value = int_param(params, "value")?;
+            input.iter().map(|x| x.saturating_add(value)).collect()
+        }
+        _ => return Err(JsValue::from_str(&format!("Unsupported int32 operation: {}", operation))),
     };
-    let result = Float64Array::new_with_length(output.len() as u32);
+    let result = Int32Array::new_with_length(output.len() as u32);
     result.copy_from(&output);
-    result
+    Ok(result)
 }
 
+#[derive(Clone)]
 pub struct WindowedOperator<T> {
     window_size: usize,
     buffer: VecDeque<T>,
-    operation: String,
+    operation: Operation,
+    emit_partial: bool,
 }
 
 impl<T: Clone> WindowedOperator<T> {
-    pub fn new(window_size: usize, operation: String) -> Self {
+    /// `compute` yields `None` for elementwise operations.
+    pub fn new(window_size: usize, operation: Operation) -> Self {
         Self {
             window_size,
             buffer: VecDeque::with_capacity(window_size),
             operation,
+            emit_partial: false,
         }
     }
 
+    /// With `emit_partial`, `compute` answers from the first push, over
+    /// whatever is buffered until the window fills (a warm-up for running
+    /// statistics) instead of waiting for `is_ready`.
+    pub fn with_emit_partial(mut self, emit_partial: bool) -> Self {
+        self.emit_partial = emit_partial;
+        self
+    }
+
     pub fn push(&mut self, value: T) {
         if self.buffer.len() >= self.window_size {
             self.buffer.pop_front();
@@ -81,40 +496,310 @@ impl<T: Clone> WindowedOperator<T> {
     pub fn get_window(&self) -> Vec<T> {
         self.buffer.iter().cloned().collect()
     }
+
+    /// Empties the window, e.g. to turn it into a tumbling window.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
+    fn can_compute(&self) -> bool {
+        if self.emit_partial {
+            !self.buffer.is_empty()
+        } else {
+            self.is_ready()
+        }
+    }
 }
 
 impl WindowedOperator<f32> {
     pub fn compute(&self) -> Option<f32> {
-        if !self.is_ready() {
+        if !self.can_compute() {
             return None;
         }
+        compute_window(self.operation, self.get_window())
+    }
+}
+
+impl WindowedOperator<f64> {
+    pub fn compute(&self) -> Option<f64> {
+        if !self.can_compute() {
+            return None;
+        }
+        compute_window(self.operation, self.get_window())
+    }
+}
 
-        let window: Vec<f32> = self.get_window();
-        
-        match self.operation.as_str() {
-            "mean" => {
-                let sum: f32 = window.iter().sum();
-                Some(sum / window.len() as f32)
+// The float operations the windowed statistics need, so `compute` is written
+// once for both f32 and f64.
+trait WindowStat:
+    Copy
+    + PartialOrd
+    + std::iter::Sum
+    + std::ops::Add<Output = Self>
+    + std::ops::Sub<Output = Self>
+    + std::ops::Mul<Output = Self>
+    + std::ops::Div<Output = Self>
+{
+    const INFINITY: Self;
+    const NEG_INFINITY: Self;
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+    fn sqrt(self) -> Self;
+    fn min(self, other: Self) -> Self;
+    fn max(self, other: Self) -> Self;
+    fn total_cmp(&self, other: &Self) -> std::cmp::Ordering;
+}
+
+macro_rules! impl_window_stat {
+    ($t:ty) => {
+        impl WindowStat for $t {
+            const INFINITY: Self = <$t>::INFINITY;
+            const NEG_INFINITY: Self = <$t>::NEG_INFINITY;
+            fn from_f64(value: f64) -> Self {
+                value as $t
+            }
+            fn to_f64(self) -> f64 {
+                self as f64
             }
-            "max" => window.iter().cloned().fold(f32::NEG_INFINITY, f32::max).into(),
-            "min" => window.iter().cloned().fold(f32::INFINITY, f32::min).into(),
-            "sum" => Some(window.iter().sum()),
-            "variance" => {
-                let mean = window.iter().sum::<f32>() / window.len() as f32;
-                let variance = window.iter()
-                    .map(|x| (x - mean).powi(2))
-                    .sum::<f32>() / window.len() as f32;
-                Some(variance)
+            fn sqrt(self) -> Self {
+                <$t>::sqrt(self)
             }
-            "std" => {
-                let mean = window.iter().sum::<f32>() / window.len() as f32;
-                let variance = window.iter()
-                    .map(|x| (x - mean).powi(2))
-                    .sum::<f32>() / window.len() as f32;
-                Some(variance.sqrt())
+            fn min(self, other: Self) -> Self {
+                <$t>::min(self, other)
             }
-            _ => None,
+            fn max(self, other: Self) -> Self {
+                <$t>::max(self, other)
+            }
+            fn total_cmp(&self, other: &Self) -> std::cmp::Ordering {
+                <$t>::total_cmp(self, other)
+            }
+        }
+    };
+}
+
+impl_window_stat!(f32);
+impl_window_stat!(f64);
+
+fn compute_window<T: WindowStat>(operation: Operation, window: Vec<T>) -> Option<T> {
+    let len = T::from_f64(window.len() as f64);
+    let variance = |window: &[T]| {
+        let mean = window.iter().cloned().sum::<T>() / len;
+        window.iter().map(|&x| (x - mean) * (x - mean)).sum::<T>() / len
+    };
+
+    match operation {
+        Operation::Mean => Some(window.iter().cloned().sum::<T>() / len),
+        Operation::Max => Some(window.iter().cloned().fold(T::NEG_INFINITY, T::max)),
+        Operation::Min => Some(window.iter().cloned().fold(T::INFINITY, T::min)),
+        Operation::Sum => Some(window.iter().cloned().sum()),
+        Operation::Variance => Some(variance(&window)),
+        Operation::VarianceStable => {
+            mean_variance_stable(window.iter().map(|x| x.to_f64())).map(|(_, variance)| T::from_f64(variance))
+        }
+        Operation::Std => Some(variance(&window).sqrt()),
+        Operation::Median => percentile(window, 50.0),
+        Operation::Percentile(p) => percentile(window, p),
+        _ => None,
+    }
+}
+
+/// Linearly interpolated percentile (`p` in `0..=100`), matching NumPy's
+/// default. Sorts a copy of the window on every call, so it costs
+/// O(w log w) per compute; fine for the small windows this operator is used
+/// with, but prefer "mean"/"min"/"max" for large ones.
+fn percentile<T: WindowStat>(mut window: Vec<T>, p: f64) -> Option<T> {
+    if window.is_empty() {
+        return None;
+    }
+    window.sort_by(|a, b| a.total_cmp(b));
+
+    let rank = p / 100.0 * (window.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let fraction = T::from_f64(rank - lower as f64);
+    Some(window[lower] + (window[upper] - window[lower]) * fraction)
+}
+
+/// Exponential moving average: `current = alpha * value + (1 - alpha) * current`.
+/// The first value pushed is returned unchanged and seeds the average.
+#[derive(Clone, Debug)]
+pub struct EmaOperator {
+    alpha: f32,
+    current: Option<f32>,
+}
+
+impl EmaOperator {
+    /// `alpha` must lie in `(0, 1]`; 1 disables smoothing.
+    pub fn new(alpha: f32) -> Result<Self, String> {
+        if !(alpha > 0.0 && alpha <= 1.0) {
+            return Err(format!("alpha must be in (0, 1], got {}", alpha));
+        }
+        Ok(Self { alpha, current: None })
+    }
+
+    pub fn push(&mut self, value: f32) -> f32 {
+        let next = match self.current {
+            Some(current) => self.alpha * value + (1.0 - self.alpha) * current,
+            None => value,
+        };
+        self.current = Some(next);
+        next
+    }
+
+    pub fn current(&self) -> Option<f32> {
+        self.current
+    }
+
+    pub fn reset(&mut self) {
+        self.current = None;
+    }
+}
+
+/// Fixed-width histogram over `[min, max]` split into `bins` equal bins.
+/// Each bin is closed below and open above, except the last, which also
+/// takes `max`. Values outside the range are counted in `underflow` /
+/// `overflow`; NaN is ignored.
+#[derive(Clone, Debug)]
+pub struct Histogram {
+    min: f64,
+    max: f64,
+    counts: Vec<u64>,
+    underflow: u64,
+    overflow: u64,
+}
+
+impl Histogram {
+    pub fn new(min: f64, max: f64, bins: usize) -> Result<Self, String> {
+        if bins == 0 {
+            return Err("bins must be at least 1".to_string());
+        }
+        if !min.is_finite() || !max.is_finite() || min >= max {
+            return Err(format!("histogram range must be finite with min < max, got [{}, {}]", min, max));
         }
+        Ok(Self { min, max, counts: vec![0; bins], underflow: 0, overflow: 0 })
+    }
+
+    pub fn push(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        if value < self.min {
+            self.underflow += 1;
+        } else if value > self.max {
+            self.overflow += 1;
+        } else {
+            let bins = self.counts.len();
+            let bin = ((value - self.min) / (self.max - self.min) * bins as f64) as usize;
+            self.counts[bin.min(bins - 1)] += 1;
+        }
+    }
+
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    pub fn underflow(&self) -> u64 {
+        self.underflow
+    }
+
+    pub fn overflow(&self) -> u64 {
+        self.overflow
+    }
+
+    /// `[lower, upper)` edges of bin `index`.
+    pub fn bin_range(&self, index: usize) -> (f64, f64) {
+        let width = (self.max - self.min) / self.counts.len() as f64;
+        (self.min + width * index as f64, self.min + width * (index + 1) as f64)
+    }
+
+    pub fn reset(&mut self) {
+        self.counts.iter_mut().for_each(|c| *c = 0);
+        self.underflow = 0;
+        self.overflow = 0;
+    }
+}
+
+/// How `Resampler` fills a grid point between two samples.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResampleMethod {
+    /// The closer of the two samples; the earlier one on a tie.
+    Nearest,
+    /// Straight line between the two samples.
+    Linear,
+    /// The earlier sample (zero-order hold).
+    Hold,
+}
+
+impl FromStr for ResampleMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nearest" => Ok(ResampleMethod::Nearest),
+            "linear" => Ok(ResampleMethod::Linear),
+            "hold" => Ok(ResampleMethod::Hold),
+            _ => Err(format!("Unsupported resample method: {}", s)),
+        }
+    }
+}
+
+/// Resamples `(timestamp, value)` samples onto the grid of multiples of
+/// `interval`. A grid point is emitted once the first sample at or after it
+/// has arrived, so output lags input by at most one sample. A gap spanning
+/// several intervals is filled at every grid point inside it, the same way
+/// as a short one: nothing is skipped or marked missing. Grid points before
+/// the first sample are never emitted, and one landing exactly on the last
+/// sample comes out of `finish`. Samples that are not later than the
+/// previous one are dropped.
+#[derive(Clone, Debug)]
+pub struct Resampler {
+    interval: u64,
+    method: ResampleMethod,
+    previous: Option<(u64, f64)>,
+    next_point: u64,
+}
+
+impl Resampler {
+    pub fn new(interval: u64, method: ResampleMethod) -> Result<Self, String> {
+        if interval == 0 {
+            return Err("resample interval must be positive".to_string());
+        }
+        Ok(Self { interval, method, previous: None, next_point: 0 })
+    }
+
+    /// Grid points now known up to (but excluding) `t`, oldest first.
+    pub fn push(&mut self, t: u64, value: f64) -> Vec<(u64, f64)> {
+        let Some((prev_t, prev_value)) = self.previous else {
+            self.previous = Some((t, value));
+            self.next_point = t.div_ceil(self.interval) * self.interval;
+            return Vec::new();
+        };
+        if t <= prev_t {
+            return Vec::new();
+        }
+        let mut points = Vec::new();
+        while self.next_point < t {
+            let at = self.next_point;
+            let filled = match self.method {
+                ResampleMethod::Hold => prev_value,
+                ResampleMethod::Nearest if at - prev_t <= t - at => prev_value,
+                ResampleMethod::Nearest => value,
+                ResampleMethod::Linear => {
+                    let fraction = (at - prev_t) as f64 / (t - prev_t) as f64;
+                    prev_value + (value - prev_value) * fraction
+                }
+            };
+            points.push((at, filled));
+            self.next_point += self.interval;
+        }
+        self.previous = Some((t, value));
+        points
+    }
+
+    /// The grid point on the last sample, if there is one, then starts over.
+    pub fn finish(&mut self) -> Option<(u64, f64)> {
+        let last = self.previous.take()?;
+        (last.0 == self.next_point).then_some(last)
     }
 }
 
@@ -129,10 +814,83 @@ where
 {
     input
         .chunks(batch_size)
-        .flat_map(|chunk| processor(chunk))
+        .flat_map(processor)
         .collect()
 }
 
+/// Like `batch_process`, but chunks are processed on the rayon pool when the
+/// `parallel` feature is enabled. Output order always matches input order.
+pub fn par_batch_process<T, F, R>(
+    input: Vec<T>,
+    batch_size: usize,
+    processor: F,
+) -> Vec<R>
+where
+    F: Fn(&[T]) -> Vec<R> + Send + Sync,
+    T: Clone + Send + Sync,
+    R: Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        input
+            .par_chunks(batch_size)
+            .flat_map_iter(processor)
+            .collect()
+    }
+    
+    #[cfg(not(feature = "parallel"))]
+    {
+        batch_process(input, batch_size, processor)
+    }
+}
+
+pub fn fold<T, A, F>(input: &[T], init: A, f: F) -> A
+where
+    F: FnMut(A, &T) -> A,
+{
+    input.iter().fold(init, f)
+}
+
+/// Like `fold`, but stops at the first step that fails and returns its error.
+pub fn try_fold<T, A, E, F>(input: &[T], init: A, mut f: F) -> Result<A, E>
+where
+    F: FnMut(A, &T) -> Result<A, E>,
+{
+    let mut acc = init;
+    for item in input {
+        acc = f(acc, item)?;
+    }
+    Ok(acc)
+}
+
+/// Folds chunks of `input` on the rayon pool when the `parallel` feature is
+/// enabled, merging the partial results with `combine`. `combine` must be
+/// associative and `init` its identity (e.g. 0 for a sum), since `init`
+/// seeds every partial fold. Without the feature this is a sequential `fold`.
+pub fn par_fold<T, A, F, C>(input: &[T], init: A, f: F, combine: C) -> A
+where
+    T: Sync,
+    A: Clone + Send + Sync,
+    F: Fn(A, &T) -> A + Send + Sync,
+    C: Fn(A, A) -> A + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        input
+            .par_iter()
+            .fold(|| init.clone(), &f)
+            .reduce(|| init.clone(), combine)
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        let _ = combine;
+        fold(input, init, f)
+    }
+}
+
 pub fn parallel_map<T, F, R>(
     input: Vec<T>,
     mapper: F,
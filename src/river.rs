@@ -1,42 +1,1062 @@
 use wasm_bindgen::prelude::*;
-use js_sys::{Array, Function, Uint8Array, Float32Array, Promise};
-use web_sys::{ReadableStream, WritableStream, AbortSignal};
+use wasm_bindgen_futures::JsFuture;
+use js_sys::{Array, Function, Object, Promise, Uint8Array, Float32Array};
+use web_sys::{ReadableStream, ReadableStreamDefaultReader, AbortController, AbortSignal};
 use std::sync::Arc;
 use std::sync::Mutex;
-use std::collections::VecDeque;
 use futures::channel::mpsc;
-use futures::stream::{Stream, StreamExt};
+use futures::future::{self, Either};
+use futures::SinkExt;
+use futures::stream::{self, AbortHandle, Abortable, LocalBoxStream, Stream, StreamExt};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::pin::Pin;
-use std::task::{Context, Poll};
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use crate::backpressure::CreditManager;
+use crate::errors::{river_error, CREDIT_EXHAUSTED, OPERATOR_THREW, READ_ERROR};
+use crate::kernels::{validate_params, Kernel, KernelOutput};
+use crate::operators::{EmaOperator, Histogram, Operation, ResampleMethod, Resampler, WindowedOperator};
 
+/// Equality is by value for numbers, bytes and floats, and `===` for
+/// `JsValue`s (so objects compare by identity).
+#[derive(Clone, PartialEq)]
 pub enum RiverValue {
     Number(f64),
     Bytes(Vec<u8>),
     Float32Array(Vec<f32>),
     JsValue(JsValue),
+    /// `value` observed at `t` (ms). Reaches JS as `{ t, value }`.
+    Timestamped { t: u64, value: Box<RiverValue> },
 }
 
+#[derive(Clone)]
 pub struct RiverCore {
     source: RiverSource,
     operators: Vec<Operator>,
-    error_handler: Option<Function>,
-    terminate_on_error: bool,
+    error_mode: ErrorMode,
+    zero_copy_output: bool,
+    // Set by `enable_profiling`; each subscription records into its own.
+    profile: Option<Profile>,
 }
 
+/// Time spent in one operator over a profiled subscription.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperatorTiming {
+    pub total_ms: f64,
+    pub calls: u64,
+}
+
+// Timings of one subscription, keyed `"<operator>#<position in the chain>"`.
+type Profile = Rc<RefCell<BTreeMap<String, OperatorTiming>>>;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+}
+
+/// What a river does with an error from its source or operators. The modes
+/// are exclusive: `rescue`, `terminate_on_error` and `skip_errors` each
+/// replace whichever was set before.
+///
+/// Every handler sees the error as a `RiverError` carrying an
+/// `ErrorInfo` code (see `crate::errors`), with what was thrown as `cause`.
+#[derive(Clone, Default)]
+pub enum ErrorMode {
+    /// Hand the error to the observer's `error` callback and keep going.
+    #[default]
+    Report,
+    /// Substitute the handler's result for a value whose operator threw
+    /// (`undefined` drops it); other errors are reported.
+    Rescue(Function),
+    /// Report the error, then end the subscription without completing.
+    Terminate,
+    /// Drop the offending element, passing the error to the optional
+    /// callback instead of the observer, and keep going.
+    Skip(Option<Function>),
+}
+
+impl ErrorMode {
+    fn rescue_handler(&self) -> Option<Function> {
+        match self {
+            ErrorMode::Rescue(handler) => Some(handler.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Receiving end of a channel river, shared by its subscriptions and senders.
+pub struct ChannelQueue {
+    receiver: futures::lock::Mutex<ValueStream>,
+    // values pushed through a `ChannelSender` and not yet read
+    queued: Cell<usize>,
+    // reported once the queued values are read, by `ChannelSender::fail`
+    failure: RefCell<Option<JsValue>>,
+}
+
+impl ChannelQueue {
+    fn new(receiver: ValueStream) -> Self {
+        Self {
+            receiver: futures::lock::Mutex::new(receiver),
+            queued: Cell::new(0),
+            failure: RefCell::new(None),
+        }
+    }
+}
+
+/// Producer side of `RiverCore::channel`. Clones feed the same river.
+#[derive(Clone)]
+pub struct ChannelSender {
+    sender: mpsc::UnboundedSender<RiverValue>,
+    queue: Rc<ChannelQueue>,
+}
+
+impl ChannelSender {
+    /// Queues `value`; `false` if the channel is closed or the river dropped.
+    pub fn push(&self, value: RiverValue) -> bool {
+        let sent = self.sender.unbounded_send(value).is_ok();
+        if sent {
+            self.queue.queued.set(self.queue.queued.get() + 1);
+        }
+        sent
+    }
+
+    /// Values pushed but not yet read by a subscription. Producers can
+    /// throttle themselves on this depth.
+    pub fn len(&self) -> usize {
+        self.queue.queued.get()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Stops further pushes, for every clone. Subscriptions still read the
+    /// queued values, then complete.
+    pub fn close(&self) {
+        self.sender.close_channel();
+    }
+
+    /// Closes the channel with `error`, which the subscription reading the
+    /// last queued value reports after it. No-op once closed.
+    pub fn fail(&self, error: JsValue) {
+        if !self.is_closed() {
+            *self.queue.failure.borrow_mut() = Some(error);
+            self.close();
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+}
+
+#[derive(Clone)]
 pub enum RiverSource {
     ReadableStream(ReadableStream),
     Array(Vec<RiverValue>),
-    Channel(mpsc::UnboundedReceiver<RiverValue>),
+    Channel(Rc<ChannelQueue>),
     Empty,
+    // Counter ticking every period (ms).
+    Interval(u32),
+    // `start + i * step` for i in 0..count, computed as the values are read.
+    Range { start: f64, step: f64, count: u64 },
+    // An async iterable, or an async iterator itself.
+    AsyncIterator(JsValue),
+    Zip(Box<RiverCore>, Box<RiverCore>),
+    // (shared upstream, partition branch: `Some(matches)` or `None` for all)
+    Shared(Rc<RefCell<SharedSource>>, Option<bool>),
+    CombineLatest(Box<RiverCore>, Box<RiverCore>),
+    // (primary, trigger)
+    SampleOn(Box<RiverCore>, Box<RiverCore>),
+    // Each river in turn, the next starting once the previous completes.
+    Concat(Vec<RiverCore>),
+}
+
+// Values (or read errors) produced for one subscription.
+type ValueStream = LocalBoxStream<'static, Result<RiverValue, JsValue>>;
+
+impl RiverSource {
+    fn values(&self) -> ValueStream {
+        match self {
+            RiverSource::ReadableStream(stream) => {
+                let reader: ReadableStreamDefaultReader = stream.get_reader().unchecked_into();
+                // A rejected read leaves the stream errored, so the error is
+                // reported once and the source ends.
+                stream::unfold(Some(reader), |reader| async move {
+                    let reader = reader?;
+                    match JsFuture::from(reader.read()).await {
+                        Ok(chunk) => {
+                            let done = js_sys::Reflect::get(&chunk, &"done".into())
+                                .ok()
+                                .and_then(|d| d.as_bool())
+                                .unwrap_or(false);
+                            if done {
+                                return None;
+                            }
+                            let value = js_sys::Reflect::get(&chunk, &"value".into()).unwrap_or(JsValue::UNDEFINED);
+                            Some((Ok(RiverValue::JsValue(value)), Some(reader)))
+                        }
+                        Err(e) => Some((Err(e), None)),
+                    }
+                })
+                .boxed_local()
+            }
+            RiverSource::Array(values) => stream::iter(values.clone().into_iter().map(Ok)).boxed_local(),
+            RiverSource::Channel(queue) => {
+                stream::unfold(queue.clone(), |queue| async move {
+                    let next = queue.receiver.lock().await.next().await;
+                    let Some(value) = next else {
+                        let failure = queue.failure.borrow_mut().take()?;
+                        return Some((Err(failure), queue));
+                    };
+                    // Saturating: values sent on a raw `from_channel` sender
+                    // were never counted.
+                    queue.queued.set(queue.queued.get().saturating_sub(1));
+                    Some((value, queue))
+                })
+                .boxed_local()
+            }
+            RiverSource::Empty => stream::empty().boxed_local(),
+            &RiverSource::Range { start, step, count } => {
+                stream::iter((0..count).map(move |i| Ok(RiverValue::Number(start + i as f64 * step)))).boxed_local()
+            }
+            RiverSource::AsyncIterator(iterable) => {
+                let iterator = match AsyncIteration::start(iterable) {
+                    Ok(iterator) => iterator,
+                    Err(e) => return stream::once(async move { Err(e) }).boxed_local(),
+                };
+                stream::unfold(Some(iterator), |iterator| async move {
+                    let mut iterator = iterator?;
+                    match iterator.next().await {
+                        Ok(Some(value)) => Some((Ok(RiverValue::JsValue(value)), Some(iterator))),
+                        Ok(None) => None,
+                        Err(e) => Some((Err(e), None)),
+                    }
+                })
+                .boxed_local()
+            }
+            RiverSource::Shared(shared, branch) => SharedSource::attach(shared, *branch).boxed_local(),
+            RiverSource::Interval(period_ms) => {
+                // The browser timer is cleared when the stream is dropped.
+                gloo_timers::future::IntervalStream::new(*period_ms)
+                    .enumerate()
+                    .map(|(tick, ())| Ok(RiverValue::Number(tick as f64)))
+                    .boxed_local()
+            }
+            RiverSource::Zip(left, right) => {
+                let left = (**left).clone().into_output();
+                let right = (**right).clone().into_output();
+                left.zip(right)
+                    .map(|pair| match pair {
+                        (Ok(a), Ok(b)) => Ok(pair_value(&a, &b)),
+                        (Err(e), _) | (_, Err(e)) => Err(e),
+                    })
+                    .boxed_local()
+            }
+            RiverSource::Concat(parts) => {
+                let state = (parts.clone().into_iter(), None::<ValueStream>, false);
+                stream::unfold(state, |(mut parts, mut current, failed)| async move {
+                    if failed {
+                        return None;
+                    }
+                    loop {
+                        let part = match current.as_mut() {
+                            Some(part) => part,
+                            None => current.insert(parts.next()?.into_output()),
+                        };
+                        match part.next().await {
+                            Some(Ok(value)) => return Some((Ok(value), (parts, current, false))),
+                            // The error ends the whole sequence.
+                            Some(Err(e)) => return Some((Err(e), (parts, current, true))),
+                            None => current = None,
+                        }
+                    }
+                })
+                .boxed_local()
+            }
+            RiverSource::CombineLatest(left, right) => {
+                let left = (**left).clone().into_output().map(|v| (true, v));
+                let right = (**right).clone().into_output().map(|v| (false, v));
+                let mut latest: (Option<RiverValue>, Option<RiverValue>) = (None, None);
+                stream::select(left, right)
+                    .filter_map(move |(is_left, item)| {
+                        let out = match item {
+                            Ok(value) => {
+                                if is_left {
+                                    latest.0 = Some(value);
+                                } else {
+                                    latest.1 = Some(value);
+                                }
+                                match &latest {
+                                    (Some(a), Some(b)) => Some(Ok(pair_value(a, b))),
+                                    _ => None,
+                                }
+                            }
+                            Err(e) => Some(Err(e)),
+                        };
+                        futures::future::ready(out)
+                    })
+                    .boxed_local()
+            }
+            RiverSource::SampleOn(primary, trigger) => {
+                let primary = (**primary).clone().into_output().map(|v| Some((true, v)));
+                // `None` marks the end of the trigger, which ends the sampling.
+                let trigger = (**trigger).clone().into_output().map(|v| Some((false, v)));
+                let trigger = trigger.chain(stream::once(async { None }));
+                let mut latest: Option<RiverValue> = None;
+                stream::select(primary, trigger)
+                    .take_while(|item| futures::future::ready(item.is_some()))
+                    .filter_map(move |item| {
+                        let out = match item {
+                            Some((true, Ok(value))) => {
+                                latest = Some(value);
+                                None
+                            }
+                            Some((false, Ok(_))) => latest.clone().map(Ok),
+                            Some((_, Err(e))) => Some(Err(e)),
+                            None => None,
+                        };
+                        futures::future::ready(out)
+                    })
+                    .boxed_local()
+            }
+        }
+    }
+}
+
+type SharedFeed = mpsc::UnboundedSender<Result<RiverValue, JsValue>>;
+
+// One upstream subscription fanned out to every attached observer, keeping
+// the last `replay_size` values for late joiners; see `RiverCore::share` and
+// `RiverCore::replay`. With a `router` (`RiverCore::partition`) each value
+// goes only to the observers of the branch the router picks for it.
+pub struct SharedSource {
+    upstream: RiverCore,
+    // (id, branch, feed)
+    subscribers: Vec<(u64, Option<bool>, SharedFeed)>,
+    next_id: u64,
+    running: Option<AbortHandle>,
+    replay_size: usize,
+    replay: VecDeque<RiverValue>,
+    router: Option<Function>,
+}
+
+impl SharedSource {
+    fn new(upstream: RiverCore, replay_size: usize) -> Self {
+        Self {
+            upstream,
+            subscribers: Vec::new(),
+            next_id: 0,
+            running: None,
+            replay_size,
+            replay: VecDeque::with_capacity(replay_size),
+            router: None,
+        }
+    }
+
+    fn record(&mut self, value: &RiverValue) {
+        if self.replay_size == 0 {
+            return;
+        }
+        if self.replay.len() == self.replay_size {
+            self.replay.pop_front();
+        }
+        self.replay.push_back(value.clone());
+    }
+
+    fn attach(shared: &Rc<RefCell<SharedSource>>, branch: Option<bool>) -> SharedSubscriber {
+        let (sender, receiver) = mpsc::unbounded();
+        let mut state = shared.borrow_mut();
+        let id = state.next_id;
+        state.next_id += 1;
+        if state.running.is_some() {
+            for value in &state.replay {
+                let _ = sender.unbounded_send(Ok(value.clone()));
+            }
+        } else {
+            state.replay.clear();
+        }
+        state.subscribers.push((id, branch, sender));
+
+        if state.running.is_none() {
+            let (abort, registration) = AbortHandle::new_pair();
+            state.running = Some(abort);
+            let mut upstream = Abortable::new(state.upstream.clone_base().into_output(), registration);
+            let driver = shared.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                while let Some(item) = upstream.next().await {
+                    // Routed once here, so the branches never disagree.
+                    // Errors, including a throwing router, reach every branch.
+                    let router = driver.borrow().router.clone();
+                    let (item, route) = match (item, router) {
+                        (Ok(value), Some(router)) => match router.call1(&JsValue::NULL, &value.to_js_value()) {
+                            Ok(matched) => (Ok(value), Some(matched.is_truthy())),
+                            Err(e) => (Err(e), None),
+                        },
+                        (item, _) => (item, None),
+                    };
+                    if let Ok(value) = &item {
+                        driver.borrow_mut().record(value);
+                    }
+                    let subscribers: Vec<_> = driver
+                        .borrow()
+                        .subscribers
+                        .iter()
+                        .filter(|(_, branch, _)| branch.is_none() || route.is_none() || *branch == route)
+                        .map(|(_, _, s)| s.clone())
+                        .collect();
+                    for subscriber in subscribers {
+                        let _ = subscriber.unbounded_send(item.clone());
+                    }
+                }
+                // Completion ends every attached observer; the next
+                // observer starts the upstream afresh.
+                if !upstream.is_aborted() {
+                    let mut state = driver.borrow_mut();
+                    state.subscribers.clear();
+                    state.running = None;
+                }
+            });
+        }
+
+        SharedSubscriber { receiver, shared: shared.clone(), id }
+    }
+}
+
+// An observer's feed from a `SharedSource`. Dropping it detaches the
+// observer, and the last one to detach stops the upstream.
+struct SharedSubscriber {
+    receiver: mpsc::UnboundedReceiver<Result<RiverValue, JsValue>>,
+    shared: Rc<RefCell<SharedSource>>,
+    id: u64,
+}
+
+impl Stream for SharedSubscriber {
+    type Item = Result<RiverValue, JsValue>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+impl Drop for SharedSubscriber {
+    fn drop(&mut self) {
+        let mut state = self.shared.borrow_mut();
+        state.subscribers.retain(|(id, _, _)| *id != self.id);
+        if state.subscribers.is_empty() {
+            if let Some(running) = state.running.take() {
+                running.abort();
+            }
+        }
+    }
+}
+
+// An in-progress `for await` over a JS async iterator. Dropping it before the
+// iterator finishes calls `return()`, so generators run their `finally`
+// blocks when a subscription stops early.
+struct AsyncIteration {
+    iterator: JsValue,
+    finished: bool,
+}
+
+impl AsyncIteration {
+    fn start(iterable: &JsValue) -> Result<Self, JsValue> {
+        let method = js_sys::Reflect::get(iterable, &js_sys::Symbol::async_iterator())?;
+        let iterator = match method.dyn_ref::<Function>() {
+            Some(method) => method.call0(iterable)?,
+            None => iterable.clone(),
+        };
+        if !js_sys::Reflect::get(&iterator, &"next".into())?.is_function() {
+            return Err(JsValue::from_str("value is not an async iterable"));
+        }
+        Ok(Self { iterator, finished: false })
+    }
+
+    async fn next(&mut self) -> Result<Option<JsValue>, JsValue> {
+        let next: Function = js_sys::Reflect::get(&self.iterator, &"next".into())?.unchecked_into();
+        let result = match next.call0(&self.iterator) {
+            Ok(promise) => JsFuture::from(Promise::resolve(&promise)).await,
+            Err(e) => Err(e),
+        };
+        let result = result.inspect_err(|_| self.finished = true)?;
+        if js_sys::Reflect::get(&result, &"done".into())?.is_truthy() {
+            self.finished = true;
+            return Ok(None);
+        }
+        Ok(Some(js_sys::Reflect::get(&result, &"value".into())?))
+    }
+}
+
+impl Drop for AsyncIteration {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        if let Ok(ret) = js_sys::Reflect::get(&self.iterator, &"return".into()) {
+            if let Some(ret) = ret.dyn_ref::<Function>() {
+                let _ = ret.call0(&self.iterator);
+            }
+        }
+    }
+}
+
+fn matches(predicate: &Option<Function>, value: &JsValue) -> Result<bool, JsValue> {
+    match predicate {
+        Some(predicate) => Ok(predicate.call1(&JsValue::NULL, value)?.is_truthy()),
+        None => Ok(true),
+    }
+}
+
+// Recovers from an operator error through a `rescue` handler, if any.
+fn rescue(error_handler: &Option<Function>, error: JsValue) -> Result<Option<RiverValue>, JsValue> {
+    let Some(handler) = error_handler else {
+        return Err(error);
+    };
+    let recovered = handler.call1(&JsValue::NULL, &error).map_err(|e| river_error(OPERATOR_THREW, e))?;
+    Ok((!recovered.is_undefined()).then_some(RiverValue::JsValue(recovered)))
+}
+
+// Collects values into an array emitted every `period_ms`, and once more
+// when the input ends. Dropping the stream clears the timer.
+fn buffer_time(input: ValueStream, period_ms: u32, emit_empty: bool) -> ValueStream {
+    let ticks = gloo_timers::future::IntervalStream::new(period_ms);
+    stream::unfold(Some((input, ticks, Vec::new())), move |state| async move {
+        let (mut input, mut ticks, mut buffer) = state?;
+        loop {
+            let ended = match future::select(input.next(), ticks.next()).await {
+                Either::Left((Some(Ok(value)), _)) => {
+                    buffer.push(value.to_js_value());
+                    continue;
+                }
+                Either::Left((Some(Err(e)), _)) => return Some((Err(e), Some((input, ticks, buffer)))),
+                Either::Left((None, _)) => true,
+                Either::Right(_) => false,
+            };
+            if buffer.is_empty() && !emit_empty {
+                if ended {
+                    return None;
+                }
+                continue;
+            }
+            let batch: Array = std::mem::take(&mut buffer).into_iter().collect();
+            let value = Ok(RiverValue::JsValue(batch.into()));
+            return Some((value, (!ended).then_some((input, ticks, buffer))));
+        }
+    })
+    .boxed_local()
+}
+
+// Holds each item (errors included) until `delay_ms` after it arrived while
+// still reading the input, so spacing and order are kept. Ends once the input
+// has ended and the last held item is out; dropping the stream discards the
+// held items along with their timer.
+fn delay(input: ValueStream, delay_ms: u32) -> ValueStream {
+    let state = (Some(input), VecDeque::<(f64, Result<RiverValue, JsValue>)>::new());
+    stream::unfold(state, move |(mut input, mut pending)| async move {
+        loop {
+            let due = pending.front().map(|(due, _)| {
+                let remaining = (*due - js_sys::Date::now()).max(0.0).ceil();
+                gloo_timers::future::TimeoutFuture::new(remaining as u32)
+            });
+            let arrived = match (input.as_mut(), due) {
+                (None, None) => return None,
+                (None, Some(due)) => {
+                    due.await;
+                    None
+                }
+                (Some(source), None) => Some(source.next().await),
+                (Some(source), Some(due)) => match future::select(source.next(), due).await {
+                    Either::Left((item, _)) => Some(item),
+                    Either::Right(_) => None,
+                },
+            };
+            match arrived {
+                Some(Some(item)) => pending.push_back((js_sys::Date::now() + f64::from(delay_ms), item)),
+                Some(None) => input = None,
+                None => {
+                    let (_, item) = pending.pop_front()?;
+                    return Some((item, (input, pending)));
+                }
+            }
+        }
+    })
+    .boxed_local()
+}
+
+// Feeds timestamped numbers through `resampler`, emitting the grid points
+// each one settles. Values without a timestamp or number become errors.
+fn resample(input: ValueStream, resampler: Resampler) -> ValueStream {
+    let state = (Some(input), resampler, VecDeque::new());
+    stream::unfold(state, |(mut input, mut resampler, mut pending)| async move {
+        loop {
+            if let Some(item) = pending.pop_front() {
+                return Some((item, (input, resampler, pending)));
+            }
+            let points = match input.as_mut()?.next().await {
+                Some(Ok(value)) => match value.as_timestamped_f64() {
+                    Some((t, value)) => resampler.push(t, value),
+                    None => {
+                        let error = JsValue::from_str("resample expects { t, value } with numeric fields");
+                        pending.push_back(Err(river_error(OPERATOR_THREW, error)));
+                        continue;
+                    }
+                },
+                Some(Err(e)) => {
+                    pending.push_back(Err(e));
+                    continue;
+                }
+                None => {
+                    input = None;
+                    resampler.finish().into_iter().collect()
+                }
+            };
+            pending.extend(points.into_iter().map(|(t, value)| {
+                Ok(RiverValue::Timestamped { t, value: Box::new(RiverValue::Number(value)) })
+            }));
+            if input.is_none() && pending.is_empty() {
+                return None;
+            }
+        }
+    })
+    .boxed_local()
+}
+
+// Calls `mapper` on each value and emits what its Promise (or plain return
+// value) resolves to. Throws and rejections go through `rescue`.
+fn map_async(input: ValueStream, mapper: Function, order: AsyncMapOrder, error_handler: Option<Function>) -> ValueStream {
+    let calls = input.map(move |item| {
+        let mapper = mapper.clone();
+        let error_handler = error_handler.clone();
+        async move {
+            let value = match item {
+                Ok(value) => value,
+                Err(e) => return Some(Err(e)),
+            };
+            let resolved = match mapper.call1(&JsValue::NULL, &value.to_js_value()) {
+                Ok(result) => JsFuture::from(Promise::resolve(&result)).await,
+                Err(e) => Err(e),
+            };
+            match resolved {
+                Ok(result) => Some(Ok(RiverValue::JsValue(result))),
+                Err(e) => rescue(&error_handler, river_error(OPERATOR_THREW, e)).transpose(),
+            }
+        }
+    });
+    let results = match order {
+        AsyncMapOrder::Concat => calls.buffered(1).boxed_local(),
+        AsyncMapOrder::Merge(limit) => calls.buffer_unordered(limit.max(1)).boxed_local(),
+    };
+    results.filter_map(future::ready).boxed_local()
+}
+
+// Reads `input` from a task of its own into a queue of up to `buffer_size`
+// items, so the next read is under way while the consumer handles the
+// current item. Dropping the stream stops the task and drops `input`.
+fn prefetch(input: ValueStream, buffer_size: usize) -> ValueStream {
+    let (mut queue, queued) = mpsc::channel(buffer_size.max(1) - 1);
+    let (cancel, mut cancelled) = futures::channel::oneshot::channel::<()>();
+    wasm_bindgen_futures::spawn_local(async move {
+        let mut input = input;
+        while let Either::Left((Some(item), _)) = future::select(input.next(), &mut cancelled).await {
+            if queue.send(item).await.is_err() {
+                break;
+            }
+        }
+    });
+    queued
+        .map(move |item| {
+            let _keep_reading = &cancel;
+            item
+        })
+        .boxed_local()
+}
+
+// Queue between the producer task of `pipe_through` and the stream reading
+// from it; `consumer` is woken when an item arrives or the input ends.
+struct PipeBuffer {
+    queue: VecDeque<Result<RiverValue, JsValue>>,
+    ended: bool,
+    consumer: Option<Waker>,
+}
+
+impl PipeBuffer {
+    fn push(&mut self, item: Option<Result<RiverValue, JsValue>>) {
+        match item {
+            Some(item) => self.queue.push_back(item),
+            None => self.ended = true,
+        }
+        if let Some(consumer) = self.consumer.take() {
+            consumer.wake();
+        }
+    }
+}
+
+// Reads `input` from a task of its own into a queue holding at most
+// `buffer_size` items, one credit each: the reader hands a credit back per
+// item it takes, and `overflow` decides what happens when none are left.
+// Dropping the stream stops the task and drops `input`.
+fn pipe_through(input: ValueStream, buffer_size: usize, overflow: OverflowPolicy) -> ValueStream {
+    let buffer = Rc::new(RefCell::new(PipeBuffer { queue: VecDeque::new(), ended: false, consumer: None }));
+    let mut credits = CreditManager::new(buffer_size.clamp(1, u32::MAX as usize) as u32);
+    let (cancel, mut cancelled) = futures::channel::oneshot::channel::<()>();
+    let producer = (buffer.clone(), credits.clone());
+    wasm_bindgen_futures::spawn_local(async move {
+        let (buffer, mut credits) = producer;
+        let mut input = input;
+        while let Either::Left((item, _)) = future::select(input.next(), &mut cancelled).await {
+            if item.is_some() && !credits.consume(1) {
+                match overflow {
+                    OverflowPolicy::DropNewest => continue,
+                    // The evicted item's credit passes to the new one.
+                    OverflowPolicy::DropOldest => {
+                        buffer.borrow_mut().queue.pop_front();
+                    }
+                    OverflowPolicy::Block => {
+                        let granted = Box::pin(credits.consume_async(1));
+                        if !matches!(future::select(granted, &mut cancelled).await, Either::Left((true, _))) {
+                            break;
+                        }
+                    }
+                }
+            }
+            let ended = item.is_none();
+            buffer.borrow_mut().push(item);
+            if ended {
+                break;
+            }
+        }
+    });
+    stream::poll_fn(move |cx| {
+        let _keep_reading = &cancel;
+        let mut buffer = buffer.borrow_mut();
+        match buffer.queue.pop_front() {
+            Some(item) => {
+                credits.add(1);
+                Poll::Ready(Some(item))
+            }
+            None if buffer.ended => Poll::Ready(None),
+            None => {
+                buffer.consumer = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    })
+    .boxed_local()
+}
+
+// Lets values through one credit at a time; errors pass freely. Without a
+// credit, `Drop` discards the value and `Buffer` holds it (and stops reading
+// `input`) until one is granted.
+fn gate(input: ValueStream, mut credits: CreditManager, policy: GatePolicy) -> ValueStream {
+    match policy {
+        GatePolicy::Drop => input.filter(move |item| future::ready(item.is_err() || credits.consume(1))).boxed_local(),
+        GatePolicy::Buffer => input
+            .then(move |item| {
+                let credit = item.is_ok().then(|| credits.consume_async(1));
+                async move {
+                    let granted = match credit {
+                        Some(credit) => credit.await,
+                        None => true,
+                    };
+                    if !granted {
+                        let error = JsValue::from_str("credit manager dropped while waiting for credit");
+                        return Err(river_error(CREDIT_EXHAUSTED, error));
+                    }
+                    item
+                }
+            })
+            .boxed_local(),
+    }
+}
+
+fn pair_value(a: &RiverValue, b: &RiverValue) -> RiverValue {
+    RiverValue::JsValue(Array::of2(&a.to_js_value(), &b.to_js_value()).into())
 }
 
+#[derive(Clone)]
 pub enum Operator {
     Map(Function),
     Filter(Function),
-    MapWasm(String, JsValue),
-    WindowedAggregate(usize, String),
+    // (mapper, index of the next value in this subscription)
+    MapIndexed(Function, u64),
+    // (kernel name, kernel, validated params)
+    MapWasm(String, Kernel, JsValue),
+    WindowedAggregate(WindowedOperator<f64>),
     Rescue(Function),
     Fork(Function),
+    Ema(EmaOperator),
+    // (n, values seen so far in this subscription)
+    SampleEvery(usize, usize),
+    DecimateMean(WindowedOperator<f64>),
+    // (histogram, emit every n values or 0 for completion only, values since last emission)
+    Histogram(Histogram, usize, usize),
+    // (samples of the current window, window size)
+    #[cfg(feature = "fft")]
+    Fft(Vec<f32>, usize),
+    // (period in ms, emit empty buffers)
+    BufferTime(u32, bool),
+    // delay in ms
+    Delay(u32),
+    // (keys of the most recent emissions, oldest first; window size; key selector)
+    DedupeWindow(VecDeque<RiverValue>, usize, Option<Function>),
+    Distinct(SeenKeys, Option<Function>),
+    // (last `size` values, step, values still to come before the next window)
+    SlidingWindow(WindowedOperator<JsValue>, usize, usize),
+    // (Sum, Mean, Min or Max; running sum or extreme; values seen)
+    Running(Operation, f64, u64),
+    AsyncMap(Function, AsyncMapOrder),
+    // read-ahead buffer size
+    Prefetch(usize),
+    TapError(Function),
+    Resample(Resampler),
+    // (buffer size, what to do when it is full)
+    PipeThrough(usize, OverflowPolicy),
+    Gate(CreditManager, GatePolicy),
+    // (operator, key of its timing in the profile, profile)
+    Profiled(Box<Operator>, String, Profile),
+}
+
+/// How `map_async` emits results whose Promises settle out of order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsyncMapOrder {
+    /// One mapper call at a time; results keep the input order (concatMap).
+    Concat,
+    /// Up to this many mapper calls in flight (0 is treated as 1); results
+    /// are emitted as they resolve (mergeMap). Further values wait for a
+    /// call to settle.
+    Merge(usize),
+}
+
+/// In-flight limit of `"merge"` when none is given.
+pub const DEFAULT_MERGE_CONCURRENCY: usize = 16;
+
+impl std::str::FromStr for AsyncMapOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "concat" => Ok(AsyncMapOrder::Concat),
+            "merge" => Ok(AsyncMapOrder::Merge(DEFAULT_MERGE_CONCURRENCY)),
+            _ => Err(format!("Unsupported map_async concurrency: {}", s)),
+        }
+    }
+}
+
+/// What `pipe_through` does with a value arriving while its buffer is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Evict the oldest buffered item to make room.
+    DropOldest,
+    /// Discard the arriving item.
+    DropNewest,
+    /// Stop reading upstream until the consumer frees a slot.
+    Block,
+}
+
+impl std::str::FromStr for OverflowPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "drop_oldest" => Ok(OverflowPolicy::DropOldest),
+            "drop_newest" => Ok(OverflowPolicy::DropNewest),
+            "block" => Ok(OverflowPolicy::Block),
+            _ => Err(format!("Unsupported overflow policy: {}", s)),
+        }
+    }
+}
+
+/// What `gate` does with a value that arrives while its `CreditManager` has
+/// no credit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GatePolicy {
+    Drop,
+    /// Hold it until a credit is added, reading nothing more meanwhile.
+    Buffer,
+}
+
+impl std::str::FromStr for GatePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "drop" => Ok(GatePolicy::Drop),
+            "buffer" => Ok(GatePolicy::Buffer),
+            _ => Err(format!("Unsupported gate policy: {}", s)),
+        }
+    }
+}
+
+impl Operator {
+    fn flush(&mut self) -> Option<RiverValue> {
+        match self {
+            Operator::Histogram(histogram, _, pending) if *pending > 0 => {
+                *pending = 0;
+                Some(histogram_value(histogram))
+            }
+            Operator::Profiled(op, _, _) => op.flush(),
+            _ => None,
+        }
+    }
+
+    // The builder method that adds the operator, for profiles.
+    fn name(&self) -> &'static str {
+        match self {
+            Operator::Map(_) => "map",
+            Operator::Filter(_) => "filter",
+            Operator::MapIndexed(..) => "map_indexed",
+            Operator::MapWasm(..) => "map_wasm",
+            Operator::WindowedAggregate(..) => "windowed_aggregate",
+            Operator::Rescue(_) => "rescue",
+            Operator::Fork(_) => "fork",
+            Operator::Ema(_) => "ema",
+            Operator::SampleEvery(..) => "sample_every",
+            Operator::DecimateMean(_) => "decimate_mean",
+            Operator::Histogram(..) => "histogram",
+            #[cfg(feature = "fft")]
+            Operator::Fft(..) => "fft",
+            Operator::BufferTime(..) => "buffer_time",
+            Operator::Delay(_) => "delay",
+            Operator::DedupeWindow(..) => "dedupe_window",
+            Operator::Distinct(..) => "distinct",
+            Operator::SlidingWindow(..) => "sliding_window",
+            Operator::Running(Operation::Sum, ..) => "running_sum",
+            Operator::Running(Operation::Mean, ..) => "running_mean",
+            Operator::Running(Operation::Min, ..) => "running_min",
+            Operator::Running(..) => "running_max",
+            Operator::AsyncMap(..) => "map_async",
+            Operator::Prefetch(_) => "prefetch",
+            Operator::TapError(_) => "tap_error",
+            Operator::Resample(_) => "resample",
+            Operator::PipeThrough(..) => "pipe_through",
+            Operator::Gate(..) => "gate",
+            Operator::Profiled(op, _, _) => op.name(),
+        }
+    }
+}
+
+// A hashable stand-in for a `distinct` key: primitives by value (NaN equal to
+// itself and -0 to 0, as in a JS `Set`), objects by identity.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum DistinctKey {
+    Number(u64),
+    String(String),
+    Bool(bool),
+    Null,
+    Undefined,
+    BigInt(String),
+    Object(u64),
+    Bytes(Vec<u8>),
+    Float32Array(Vec<u32>),
+    Timestamped(u64, Box<DistinctKey>),
+}
+
+thread_local! {
+    // Identities of objects used as `distinct` keys. Weak, so remembering a
+    // key never keeps its object alive.
+    static OBJECT_IDS: (js_sys::WeakMap, Cell<u64>) = (js_sys::WeakMap::new(), Cell::new(0));
+}
+
+impl DistinctKey {
+    fn number(x: f64) -> Self {
+        let x = if x.is_nan() { f64::NAN } else { x + 0.0 };
+        DistinctKey::Number(x.to_bits())
+    }
+
+    fn of(value: &RiverValue) -> Option<Self> {
+        Some(match value {
+            RiverValue::Number(x) => Self::number(*x),
+            RiverValue::Bytes(bytes) => DistinctKey::Bytes(bytes.clone()),
+            RiverValue::Float32Array(data) => DistinctKey::Float32Array(data.iter().map(|x| x.to_bits()).collect()),
+            RiverValue::JsValue(value) => return Self::of_js(value),
+            RiverValue::Timestamped { t, value } => DistinctKey::Timestamped(*t, Box::new(Self::of(value)?)),
+        })
+    }
+
+    // `None` for symbols, which have neither a value nor a usable identity.
+    fn of_js(value: &JsValue) -> Option<Self> {
+        if let Some(x) = value.as_f64() {
+            return Some(Self::number(x));
+        }
+        if let Some(s) = value.as_string() {
+            return Some(DistinctKey::String(s));
+        }
+        if let Some(b) = value.as_bool() {
+            return Some(DistinctKey::Bool(b));
+        }
+        if value.is_null() {
+            return Some(DistinctKey::Null);
+        }
+        if value.is_undefined() {
+            return Some(DistinctKey::Undefined);
+        }
+        if value.is_bigint() {
+            return Some(DistinctKey::BigInt(value.unchecked_ref::<js_sys::BigInt>().to_string(10).ok()?.into()));
+        }
+        if !value.is_object() && !value.is_function() {
+            return None;
+        }
+        let object: &Object = value.unchecked_ref();
+        OBJECT_IDS.with(|(ids, next)| {
+            let id = match ids.get(object).as_f64() {
+                Some(id) => id as u64,
+                None => {
+                    let id = next.get();
+                    next.set(id + 1);
+                    ids.set(object, &JsValue::from_f64(id as f64));
+                    id
+                }
+            };
+            Some(DistinctKey::Object(id))
+        })
+    }
+}
+
+/// Keys `distinct` has let through, forgetting the least recently seen one
+/// once more than `max_keys` are held.
+#[derive(Clone)]
+pub struct SeenKeys {
+    max_keys: usize,
+    // key -> tick it was last seen at, and the reverse for eviction
+    last_seen: HashMap<DistinctKey, u64>,
+    by_age: BTreeMap<u64, DistinctKey>,
+    clock: u64,
+}
+
+impl SeenKeys {
+    fn new(max_keys: usize) -> Self {
+        Self { max_keys, last_seen: HashMap::new(), by_age: BTreeMap::new(), clock: 0 }
+    }
+
+    // Marks `key` as just seen; `true` if it was not already held.
+    fn insert(&mut self, key: DistinctKey) -> bool {
+        self.clock += 1;
+        if let Some(seen) = self.last_seen.get_mut(&key) {
+            let previous = std::mem::replace(seen, self.clock);
+            self.by_age.remove(&previous);
+            self.by_age.insert(self.clock, key);
+            return false;
+        }
+        if self.last_seen.len() == self.max_keys {
+            if let Some((_, oldest)) = self.by_age.pop_first() {
+                self.last_seen.remove(&oldest);
+            }
+        }
+        self.last_seen.insert(key.clone(), self.clock);
+        self.by_age.insert(self.clock, key);
+        true
+    }
+}
+
+// The counts as a JS array carrying `underflow` and `overflow` properties.
+fn histogram_value(histogram: &Histogram) -> RiverValue {
+    let counts: Array = histogram.counts().iter().map(|&c| JsValue::from_f64(c as f64)).collect();
+    let _ = js_sys::Reflect::set(&counts, &"underflow".into(), &JsValue::from_f64(histogram.underflow() as f64));
+    let _ = js_sys::Reflect::set(&counts, &"overflow".into(), &JsValue::from_f64(histogram.overflow() as f64));
+    RiverValue::JsValue(counts.into())
 }
 
 impl RiverCore {
@@ -44,8 +1064,9 @@ impl RiverCore {
         Self {
             source: RiverSource::Empty,
             operators: Vec::new(),
-            error_handler: None,
-            terminate_on_error: false,
+            error_mode: ErrorMode::Report,
+            zero_copy_output: false,
+            profile: None,
         }
     }
 
@@ -53,8 +1074,9 @@ impl RiverCore {
         Self {
             source: RiverSource::ReadableStream(stream),
             operators: Vec::new(),
-            error_handler: None,
-            terminate_on_error: false,
+            error_mode: ErrorMode::Report,
+            zero_copy_output: false,
+            profile: None,
         }
     }
 
@@ -66,8 +1088,9 @@ impl RiverCore {
         Self {
             source: RiverSource::Array(values),
             operators: Vec::new(),
-            error_handler: None,
-            terminate_on_error: false,
+            error_mode: ErrorMode::Report,
+            zero_copy_output: false,
+            profile: None,
         }
     }
 
@@ -76,8 +1099,101 @@ impl RiverCore {
         Self {
             source: RiverSource::Array(vec![RiverValue::Bytes(bytes)]),
             operators: Vec::new(),
-            error_handler: None,
-            terminate_on_error: false,
+            error_mode: ErrorMode::Report,
+            zero_copy_output: false,
+            profile: None,
+        }
+    }
+
+    /// Emits the array's data as native `Float32Array` values, which
+    /// `map_wasm` kernels take without converting from JS. `chunk_size`
+    /// splits it into arrays of that many elements (the last may be
+    /// shorter, and an empty array emits none); `None` or 0 emits it whole.
+    pub fn from_float32_array(array: &Float32Array, chunk_size: Option<usize>) -> Self {
+        let data = array.to_vec();
+        let values = match chunk_size {
+            Some(size) if size > 0 => data.chunks(size).map(|chunk| RiverValue::Float32Array(chunk.to_vec())).collect(),
+            _ => vec![RiverValue::Float32Array(data)],
+        };
+        Self::from_source(RiverSource::Array(values))
+    }
+
+    /// A river fed by `receiver`, for Rust producers. All subscriptions
+    /// share the receiver, so each value goes to only one of them.
+    pub fn from_channel(receiver: mpsc::UnboundedReceiver<RiverValue>) -> Self {
+        Self::from_channel_queue(Rc::new(ChannelQueue::new(receiver.map(Ok).boxed_local())))
+    }
+
+    /// `from_channel` for a bounded channel, whose senders wait while it is
+    /// full. Errors sent on it are reported like read errors.
+    pub fn from_bounded_channel(receiver: mpsc::Receiver<Result<RiverValue, JsValue>>) -> Self {
+        Self::from_channel_queue(Rc::new(ChannelQueue::new(receiver.boxed_local())))
+    }
+
+    /// A river fed through the returned `ChannelSender`, which can report how
+    /// many pushed values no subscription has read yet.
+    pub fn channel() -> (Self, ChannelSender) {
+        let (sender, receiver) = mpsc::unbounded();
+        let queue = Rc::new(ChannelQueue::new(receiver.map(Ok).boxed_local()));
+        (Self::from_channel_queue(queue.clone()), ChannelSender { sender, queue })
+    }
+
+    fn from_channel_queue(queue: Rc<ChannelQueue>) -> Self {
+        Self::from_source(RiverSource::Channel(queue))
+    }
+
+    /// Emits each value of a JS async iterable (anything usable with
+    /// `for await`). Iteration stops on unsubscribe or abort, calling the
+    /// iterator's `return()`; a rejected `next()` is reported as an error and
+    /// ends the river. An async generator object can only be iterated once,
+    /// so later subscriptions to it see nothing.
+    pub fn from_async_iterator(iterable: JsValue) -> Self {
+        Self::from_source(RiverSource::AsyncIterator(iterable))
+    }
+
+    /// Emits 0, 1, 2, ... every `period_ms` until unsubscribed or aborted.
+    /// Each subscription runs its own timer, starting from 0.
+    pub fn interval(period_ms: u32) -> Self {
+        Self::from_source(RiverSource::Interval(period_ms))
+    }
+
+    /// The finite sequence `start, start + step, ...` up to but excluding
+    /// `end`, produced as it is read. A zero or non-finite step is an error,
+    /// as is one pointing away from `end` or too small to ever reach it.
+    pub fn range(start: f64, end: f64, step: f64) -> Result<Self, JsValue> {
+        if step == 0.0 || !step.is_finite() {
+            return Err(JsValue::from_str("range step must be finite and non-zero"));
+        }
+        let steps = ((end - start) / step).ceil();
+        if !steps.is_finite() || steps <= 0.0 {
+            return Err(JsValue::from_str(&format!(
+                "range from {} to {} by {} has no finite, positive number of steps",
+                start, end, step
+            )));
+        }
+        Ok(Self::from_source(RiverSource::Range { start, step, count: steps as u64 }))
+    }
+
+    /// An `AbortSignal` that aborts after `ms`, with a `TimeoutError` as its
+    /// reason, for `observe` to stop a subscription that runs too long.
+    /// Driven by a timer of its own, so it works where `AbortSignal.timeout`
+    /// is missing.
+    pub fn timeout_signal(ms: u32) -> Result<AbortSignal, JsValue> {
+        let controller = AbortController::new()?;
+        let signal = controller.signal();
+        gloo_timers::callback::Timeout::new(ms, move || {
+            let reason = js_sys::Error::new(&format!("timed out after {} ms", ms));
+            reason.set_name("TimeoutError");
+            controller.abort_with_reason(&reason);
+        })
+        .forget();
+        Ok(signal)
+    }
+
+    fn from_source(source: RiverSource) -> Self {
+        Self {
+            source,
+            ..Self::new()
         }
     }
 
@@ -87,199 +1203,851 @@ impl RiverCore {
         error: Option<Function>,
         complete: Option<Function>,
         signal: Option<AbortSignal>,
+    ) -> SubscriptionHandle {
+        self.observe_inner(next, error, complete, signal, None)
+    }
+
+    /// Like `observe`, but each value costs one credit from `credits` before
+    /// it is handed to `next`. When credits run out the subscription stops
+    /// reading the source (at most one value waits in between) until the
+    /// consumer signals demand by adding credits, e.g. `addCredits(n)` on the
+    /// JS `CreditController` once it has processed `n` values. Nothing is
+    /// dropped; errors and completion are not charged.
+    pub fn observe_with_credits(
+        &self,
+        credits: &CreditManager,
+        next: Function,
+        error: Option<Function>,
+        complete: Option<Function>,
+        signal: Option<AbortSignal>,
+    ) -> SubscriptionHandle {
+        self.observe_inner(next, error, complete, signal, Some(credits.clone()))
+    }
+
+    fn observe_inner(
+        &self,
+        next: Function,
+        error: Option<Function>,
+        complete: Option<Function>,
+        signal: Option<AbortSignal>,
+        credits: Option<CreditManager>,
     ) -> SubscriptionHandle {
         let sub_id = uuid::Uuid::new_v4().to_string();
         let active = Arc::new(Mutex::new(true));
+        // Wakes the subscription on unsubscribe/abort so a source that is
+        // waiting (a timer, an idle stream) is dropped immediately.
+        let (abort, registration) = AbortHandle::new_pair();
         
         let active_clone = active.clone();
+        // Operators carry their own state, so each subscription works on a
+        // fresh copy of the chain and never mutates `self`.
+        let mut core = self.clone_base();
+        if core.profile.is_some() {
+            core.profile = Some(Profile::default());
+        }
+        let profile = core.profile.clone();
+        let zero_copy = core.zero_copy_output;
+        let terminate_on_error = matches!(core.error_mode, ErrorMode::Terminate);
+        let abort_on_signal = abort.clone();
         wasm_bindgen_futures::spawn_local(async move {
-            match &self.source {
-                RiverSource::ReadableStream(stream) => {
-                    let reader = stream.get_reader();
-                    loop {
-                        if !*active_clone.lock().unwrap() {
-                            break;
-                        }
-                        
-                        if let Some(sig) = &signal {
-                            if sig.aborted() {
-                                break;
-                            }
-                        }
-                        
-                        let result = reader.read().await;
-                        match result {
-                            Ok(chunk) => {
-                                if let Ok(done) = js_sys::Reflect::get(&chunk, &"done".into()) {
-                                    if done.as_bool().unwrap_or(false) {
-                                        if let Some(complete_fn) = &complete {
-                                            let _ = complete_fn.call0(&JsValue::NULL);
-                                        }
-                                        break;
-                                    }
-                                }
-                                
-                                if let Ok(value) = js_sys::Reflect::get(&chunk, &"value".into()) {
-                                    let processed = self.apply_operators(RiverValue::JsValue(value));
-                                    if let Some(val) = processed {
-                                        let _ = next.call1(&JsValue::NULL, &val.to_js_value());
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                if let Some(error_fn) = &error {
-                                    let _ = error_fn.call1(&JsValue::NULL, &e);
-                                }
-                                if self.terminate_on_error {
-                                    break;
-                                }
-                            }
-                        }
-                    }
+            // Checked before the output is built: some sources and operators
+            // start reading as soon as they are set up.
+            if signal.as_ref().is_some_and(|sig| sig.aborted()) {
+                return;
+            }
+            let on_abort = signal.as_ref().map(|sig| {
+                let callback = Closure::<dyn FnMut()>::new(move || abort_on_signal.abort());
+                let _ = sig.add_event_listener_with_callback("abort", callback.as_ref().unchecked_ref());
+                callback
+            });
+            let mut retained = None;
+            let output = match credits {
+                // Waiting for a credit inside the stream keeps the wait
+                // abortable, and dropping it gives up the place in line.
+                Some(credits) => gate(core.into_output(), credits, GatePolicy::Buffer),
+                None => core.into_output(),
+            };
+            let mut values = Abortable::new(output, registration);
+            let completed = loop {
+                if !*active_clone.lock().unwrap() || signal.as_ref().is_some_and(|sig| sig.aborted()) {
+                    break false;
                 }
-                RiverSource::Array(values) => {
-                    for value in values {
-                        if !*active_clone.lock().unwrap() {
-                            break;
-                        }
-                        
-                        if let Some(sig) = &signal {
-                            if sig.aborted() {
-                                break;
-                            }
+
+                match values.next().await {
+                    Some(Ok(val)) => emit(&next, val, zero_copy, &mut retained),
+                    Some(Err(e)) => {
+                        if let Some(error_fn) = &error {
+                            let _ = error_fn.call1(&JsValue::NULL, &e);
                         }
-                        
-                        let processed = self.apply_operators(value.clone());
-                        if let Some(val) = processed {
-                            let _ = next.call1(&JsValue::NULL, &val.to_js_value());
+                        if terminate_on_error {
+                            break false;
                         }
                     }
-                    
-                    if let Some(complete_fn) = &complete {
-                        let _ = complete_fn.call0(&JsValue::NULL);
-                    }
+                    None => break !values.is_aborted(),
+                }
+            };
+
+            if let (Some(sig), Some(callback)) = (&signal, &on_abort) {
+                let _ = sig.remove_event_listener_with_callback("abort", callback.as_ref().unchecked_ref());
+            }
+            if completed {
+                if let Some(complete_fn) = &complete {
+                    let _ = complete_fn.call0(&JsValue::NULL);
                 }
-                _ => {}
             }
         });
         
         SubscriptionHandle {
             id: sub_id,
             active,
+            abort,
+            profile,
         }
     }
 
-    fn apply_operators(&self, value: RiverValue) -> Option<RiverValue> {
-        let mut current = Some(value);
-        
-        for op in &self.operators {
-            current = match op {
-                Operator::Map(fn_) => {
-                    if let Some(val) = current {
-                        let js_val = val.to_js_value();
-                        match fn_.call1(&JsValue::NULL, &js_val) {
-                            Ok(result) => Some(RiverValue::JsValue(result)),
-                            Err(_) => None,
-                        }
-                    } else {
-                        None
-                    }
+    /// Resolves with the number of values emitted before completion (0 for an
+    /// empty river). Rejects with the first error.
+    pub fn count(&self) -> Promise {
+        let mut values = self.clone_base().into_output();
+        wasm_bindgen_futures::future_to_promise(async move {
+            let mut count = 0u32;
+            while let Some(value) = values.next().await {
+                value?;
+                count += 1;
+            }
+            Ok(JsValue::from(count))
+        })
+    }
+
+    /// Resolves once the river completes with an array of `[index, value]`
+    /// pairs, in emission order with indices counting from 0 (an empty array
+    /// for an empty river). Rejects with the first error.
+    pub fn collect_entries(&self) -> Promise {
+        let mut values = self.clone_base().into_output();
+        wasm_bindgen_futures::future_to_promise(async move {
+            let entries = Array::new();
+            while let Some(value) = values.next().await {
+                let index = JsValue::from(entries.length());
+                entries.push(&Array::of2(&index, &value?.to_js_value()));
+            }
+            Ok(entries.into())
+        })
+    }
+
+    /// Resolves with the first value matching `predicate` (any value when
+    /// `None`) and stops reading the source right away. Rejects if the river
+    /// completes without a match, on the first error, or if `predicate` throws.
+    pub fn first(&self, predicate: Option<Function>) -> Promise {
+        let mut values = self.clone_base().into_output();
+        wasm_bindgen_futures::future_to_promise(async move {
+            while let Some(value) = values.next().await {
+                let value = value?.to_js_value();
+                if matches(&predicate, &value)? {
+                    return Ok(value);
                 }
-                Operator::Filter(pred) => {
-                    if let Some(val) = current {
-                        let js_val = val.to_js_value();
-                        match pred.call1(&JsValue::NULL, &js_val) {
-                            Ok(result) if result.as_bool().unwrap_or(false) => Some(val),
-                            _ => None,
-                        }
-                    } else {
-                        None
+            }
+            Err(JsValue::from_str("first: no matching value"))
+        })
+    }
+
+    /// Resolves with the last value matching `predicate` (any value when
+    /// `None`) once the river completes. Rejects like `first`.
+    pub fn last(&self, predicate: Option<Function>) -> Promise {
+        let mut values = self.clone_base().into_output();
+        wasm_bindgen_futures::future_to_promise(async move {
+            let mut last = None;
+            while let Some(value) = values.next().await {
+                let value = value?.to_js_value();
+                if matches(&predicate, &value)? {
+                    last = Some(value);
+                }
+            }
+            last.ok_or_else(|| JsValue::from_str("last: no matching value"))
+        })
+    }
+
+    // Source values run through this river's operators. Consecutive
+    // per-value operators form one stage, followed by whatever they flush
+    // when their input ends; time-based operators wrap the stream between
+    // stages. Skipped errors are filtered out last, wherever they arose.
+    fn into_output(self) -> ValueStream {
+        let error_handler = self.error_mode.rescue_handler();
+        let source = match (self.source, &self.error_mode) {
+            // Skipping has to happen inside the parts, or their first error
+            // would end the concatenation before it could be skipped.
+            (RiverSource::Concat(parts), ErrorMode::Skip(on_error)) => RiverSource::Concat(
+                parts
+                    .into_iter()
+                    .map(|part| match part.error_mode {
+                        ErrorMode::Report | ErrorMode::Terminate => part.skip_errors(on_error.clone()),
+                        _ => part,
+                    })
+                    .collect(),
+            ),
+            (source, _) => source,
+        };
+        let mut output = source.values().map(|item| item.map_err(|e| river_error(READ_ERROR, e))).boxed_local();
+        let mut stage = Vec::new();
+        for (position, op) in self.operators.into_iter().enumerate() {
+            match op {
+                Operator::BufferTime(period_ms, emit_empty) => {
+                    output = Self::run_stage(output, std::mem::take(&mut stage), &error_handler);
+                    output = buffer_time(output, period_ms, emit_empty);
+                }
+                Operator::Delay(delay_ms) => {
+                    output = Self::run_stage(output, std::mem::take(&mut stage), &error_handler);
+                    output = delay(output, delay_ms);
+                }
+                Operator::Prefetch(buffer_size) => {
+                    output = Self::run_stage(output, std::mem::take(&mut stage), &error_handler);
+                    output = prefetch(output, buffer_size);
+                }
+                Operator::TapError(callback) => {
+                    output = Self::run_stage(output, std::mem::take(&mut stage), &error_handler);
+                    output = output
+                        .inspect(move |item| {
+                            if let Err(e) = item {
+                                let _ = callback.call1(&JsValue::NULL, e);
+                            }
+                        })
+                        .boxed_local();
+                }
+                Operator::AsyncMap(mapper, order) => {
+                    output = Self::run_stage(output, std::mem::take(&mut stage), &error_handler);
+                    output = map_async(output, mapper, order, error_handler.clone());
+                }
+                Operator::Gate(credits, policy) => {
+                    output = Self::run_stage(output, std::mem::take(&mut stage), &error_handler);
+                    output = gate(output, credits, policy);
+                }
+                Operator::PipeThrough(buffer_size, overflow) => {
+                    output = Self::run_stage(output, std::mem::take(&mut stage), &error_handler);
+                    output = pipe_through(output, buffer_size, overflow);
+                }
+                Operator::Resample(resampler) => {
+                    output = Self::run_stage(output, std::mem::take(&mut stage), &error_handler);
+                    output = resample(output, resampler);
+                }
+                op => stage.push(match &self.profile {
+                    Some(profile) => {
+                        let key = format!("{}#{}", op.name(), position);
+                        Operator::Profiled(Box::new(op), key, profile.clone())
+                    }
+                    None => op,
+                }),
+            }
+        }
+        let output = Self::run_stage(output, stage, &error_handler);
+        match self.error_mode {
+            ErrorMode::Skip(on_error) => output
+                .filter_map(move |item| {
+                    if let (Err(e), Some(on_error)) = (&item, &on_error) {
+                        let _ = on_error.call1(&JsValue::NULL, e);
                     }
+                    future::ready(item.ok().map(Ok))
+                })
+                .boxed_local(),
+            _ => output,
+        }
+    }
+
+    fn run_stage(input: ValueStream, operators: Vec<Operator>, error_handler: &Option<Function>) -> ValueStream {
+        if operators.is_empty() {
+            return input;
+        }
+        let state = (operators, error_handler.clone(), input, None::<std::vec::IntoIter<Result<RiverValue, JsValue>>>);
+        stream::unfold(state, |(mut operators, error_handler, mut input, mut flushed)| async move {
+            loop {
+                if let Some(rest) = flushed.as_mut() {
+                    let value = rest.next();
+                    return value.map(|v| (v, (operators, error_handler, input, flushed)));
                 }
-                Operator::MapWasm(kernel, params) => {
-                    if let Some(val) = current {
-                        self.apply_wasm_kernel(val, kernel, params)
-                    } else {
-                        None
+                match input.next().await {
+                    Some(Ok(value)) => {
+                        if let Some(result) = Self::run_operators(&mut operators, &error_handler, value).transpose() {
+                            return Some((result, (operators, error_handler, input, flushed)));
+                        }
                     }
+                    Some(Err(e)) => return Some((Err(e), (operators, error_handler, input, flushed))),
+                    None => flushed = Some(Self::flush_operators(&mut operators, &error_handler).into_iter()),
                 }
-                _ => current,
+            }
+        })
+        .boxed_local()
+    }
+
+    /// Like `observe`, but typed-array values (`Float32Array`, `Bytes`) are
+    /// handed to `next` as views into WASM linear memory instead of copies.
+    ///
+    /// A view stays valid only until the next emission, and any WASM memory
+    /// growth detaches it before then. The consumer must copy it (`slice()`)
+    /// or finish using it inside the `next` callback; a view cannot be
+    /// transferred to a worker, so copy first and transfer the copy.
+    pub fn observe_transferable(
+        &self,
+        next: Function,
+        error: Option<Function>,
+        complete: Option<Function>,
+        signal: Option<AbortSignal>,
+    ) -> SubscriptionHandle {
+        let mut core = self.clone();
+        core.zero_copy_output = true;
+        core.observe(next, error, complete, signal)
+    }
+
+    // An operator that throws (`map`/`filter` callbacks) is handed to the
+    // `rescue` handler when there is one; its result stands in for the value
+    // (`undefined` drops it). Otherwise the error is surfaced to the observer.
+    fn run_operators(
+        operators: &mut [Operator],
+        error_handler: &Option<Function>,
+        value: RiverValue,
+    ) -> Result<Option<RiverValue>, JsValue> {
+        let mut current = value;
+        for op in operators {
+            let result = match Self::apply_operator(op, current) {
+                Err(e) => rescue(error_handler, river_error(OPERATOR_THREW, e))?,
+                Ok(result) => result,
             };
+            match result {
+                Some(value) => current = value,
+                None => return Ok(None),
+            }
         }
-        
-        current
-    }
-
-    fn apply_wasm_kernel(&self, value: RiverValue, kernel: &str, params: &JsValue) -> Option<RiverValue> {
-        match kernel {
-            "f32x_map_mul_add" => {
-                if let RiverValue::Float32Array(data) = value {
-                    let a = js_sys::Reflect::get(params, &"a".into())
-                        .ok()?.as_f64()? as f32;
-                    let b = js_sys::Reflect::get(params, &"b".into())
-                        .ok()?.as_f64()? as f32;
-                    
-                    #[cfg(feature = "simd")]
-                    {
-                        let result = crate::simd_ops::f32x_map_mul_add(&data, a, b);
-                        Some(RiverValue::Float32Array(result))
-                    }
-                    
-                    #[cfg(not(feature = "simd"))]
-                    {
-                        let result: Vec<f32> = data.iter()
-                            .map(|x| x * a + b)
-                            .collect();
-                        Some(RiverValue::Float32Array(result))
+        Ok(Some(current))
+    }
+
+    // Values an operator still holds when the source completes (e.g. a
+    // histogram not yet emitted), each passed through the operators after it.
+    fn flush_operators(operators: &mut [Operator], error_handler: &Option<Function>) -> Vec<Result<RiverValue, JsValue>> {
+        let mut flushed: Vec<Result<RiverValue, JsValue>> = Vec::new();
+        for op in operators {
+            flushed = flushed
+                .into_iter()
+                .filter_map(|value| match value {
+                    Ok(value) => Self::run_operators(std::slice::from_mut(op), error_handler, value).transpose(),
+                    Err(e) => Some(Err(e)),
+                })
+                .collect();
+            flushed.extend(op.flush().into_iter().map(Ok));
+        }
+        flushed
+    }
+
+    fn apply_operator(op: &mut Operator, value: RiverValue) -> Result<Option<RiverValue>, JsValue> {
+        match op {
+            Operator::Map(fn_) => {
+                let result = fn_.call1(&JsValue::NULL, &value.to_js_value())?;
+                Ok(Some(RiverValue::JsValue(result)))
+            }
+            Operator::Profiled(op, key, profile) => {
+                let start = performance_now();
+                let result = Self::apply_operator(op, value);
+                let elapsed = performance_now() - start;
+                let mut profile = profile.borrow_mut();
+                let timing = profile.entry(key.clone()).or_default();
+                timing.total_ms += elapsed;
+                timing.calls += 1;
+                result
+            }
+            Operator::MapIndexed(fn_, index) => {
+                let position = *index;
+                *index += 1;
+                let result = fn_.call2(&JsValue::NULL, &value.to_js_value(), &JsValue::from_f64(position as f64))?;
+                Ok(Some(RiverValue::JsValue(result)))
+            }
+            Operator::Filter(pred) => {
+                let keep = pred.call1(&JsValue::NULL, &value.to_js_value())?;
+                Ok(keep.as_bool().unwrap_or(false).then_some(value))
+            }
+            Operator::Distinct(seen, key_selector) => {
+                let key = match key_selector {
+                    Some(selector) => DistinctKey::of_js(&selector.call1(&JsValue::NULL, &value.to_js_value())?),
+                    None => DistinctKey::of(&value),
+                };
+                let repeated = key.is_some_and(|key| !seen.insert(key));
+                Ok((!repeated).then_some(value))
+            }
+            Operator::DedupeWindow(_, 0, _) => Ok(Some(value)),
+            Operator::DedupeWindow(recent, window, key_selector) => {
+                let key = match key_selector {
+                    Some(selector) => RiverValue::JsValue(selector.call1(&JsValue::NULL, &value.to_js_value())?),
+                    None => value.clone(),
+                };
+                if recent.contains(&key) {
+                    return Ok(None);
+                }
+                if recent.len() == *window {
+                    recent.pop_front();
+                }
+                recent.push_back(key);
+                Ok(Some(value))
+            }
+            Operator::MapWasm(name, kernel, params) => Self::apply_wasm_kernel(value, name, *kernel, params),
+            _ => Ok(Self::apply_native_operator(op, value)),
+        }
+    }
+
+    fn apply_native_operator(op: &mut Operator, value: RiverValue) -> Option<RiverValue> {
+        match op {
+            Operator::Ema(ema) => {
+                value
+                    .as_f64()
+                    .map(|x| RiverValue::Number(ema.push(x as f32) as f64))
+            }
+            Operator::SampleEvery(n, seen) => {
+                let keep = *seen % *n == 0;
+                *seen += 1;
+                keep.then_some(value)
+            }
+            Operator::WindowedAggregate(window) => {
+                let x = value.as_f64()?;
+                window.push(x);
+                window.compute().map(RiverValue::Number)
+            }
+            Operator::DecimateMean(window) => {
+                value.as_f64().and_then(|x| {
+                    window.push(x);
+                    let mean = window.compute();
+                    if mean.is_some() {
+                        window.clear();
                     }
+                    mean.map(RiverValue::Number)
+                })
+            }
+            Operator::Running(aggregate, acc, seen) => {
+                let x = value.as_f64()?;
+                *acc = match aggregate {
+                    Operation::Min if *seen > 0 => acc.min(x),
+                    Operation::Max if *seen > 0 => acc.max(x),
+                    Operation::Min | Operation::Max => x,
+                    _ => *acc + x,
+                };
+                *seen += 1;
+                let current = match aggregate {
+                    Operation::Mean => *acc / *seen as f64,
+                    _ => *acc,
+                };
+                Some(RiverValue::Number(current))
+            }
+            Operator::SlidingWindow(window, step, countdown) => {
+                window.push(value.to_js_value());
+                *countdown = countdown.saturating_sub(1);
+                if !window.is_ready() || *countdown > 0 {
+                    return None;
+                }
+                *countdown = *step;
+                let values: Array = window.get_window().into_iter().collect();
+                Some(RiverValue::JsValue(values.into()))
+            }
+            Operator::Histogram(histogram, every, pending) => {
+                let x = value.as_f64()?;
+                histogram.push(x);
+                *pending += 1;
+                if *every > 0 && *pending >= *every {
+                    *pending = 0;
+                    Some(histogram_value(histogram))
                 } else {
                     None
                 }
             }
-            _ => None,
+            #[cfg(feature = "fft")]
+            Operator::Fft(samples, window_size) => {
+                let x = value.as_f64()?;
+                samples.push(x as f32);
+                if samples.len() < *window_size {
+                    return None;
+                }
+                let spectrum = crate::fft::magnitude_spectrum(samples).ok();
+                samples.clear();
+                spectrum.map(RiverValue::Float32Array)
+            }
+            _ => Some(value),
         }
     }
 
+    // Runs `kernel` on a Float32Array value, native or from JS; anything
+    // else is dropped. A kernel that fails on the value raises an error.
+    fn apply_wasm_kernel(value: RiverValue, name: &str, kernel: Kernel, params: &JsValue) -> Result<Option<RiverValue>, JsValue> {
+        let data = match value {
+            RiverValue::Float32Array(data) => data,
+            RiverValue::JsValue(val) => match val.dyn_into::<Float32Array>() {
+                Ok(array) => array.to_vec(),
+                Err(_) => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        let output = kernel(&data, params).map_err(|e| JsValue::from_str(&format!("{}: {}", name, e)))?;
+        Ok(Some(match output {
+            KernelOutput::Array(data) => RiverValue::Float32Array(data),
+            KernelOutput::Number(n) => RiverValue::Number(n),
+        }))
+    }
+
     pub fn map(&self, mapper: Function) -> RiverCore {
         let mut new_core = self.clone_base();
         new_core.operators.push(Operator::Map(mapper));
         new_core
     }
 
+    /// `map` with the value's position: `mapper(value, index)`, the index
+    /// counting from 0 in each subscription.
+    pub fn map_indexed(&self, mapper: Function) -> RiverCore {
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::MapIndexed(mapper, 0));
+        new_core
+    }
+
+    /// Records the time each subscription spends in each per-value operator
+    /// (`map`, `filter`, `ema`, ...) and how often it is called; read it
+    /// with `SubscriptionHandle::profile`. Time-based and async operators
+    /// (`delay`, `map_async`, ...) are not timed. Without this, operators
+    /// run unwrapped and nothing is measured.
+    pub fn enable_profiling(&self) -> RiverCore {
+        let mut new_core = self.clone_base();
+        new_core.profile = Some(Profile::default());
+        new_core
+    }
+
     pub fn filter(&self, predicate: Function) -> RiverCore {
         let mut new_core = self.clone_base();
         new_core.operators.push(Operator::Filter(predicate));
         new_core
     }
 
-    pub fn map_wasm(&self, kernel_name: &str, params: JsValue) -> RiverCore {
+    /// Runs the kernel registered as `kernel_name` (see `kernels`) on each
+    /// Float32Array value with `params`. An unknown kernel or parameters not
+    /// matching its schema are rejected here rather than per value.
+    pub fn map_wasm(&self, kernel_name: &str, params: JsValue) -> Result<RiverCore, JsValue> {
+        let kernel = validate_params(kernel_name, &params).map_err(|e| JsValue::from_str(&e))?;
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::MapWasm(kernel_name.to_string(), kernel, params));
+        Ok(new_core)
+    }
+
+    /// Exponential moving average over numeric values; non-numeric values are
+    /// dropped. Each subscription starts from an empty average.
+    pub fn ema(&self, alpha: f32) -> Result<RiverCore, JsValue> {
+        let ema = EmaOperator::new(alpha).map_err(|e| JsValue::from_str(&e))?;
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::Ema(ema));
+        Ok(new_core)
+    }
+
+    /// Passes the first of every `n` values and drops the rest (`n == 0` is
+    /// treated as 1). The count restarts with each subscription.
+    pub fn sample_every(&self, n: usize) -> RiverCore {
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::SampleEvery(n.max(1), 0));
+        new_core
+    }
+
+    /// Emits the mean of each consecutive group of `n` numeric values; a
+    /// trailing partial group is never emitted and non-numeric values are
+    /// dropped.
+    pub fn decimate_mean(&self, n: usize) -> RiverCore {
         let mut new_core = self.clone_base();
-        new_core.operators.push(Operator::MapWasm(kernel_name.to_string(), params));
+        new_core.operators.push(Operator::DecimateMean(WindowedOperator::new(n.max(1), Operation::Mean)));
         new_core
     }
 
-    pub fn windowed_aggregate(&self, window_size: usize, operation: &str) -> RiverCore {
+    /// Bins numeric values into a `Histogram` and emits its counts as a JS
+    /// array with `underflow`/`overflow` properties: after every
+    /// `emit_every` values, and once more on completion if anything arrived
+    /// since. With `emit_every == 0` it emits only on completion.
+    pub fn histogram(&self, min: f64, max: f64, bins: usize, emit_every: usize) -> Result<RiverCore, JsValue> {
+        let histogram = Histogram::new(min, max, bins).map_err(|e| JsValue::from_str(&e))?;
         let mut new_core = self.clone_base();
-        new_core.operators.push(Operator::WindowedAggregate(window_size, operation.to_string()));
+        new_core.operators.push(Operator::Histogram(histogram, emit_every, 0));
+        Ok(new_core)
+    }
+
+    /// Collects numeric values into consecutive windows of `window_size` (a
+    /// power of two) and emits each window's magnitude spectrum as a
+    /// `Float32Array` of `window_size / 2 + 1` bins. A trailing partial window
+    /// is dropped.
+    #[cfg(feature = "fft")]
+    pub fn fft(&self, window_size: usize) -> Result<RiverCore, JsValue> {
+        if !window_size.is_power_of_two() {
+            return Err(JsValue::from_str(&format!("FFT window must be a power of two, got {}", window_size)));
+        }
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::Fft(Vec::with_capacity(window_size), window_size));
+        Ok(new_core)
+    }
+
+    /// Drops any value whose key matches one of the last `window` emitted
+    /// keys; a key is free again once `window` newer values have gone out.
+    /// Keys are the values themselves, compared like `RiverValue`'s
+    /// `PartialEq`, or what `key_selector` returns for them (compared with
+    /// `===`, so return strings or numbers). Holds at most `window` keys per
+    /// subscription. A window of 0 drops nothing.
+    pub fn dedupe_window(&self, window: usize, key_selector: Option<Function>) -> RiverCore {
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::DedupeWindow(VecDeque::with_capacity(window), window, key_selector));
         new_core
     }
 
-    pub fn rescue(&self, handler: Function) -> RiverCore {
+    /// Emits a value only when `selector(value)` differs (`!==`) from the
+    /// key of the value before it, starting with the first value: a
+    /// `distinctUntilChanged` on a projection. The previous key is kept per
+    /// subscription.
+    pub fn changes(&self, selector: Function) -> RiverCore {
+        // The last emitted key is also the previous value's key, since a
+        // value is only dropped when its key equals it.
+        self.dedupe_window(1, Some(selector))
+    }
+
+    /// Drops every value whose key has been seen before in the subscription.
+    /// Keys are the values themselves, or what `key_selector` returns for
+    /// them; like a JS `Set`, primitives (and typed arrays) compare by value
+    /// and objects by identity. Symbol keys are never deduplicated.
+    ///
+    /// At most `max_keys` (at least 1) keys are held. Beyond that the least
+    /// recently seen key is evicted, so a value can pass again once
+    /// `max_keys` other keys have been seen since it last appeared; repeats
+    /// keep a key from being evicted.
+    pub fn distinct(&self, key_selector: Option<Function>, max_keys: usize) -> RiverCore {
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::Distinct(SeenKeys::new(max_keys.max(1)), key_selector));
+        new_core
+    }
+
+    /// Reads up to `buffer_size` values ahead of the observer, overlapping
+    /// source I/O (e.g. `ReadableStream` reads) with the work done on each
+    /// value after this point. Upstream stops reading while the buffer is
+    /// full, so a slow consumer, or one out of credits under
+    /// `observe_with_credits`, holds at most `buffer_size` values here.
+    pub fn prefetch(&self, buffer_size: usize) -> RiverCore {
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::Prefetch(buffer_size));
+        new_core
+    }
+
+    /// Forwards a value only when `credits` has a credit to spend on it, so
+    /// whoever holds (a clone of) the manager meters the flow mid-chain by
+    /// adding credits. `on_no_credit` is "drop" or "buffer"; see
+    /// `GatePolicy`. Errors are not metered.
+    pub fn gate(&self, credits: &CreditManager, on_no_credit: &str) -> Result<RiverCore, JsValue> {
+        let policy: GatePolicy = on_no_credit.parse().map_err(|e: String| JsValue::from_str(&e))?;
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::Gate(credits.clone(), policy));
+        Ok(new_core)
+    }
+
+    /// Decouples everything upstream from whatever consumes this river
+    /// through a buffer of `buffer_size` values (at least 1), read ahead from
+    /// a task of its own. `overflow` is "drop_oldest", "drop_newest" or
+    /// "block" and applies when a slow consumer lets the buffer fill up;
+    /// errors take a slot and can be dropped like values. Unlike `prefetch`,
+    /// the drop policies never hold upstream back.
+    pub fn pipe_through(&self, buffer_size: usize, overflow: &str) -> Result<RiverCore, JsValue> {
+        let overflow: OverflowPolicy = overflow.parse().map_err(|e: String| JsValue::from_str(&e))?;
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::PipeThrough(buffer_size, overflow));
+        Ok(new_core)
+    }
+
+    /// Emits the last `size` values as a JS array once the first `size` have
+    /// arrived and then every `step` values, so windows overlap when `step <
+    /// size`. Partial windows are never emitted.
+    pub fn sliding_window(&self, size: usize, step: usize) -> Result<RiverCore, JsValue> {
+        if size == 0 || step == 0 {
+            return Err(JsValue::from_str("sliding window size and step must be positive"));
+        }
+        // Only the window's buffer is used; the operation is never computed.
+        let window = WindowedOperator::new(size, Operation::Mean);
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::SlidingWindow(window, step, 0));
+        Ok(new_core)
+    }
+
+    /// Resamples timestamped numbers (`RiverValue::Timestamped`, or `{ t,
+    /// value }` objects from JS) onto a grid every `interval_ms`, emitting
+    /// `{ t, value }` per grid point. `method` is "nearest", "linear" or
+    /// "hold"; see `Resampler` for gap and ordering rules.
+    pub fn resample(&self, interval_ms: u32, method: &str) -> Result<RiverCore, JsValue> {
+        let method: ResampleMethod = method.parse().map_err(|e: String| JsValue::from_str(&e))?;
+        let resampler = Resampler::new(u64::from(interval_ms), method).map_err(|e| JsValue::from_str(&e))?;
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::Resample(resampler));
+        Ok(new_core)
+    }
+
+    /// Emits the sum of the numeric values so far with each one.
+    pub fn running_sum(&self) -> RiverCore {
+        self.running(Operation::Sum)
+    }
+
+    /// Emits the mean of the numeric values so far with each one.
+    pub fn running_mean(&self) -> RiverCore {
+        self.running(Operation::Mean)
+    }
+
+    /// Emits the smallest numeric value so far with each one.
+    pub fn running_min(&self) -> RiverCore {
+        self.running(Operation::Min)
+    }
+
+    /// Emits the largest numeric value so far with each one.
+    pub fn running_max(&self) -> RiverCore {
+        self.running(Operation::Max)
+    }
+
+    // Like `ema`, non-numeric values are dropped and each subscription
+    // starts from scratch.
+    fn running(&self, aggregate: Operation) -> RiverCore {
         let mut new_core = self.clone_base();
-        new_core.error_handler = Some(handler);
+        new_core.operators.push(Operator::Running(aggregate, 0.0, 0));
         new_core
     }
 
+    /// Emits `operation` ("mean", "max", "std", "p95", ...) over the last
+    /// `window_size` numeric values, once that many have arrived and then for
+    /// each value after. Non-numeric values are dropped. An unknown or
+    /// elementwise operation, or a zero window, is an error.
+    pub fn windowed_aggregate(&self, window_size: usize, operation: &str) -> Result<RiverCore, JsValue> {
+        let operation = operation.parse::<Operation>().map_err(|e| JsValue::from_str(&e))?;
+        if operation.is_elementwise() {
+            return Err(JsValue::from_str(&format!("{} is not an aggregate operation", operation)));
+        }
+        if window_size == 0 {
+            return Err(JsValue::from_str("windowed aggregate window size must be positive"));
+        }
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::WindowedAggregate(WindowedOperator::new(window_size, operation)));
+        Ok(new_core)
+    }
+
+    /// Like `map` for a mapper returning a Promise: the resolved value is
+    /// emitted instead of the Promise. `order` decides whether values are
+    /// mapped one at a time in order or up to a limit concurrently, emitted
+    /// as they settle.
+    /// A rejection is treated like a throwing `map`.
+    pub fn map_async(&self, mapper: Function, order: AsyncMapOrder) -> RiverCore {
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::AsyncMap(mapper, order));
+        new_core
+    }
+
+    /// Batches values into arrays, one every `period_ms` with whatever arrived
+    /// since the last, plus a final batch when the river completes. Periods
+    /// with nothing in them are skipped unless `emit_empty` is set.
+    pub fn buffer_time(&self, period_ms: u32, emit_empty: bool) -> RiverCore {
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::BufferTime(period_ms, emit_empty));
+        new_core
+    }
+
+    /// Emits every value `delay_ms` later than it arrived, in order.
+    /// Completion waits for the last delayed value; unsubscribing drops the
+    /// values still waiting.
+    pub fn delay(&self, delay_ms: u32) -> RiverCore {
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::Delay(delay_ms));
+        new_core
+    }
+
+    /// Calls `callback` with each error raised up to this point in the chain
+    /// (read errors and throwing operators alike), then lets the error carry
+    /// on to the river's error mode unchanged.
+    pub fn tap_error(&self, callback: Function) -> RiverCore {
+        let mut new_core = self.clone_base();
+        new_core.operators.push(Operator::TapError(callback));
+        new_core
+    }
+
+    /// See `ErrorMode::Rescue`.
+    pub fn rescue(&self, handler: Function) -> RiverCore {
+        self.with_error_mode(ErrorMode::Rescue(handler))
+    }
+
+    /// See `ErrorMode::Terminate`.
     pub fn terminate_on_error(&self) -> RiverCore {
+        self.with_error_mode(ErrorMode::Terminate)
+    }
+
+    /// See `ErrorMode::Skip`.
+    pub fn skip_errors(&self, on_error: Option<Function>) -> RiverCore {
+        self.with_error_mode(ErrorMode::Skip(on_error))
+    }
+
+    fn with_error_mode(&self, error_mode: ErrorMode) -> RiverCore {
         let mut new_core = self.clone_base();
-        new_core.terminate_on_error = true;
+        new_core.error_mode = error_mode;
         new_core
     }
 
+    /// A hot version of this river. By default every `observe` runs the
+    /// source and operators from scratch (cold), which for a one-shot
+    /// `ReadableStream` means only the first observer gets data. A shared
+    /// river runs them once: the first observer starts the upstream, later
+    /// observers join it and see only values emitted after they attach, and
+    /// the upstream is stopped when the last observer unsubscribes. Once it
+    /// completes or is stopped, the next observer starts it again.
+    /// Operators added after `share` still run per observer.
+    pub fn share(&self) -> RiverCore {
+        self.replay(0)
+    }
+
+    /// `share`, but each observer joining a running upstream first receives
+    /// the last `buffer_size` values (fewer if fewer were emitted), then live
+    /// ones. At most `buffer_size` values are retained; the buffer starts
+    /// empty each time the upstream is (re)started.
+    pub fn replay(&self, buffer_size: usize) -> RiverCore {
+        let shared = SharedSource::new(self.clone(), buffer_size);
+        RiverCore::from_source(RiverSource::Shared(Rc::new(RefCell::new(shared)), None))
+    }
+
+    /// Pairs the nth values of both rivers into `[a, b]` arrays, holding
+    /// values from the faster side until the other catches up. Completes as
+    /// soon as either river completes.
+    pub fn zip(&self, other: &RiverCore) -> RiverCore {
+        RiverCore::from_source(RiverSource::Zip(Box::new(self.clone()), Box::new(other.clone())))
+    }
+
+    /// Emits `[latestA, latestB]` whenever either river emits, once both have
+    /// emitted at least once. Completes when both rivers have completed.
+    pub fn combine_latest(&self, other: &RiverCore) -> RiverCore {
+        RiverCore::from_source(RiverSource::CombineLatest(Box::new(self.clone()), Box::new(other.clone())))
+    }
+
+    /// Emits the latest value of this river each time `trigger` emits,
+    /// discarding the values in between; triggers before this river's first
+    /// value emit nothing. Completes when `trigger` completes, sampling the
+    /// last value after this river has completed.
+    pub fn sample_on(&self, trigger: &RiverCore) -> RiverCore {
+        RiverCore::from_source(RiverSource::SampleOn(Box::new(self.clone()), Box::new(trigger.clone())))
+    }
+
+    /// Emits everything from this river (through its operators), then,
+    /// once it completes, everything from `other`. An error from either
+    /// ends the sequence after being reported, so `other` never starts after
+    /// a failed first river, unless errors are skipped (`skip_errors` here
+    /// applies to both parts).
+    pub fn concat(&self, other: &RiverCore) -> RiverCore {
+        RiverCore::from_source(RiverSource::Concat(vec![self.clone(), other.clone()]))
+    }
+
     pub fn merge(&self, other: &RiverCore) -> RiverCore {
         self.clone_base()
     }
 
+    /// Splits this river into values matching `predicate` and the rest. The
+    /// branches share one upstream subscription (as with `share`) and the
+    /// predicate runs once per value, so every value reaches exactly one
+    /// branch. Errors reach both. Observe both branches before the source
+    /// starts emitting: values for a branch nobody is observing are dropped.
+    pub fn partition(&self, predicate: Function) -> (RiverCore, RiverCore) {
+        let mut shared = SharedSource::new(self.clone(), 0);
+        shared.router = Some(predicate);
+        let shared = Rc::new(RefCell::new(shared));
+        (
+            RiverCore::from_source(RiverSource::Shared(shared.clone(), Some(true))),
+            RiverCore::from_source(RiverSource::Shared(shared, Some(false))),
+        )
+    }
+
     pub fn fork(&self, predicate: Function) -> (RiverCore, RiverCore) {
         let mut left = self.clone_base();
         let mut right = self.clone_base();
@@ -295,10 +2063,11 @@ impl RiverCore {
 
     fn clone_base(&self) -> RiverCore {
         RiverCore {
-            source: RiverSource::Empty,
+            source: self.source.clone(),
             operators: self.operators.clone(),
-            error_handler: self.error_handler.clone(),
-            terminate_on_error: self.terminate_on_error,
+            error_mode: self.error_mode.clone(),
+            zero_copy_output: self.zero_copy_output,
+            profile: self.profile.clone(),
         }
     }
 }
@@ -318,21 +2087,87 @@ impl RiverValue {
                 array.into()
             }
             RiverValue::JsValue(val) => val.clone(),
+            RiverValue::Timestamped { t, value } => {
+                let object = Object::new();
+                let _ = js_sys::Reflect::set(&object, &"t".into(), &JsValue::from_f64(*t as f64));
+                let _ = js_sys::Reflect::set(&object, &"value".into(), &value.to_js_value());
+                object.into()
+            }
+        }
+    }
+
+    /// Typed arrays become views into WASM memory rather than copies.
+    ///
+    /// # Safety
+    /// The view aliases `self`'s buffer: it must not be used after `self` is
+    /// dropped or mutated, or after WASM memory grows.
+    pub unsafe fn to_js_view(&self) -> JsValue {
+        match self {
+            RiverValue::Bytes(bytes) => Uint8Array::view(bytes).into(),
+            RiverValue::Float32Array(floats) => Float32Array::view(floats).into(),
+            _ => self.to_js_value(),
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            RiverValue::Number(n) => Some(*n),
+            RiverValue::JsValue(val) => val.as_f64(),
+            RiverValue::Timestamped { value, .. } => value.as_f64(),
+            _ => None,
+        }
+    }
+
+    /// `(t, number)` from a `Timestamped` value or a JS `{ t, value }`
+    /// object with numeric fields.
+    pub fn as_timestamped_f64(&self) -> Option<(u64, f64)> {
+        match self {
+            RiverValue::Timestamped { t, value } => Some((*t, value.as_f64()?)),
+            RiverValue::JsValue(val) if val.is_object() => {
+                let t = js_sys::Reflect::get(val, &"t".into()).ok()?.as_f64()?;
+                let value = js_sys::Reflect::get(val, &"value".into()).ok()?.as_f64()?;
+                (t.is_finite() && t >= 0.0).then_some((t as u64, value))
+            }
+            _ => None,
         }
     }
 }
 
+// Hands `value` to `next`. In zero-copy mode typed arrays are passed as views
+// and the backing value is retained until the following emission so the view
+// does not point at freed memory while the consumer still holds it.
+fn emit(next: &Function, value: RiverValue, zero_copy: bool, retained: &mut Option<RiverValue>) {
+    if zero_copy {
+        let view = unsafe { value.to_js_view() };
+        let _ = next.call1(&JsValue::NULL, &view);
+        *retained = Some(value);
+    } else {
+        let _ = next.call1(&JsValue::NULL, &value.to_js_value());
+    }
+}
+
 pub struct SubscriptionHandle {
     pub id: String,
     pub active: Arc<Mutex<bool>>,
+    abort: AbortHandle,
+    profile: Option<Profile>,
 }
 
 impl SubscriptionHandle {
+    /// Stops emissions and drops the subscription's source, clearing any
+    /// timer it holds. `complete` is not called.
     pub fn unsubscribe(&self) {
         *self.active.lock().unwrap() = false;
+        self.abort.abort();
     }
 
     pub fn is_active(&self) -> bool {
         *self.active.lock().unwrap()
     }
+
+    /// Timings so far, keyed `"<operator>#<position>"` (e.g. `"map#0"`);
+    /// empty unless the river was built with `enable_profiling`.
+    pub fn profile(&self) -> BTreeMap<String, OperatorTiming> {
+        self.profile.as_ref().map(|profile| profile.borrow().clone()).unwrap_or_default()
+    }
 }
\ No newline at end of file
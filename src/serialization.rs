@@ -16,6 +16,9 @@ pub enum Payload {
     Float32Array(Vec<f32>),
     Control(ControlMessage),
     Error(ErrorInfo),
+    // Appended so the postcard indices of the variants above stay stable.
+    Float64Array(Vec<f64>),
+    Int32Array(Vec<i32>),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -27,13 +30,77 @@ pub enum ControlMessage {
     Complete,
     Subscribe { stream_id: String },
     Unsubscribe { stream_id: String },
+    // Appended so the postcard indices of the variants above stay stable.
+    /// Keepalive; a peer silent for too long can be presumed dead.
+    Heartbeat { timestamp: u64 },
+    Flush,
+    /// Resume delivery from `sequence`, for replay after reconnecting.
+    Seek { sequence: u64 },
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct ErrorInfo {
-    pub code: u32,
-    pub message: String,
-    pub recoverable: bool,
+pub use crate::errors::ErrorInfo;
+
+// Versioned formats start with nine 0xFF bytes and a version byte. Read as a
+// v1 frame that prefix would be a u64 varint overflowing 64 bits, which
+// postcard never produces, so `decode_postcard` can tell the formats apart.
+const V2_MARKER: [u8; 9] = [0xFF; 9];
+const FRAME_V2: u8 = 2;
+const FRAME_COMPRESSED: u8 = 3;
+
+/// Payload size cap applied by `decode_postcard`, `decode_frames` and
+/// `FrameDecoder`.
+pub const DEFAULT_MAX_PAYLOAD_BYTES: usize = 64 * 1024 * 1024;
+
+// Room for the frame fields around a maximal payload: two u64 varints, the
+// variant tag and the length prefix.
+const FRAME_FIELDS_MAX_LEN: usize = 32;
+const V2_HEADER_LEN: usize = V2_MARKER.len() + 1 + 4;
+
+/// `ErrorInfo::code` reported when a FrameV2 checksum does not match.
+pub const CHECKSUM_MISMATCH: u32 = 1001;
+
+/// FrameV2 wire format: marker, version byte, CRC32 (little-endian) of the
+/// postcard-encoded frame, then that encoding. Detects corruption on
+/// unreliable transports at the cost of 14 bytes per frame.
+pub struct FrameV2;
+
+impl FrameV2 {
+    pub fn encode(frame: &Frame) -> Result<Vec<u8>, postcard::Error> {
+        let body = postcard::to_allocvec(frame)?;
+        let mut bytes = Vec::with_capacity(V2_HEADER_LEN + body.len());
+        bytes.extend_from_slice(&V2_MARKER);
+        bytes.push(FRAME_V2);
+        bytes.extend_from_slice(&crc32fast::hash(&body).to_le_bytes());
+        bytes.extend_from_slice(&body);
+        Ok(bytes)
+    }
+
+    fn decode(bytes: &[u8], max_payload_bytes: usize) -> Result<Frame, JsValue> {
+        if bytes.len() < V2_HEADER_LEN {
+            return Err(JsValue::from_str("Truncated FrameV2 header"));
+        }
+        let version = bytes[V2_MARKER.len()];
+        if version != FRAME_V2 {
+            return Err(JsValue::from_str(&format!("Unsupported frame version: {}", version)));
+        }
+        let crc_start = V2_MARKER.len() + 1;
+        let expected = u32::from_le_bytes([
+            bytes[crc_start],
+            bytes[crc_start + 1],
+            bytes[crc_start + 2],
+            bytes[crc_start + 3],
+        ]);
+        let body = &bytes[V2_HEADER_LEN..];
+        let actual = crc32fast::hash(body);
+        if actual != expected {
+            return Err(payload_to_js(&Payload::Error(ErrorInfo {
+                code: CHECKSUM_MISMATCH,
+                message: format!("Checksum mismatch: expected {:08x}, got {:08x}", expected, actual),
+                recoverable: true,
+            })));
+        }
+        frame_from_body(body, max_payload_bytes)
+    }
 }
 
 pub fn encode_postcard(value: JsValue) -> Result<Uint8Array, JsValue> {
@@ -51,23 +118,394 @@ pub fn encode_postcard(value: JsValue) -> Result<Uint8Array, JsValue> {
     Ok(array)
 }
 
+// The version byte of a versioned frame, or `None` for a plain v1 frame.
+fn frame_version(bytes: &[u8]) -> Option<u8> {
+    if bytes.starts_with(&V2_MARKER) {
+        Some(bytes.get(V2_MARKER.len()).copied().unwrap_or(0))
+    } else {
+        None
+    }
+}
+
+/// Compression recorded in the header of a compressed frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None = 0,
+    Lz4 = 1,
+    Deflate = 2,
+}
+
+impl Compression {
+    pub fn parse(name: &str) -> Option<Compression> {
+        match name {
+            "none" => Some(Compression::None),
+            "lz4" => Some(Compression::Lz4),
+            "deflate" => Some(Compression::Deflate),
+            _ => None,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Compression> {
+        match byte {
+            0 => Some(Compression::None),
+            1 => Some(Compression::Lz4),
+            2 => Some(Compression::Deflate),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "compression")]
+    fn compress(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Compression::None => data.to_vec(),
+            Compression::Lz4 => lz4_flex::compress_prepend_size(data),
+            Compression::Deflate => miniz_oxide::deflate::compress_to_vec(data, 6),
+        }
+    }
+
+    #[cfg(feature = "compression")]
+    fn decompress(self, data: &[u8], max_len: usize) -> Result<Vec<u8>, String> {
+        match self {
+            Compression::None => Ok(data.to_vec()),
+            Compression::Lz4 => {
+                let size = data.get(..4)
+                    .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+                    .ok_or("Truncated lz4 size prefix")?;
+                if size > max_len {
+                    return Err(format!("Decompressed frame too large: {} bytes", size));
+                }
+                lz4_flex::decompress_size_prepended(data).map_err(|e| e.to_string())
+            }
+            Compression::Deflate => {
+                miniz_oxide::inflate::decompress_to_vec_with_limit(data, max_len)
+                    .map_err(|_| format!("Decompressed frame exceeds {} bytes", max_len))
+            }
+        }
+    }
+
+    #[cfg(not(feature = "compression"))]
+    fn decompress(self, data: &[u8], _max_len: usize) -> Result<Vec<u8>, String> {
+        match self {
+            Compression::None => Ok(data.to_vec()),
+            _ => Err("compression feature not enabled".to_string()),
+        }
+    }
+}
+
+/// Encodes a frame compressed with `algo` ("none", "lz4" or "deflate").
+/// Falls back to "none" when compression would not make the frame smaller,
+/// which is typical for small or already-dense payloads.
+#[cfg(feature = "compression")]
+pub fn encode_postcard_compressed(value: JsValue, algo: &str) -> Result<Uint8Array, JsValue> {
+    let algo = Compression::parse(algo)
+        .ok_or_else(|| JsValue::from_str(&format!("Unknown compression: {}", algo)))?;
+    let js_obj = value.dyn_into::<Object>()
+        .map_err(|_| JsValue::from_str("Value must be an object"))?;
+
+    let frame = js_to_frame(&js_obj)?;
+    let body = postcard::to_allocvec(&frame)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))?;
+
+    let mut compressed = algo.compress(&body);
+    let mut algo = algo;
+    if compressed.len() >= body.len() {
+        compressed = body;
+        algo = Compression::None;
+    }
+
+    let mut bytes = Vec::with_capacity(V2_MARKER.len() + 2 + compressed.len());
+    bytes.extend_from_slice(&V2_MARKER);
+    bytes.push(FRAME_COMPRESSED);
+    bytes.push(algo as u8);
+    bytes.extend_from_slice(&compressed);
+
+    let array = Uint8Array::new_with_length(bytes.len() as u32);
+    array.copy_from(&bytes);
+    Ok(array)
+}
+
+fn decode_compressed(bytes: &[u8], max_payload_bytes: usize) -> Result<Frame, JsValue> {
+    let algo_byte = *bytes.get(V2_MARKER.len() + 1)
+        .ok_or_else(|| JsValue::from_str("Truncated compressed frame header"))?;
+    let algo = Compression::from_byte(algo_byte)
+        .ok_or_else(|| JsValue::from_str(&format!("Unknown compression: {}", algo_byte)))?;
+    let max_len = max_payload_bytes.saturating_add(FRAME_FIELDS_MAX_LEN);
+    let body = algo.decompress(&bytes[V2_MARKER.len() + 2..], max_len)
+        .map_err(|e| JsValue::from_str(&format!("Decompression error: {}", e)))?;
+    frame_from_body(&body, max_payload_bytes)
+}
+
+// Reads a postcard (LEB128) varint from the front of `bytes`.
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+        value |= u64::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+// The size in bytes a frame body declares for its array or string payload,
+// read from the length prefix without deserializing anything.
+fn declared_payload_len(mut body: &[u8]) -> Option<u64> {
+    let bytes = &mut body;
+    read_varint(bytes)?; // sequence
+    read_varint(bytes)?; // timestamp
+    let element_size = match read_varint(bytes)? {
+        0 => 1, // Data
+        1 => 4, // Float32Array
+        3 => {
+            read_varint(bytes)?; // ErrorInfo::code
+            1 // ErrorInfo::message
+        }
+        4 => 8, // Float64Array
+        5 => 1, // Int32Array, varint-encoded: at least one byte each
+        _ => return Some(0),
+    };
+    Some(read_varint(bytes)?.saturating_mul(element_size))
+}
+
+// Deserializes a postcard-encoded frame after checking its declared payload
+// length against `max_payload_bytes`.
+fn frame_from_body(body: &[u8], max_payload_bytes: usize) -> Result<Frame, JsValue> {
+    if let Some(len) = declared_payload_len(body) {
+        if len > max_payload_bytes as u64 {
+            return Err(JsValue::from_str(&format!(
+                "Payload of {} bytes exceeds limit of {} bytes",
+                len, max_payload_bytes
+            )));
+        }
+    }
+    postcard::from_bytes(body)
+        .map_err(|e| JsValue::from_str(&format!("Deserialization error: {}", e)))
+}
+
+/// Like `encode_postcard`, but in the checksummed FrameV2 format.
+pub fn encode_postcard_v2(value: JsValue) -> Result<Uint8Array, JsValue> {
+    let js_obj = value.dyn_into::<Object>()
+        .map_err(|_| JsValue::from_str("Value must be an object"))?;
+
+    let frame = js_to_frame(&js_obj)?;
+
+    let bytes = FrameV2::encode(&frame)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))?;
+
+    let array = Uint8Array::new_with_length(bytes.len() as u32);
+    array.copy_from(&bytes);
+
+    Ok(array)
+}
+
+/// Decodes plain postcard frames, FrameV2 and compressed frames (the latter
+/// need the `compression` feature unless stored uncompressed). A FrameV2 checksum
+/// mismatch rejects with an error-payload object (`type: "error"`,
+/// `code: CHECKSUM_MISMATCH`) rather than a string. Payloads larger than
+/// `DEFAULT_MAX_PAYLOAD_BYTES` are rejected.
 pub fn decode_postcard(bytes: &Uint8Array) -> Result<JsValue, JsValue> {
+    decode_postcard_with_limit(bytes, DEFAULT_MAX_PAYLOAD_BYTES)
+}
+
+/// `decode_postcard` with a caller-chosen cap on the payload's declared size
+/// (array or message bytes, after decompression). Oversized frames are
+/// rejected before anything is allocated for them.
+pub fn decode_postcard_with_limit(bytes: &Uint8Array, max_payload_bytes: usize) -> Result<JsValue, JsValue> {
     let vec = bytes.to_vec();
     
-    let frame: Frame = postcard::from_bytes(&vec)
-        .map_err(|e| JsValue::from_str(&format!("Deserialization error: {}", e)))?;
+    let frame: Frame = match frame_version(&vec) {
+        None => frame_from_body(&vec, max_payload_bytes)?,
+        Some(FRAME_V2) => FrameV2::decode(&vec, max_payload_bytes)?,
+        Some(FRAME_COMPRESSED) => decode_compressed(&vec, max_payload_bytes)?,
+        Some(version) => {
+            return Err(JsValue::from_str(&format!("Unsupported frame version: {}", version)))
+        }
+    };
     
     Ok(frame_to_js(&frame))
 }
 
+/// Human-readable alternative to `encode_postcard` using the same serde
+/// representation, e.g. `{"sequence":1,"timestamp":2,"payload":{"Float32Array":[0.5]}}`.
+/// Typed arrays become number arrays; NaN and infinities are not representable
+/// in JSON and encode as `null`, which `decode_json` rejects.
+#[cfg(feature = "json")]
+pub fn encode_json(value: JsValue) -> Result<String, JsValue> {
+    let js_obj = value.dyn_into::<Object>()
+        .map_err(|_| JsValue::from_str("Value must be an object"))?;
+
+    let frame = js_to_frame(&js_obj)?;
+
+    serde_json::to_string(&frame)
+        .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))
+}
+
+#[cfg(feature = "json")]
+pub fn decode_json(s: &str) -> Result<JsValue, JsValue> {
+    let frame: Frame = serde_json::from_str(s)
+        .map_err(|e| JsValue::from_str(&format!("Deserialization error: {}", e)))?;
+
+    Ok(frame_to_js(&frame))
+}
+
+/// Encodes an array of frames into one buffer of `FrameCodec` frames, so a
+/// `FrameDecoder` reads it as well as `decode_frames`.
+pub fn encode_frames(frames: &Array) -> Result<Uint8Array, JsValue> {
+    let mut bytes = Vec::new();
+    for value in frames.iter() {
+        let js_obj = value.dyn_into::<Object>()
+            .map_err(|_| JsValue::from_str("Each frame must be an object"))?;
+        let frame = js_to_frame(&js_obj)?;
+        let encoded = FrameCodec::encode(&frame)
+            .map_err(|e| JsValue::from_str(&format!("Serialization error: {}", e)))?;
+        bytes.extend_from_slice(&encoded);
+    }
+
+    let array = Uint8Array::new_with_length(bytes.len() as u32);
+    array.copy_from(&bytes);
+    Ok(array)
+}
+
+/// Inverse of `encode_frames`. A truncated or corrupt buffer is an error, as
+/// is a frame too long to hold a payload of `DEFAULT_MAX_PAYLOAD_BYTES`.
+pub fn decode_frames(bytes: &Uint8Array) -> Result<Array, JsValue> {
+    decode_frames_with_limit(bytes, DEFAULT_MAX_PAYLOAD_BYTES)
+}
+
+/// Like `decode_frames`, with a caller-chosen payload size limit.
+pub fn decode_frames_with_limit(bytes: &Uint8Array, max_payload_bytes: usize) -> Result<Array, JsValue> {
+    let frames = decode_frame_batch(&bytes.to_vec(), max_payload_bytes).map_err(|e| JsValue::from_str(&e))?;
+    Ok(frames.iter().map(frame_to_js).collect())
+}
+
+// A frame's postcard bytes and the bytes after it.
+type SplitFrame<'a> = (&'a [u8], &'a [u8]);
+
+// Splits the `FrameCodec` frame at the front of `bytes`, or `None` while it is
+// incomplete. A length prefix declaring more than a frame around a payload of
+// `max_payload_bytes` can take is an error, before anything waits for those
+// bytes.
+fn split_frame(bytes: &[u8], max_payload_bytes: usize) -> Result<Option<SplitFrame<'_>>, String> {
+    let Some((prefix, rest)) = bytes.split_first_chunk::<4>() else {
+        return Ok(None);
+    };
+    let len = u32::from_be_bytes(*prefix) as usize;
+    let max_len = max_payload_bytes.saturating_add(FRAME_FIELDS_MAX_LEN);
+    if len > max_len {
+        return Err(format!("Frame of {} bytes exceeds limit of {} bytes", len, max_len));
+    }
+    Ok((rest.len() >= len).then(|| rest.split_at(len)))
+}
+
+fn decode_frame_batch(mut bytes: &[u8], max_payload_bytes: usize) -> Result<Vec<Frame>, String> {
+    let mut frames = Vec::new();
+    while !bytes.is_empty() {
+        let Some((encoded, rest)) = split_frame(bytes, max_payload_bytes)? else {
+            return Err(format!("Truncated frame {}: {} bytes left", frames.len(), bytes.len()));
+        };
+        let frame = postcard::from_bytes(encoded)
+            .map_err(|e| format!("Deserialization error in frame {}: {}", frames.len(), e))?;
+        frames.push(frame);
+        bytes = rest;
+    }
+    Ok(frames)
+}
+
+/// Self-delimiting framing for byte transports: each frame is its postcard
+/// bytes preceded by their length as a big-endian u32.
+pub struct FrameCodec;
+
+impl FrameCodec {
+    pub fn encode(frame: &Frame) -> Result<Vec<u8>, postcard::Error> {
+        let encoded = postcard::to_allocvec(frame)?;
+        let mut bytes = Vec::with_capacity(4 + encoded.len());
+        bytes.extend_from_slice(&(encoded.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&encoded);
+        Ok(bytes)
+    }
+}
+
+/// Reassembles `FrameCodec` output that arrives in arbitrary chunks.
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+    max_payload_bytes: usize,
+}
+
+impl Default for FrameDecoder {
+    fn default() -> Self {
+        Self::with_max_payload_bytes(DEFAULT_MAX_PAYLOAD_BYTES)
+    }
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A decoder rejecting length prefixes too long for a payload of
+    /// `max_payload_bytes`, rather than buffering toward them.
+    pub fn with_max_payload_bytes(max_payload_bytes: usize) -> Self {
+        Self { buffer: Vec::new(), max_payload_bytes }
+    }
+
+    /// Buffers `bytes` and returns every frame completed by them, in order.
+    /// A partial trailing frame is kept for the next push. A frame that fails
+    /// to deserialize stops decoding: the frames before it are still returned,
+    /// alongside the error, and the bytes after it stay buffered. An oversized
+    /// length prefix is an error too, and drops the buffer: the framing after
+    /// it can't be trusted.
+    pub fn push(&mut self, bytes: &[u8]) -> (Vec<Frame>, Option<String>) {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut frames = Vec::new();
+        let mut consumed = 0;
+        let error = loop {
+            let (encoded, rest) = match split_frame(&self.buffer[consumed..], self.max_payload_bytes) {
+                Ok(Some(frame)) => frame,
+                Ok(None) => break None,
+                Err(e) => {
+                    consumed = self.buffer.len();
+                    break Some(e);
+                }
+            };
+            consumed = self.buffer.len() - rest.len();
+            match postcard::from_bytes(encoded) {
+                Ok(frame) => frames.push(frame),
+                Err(e) => break Some(format!("Deserialization error: {}", e)),
+            }
+        };
+
+        self.buffer.drain(..consumed);
+        (frames, error)
+    }
+
+    /// Bytes held back waiting for the rest of a frame.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+}
+
 fn js_to_frame(obj: &Object) -> Result<Frame, JsValue> {
     let sequence = js_sys::Reflect::get(obj, &"sequence".into())?
         .as_f64()
         .ok_or_else(|| JsValue::from_str("sequence must be a number"))? as u64;
     
-    let timestamp = js_sys::Reflect::get(obj, &"timestamp".into())?
-        .as_f64()
-        .ok_or_else(|| JsValue::from_str("timestamp must be a number"))? as u64;
+    // A missing or zero timestamp means "now", in milliseconds since the epoch.
+    let timestamp = js_sys::Reflect::get(obj, &"timestamp".into())?;
+    let timestamp = if timestamp.is_undefined() {
+        0
+    } else {
+        timestamp
+            .as_f64()
+            .ok_or_else(|| JsValue::from_str("timestamp must be a number"))? as u64
+    };
+    let timestamp = if timestamp == 0 {
+        js_sys::Date::now() as u64
+    } else {
+        timestamp
+    };
     
     let payload_obj = js_sys::Reflect::get(obj, &"payload".into())?;
     let payload = js_to_payload(&payload_obj)?;
@@ -80,7 +518,7 @@ fn js_to_frame(obj: &Object) -> Result<Frame, JsValue> {
 }
 
 fn js_to_payload(value: &JsValue) -> Result<Payload, JsValue> {
-    if let Ok(obj) = value.dyn_ref::<Object>() {
+    if let Some(obj) = value.dyn_ref::<Object>() {
         if let Ok(payload_type) = js_sys::Reflect::get(obj, &"type".into()) {
             let type_str = payload_type.as_string()
                 .ok_or_else(|| JsValue::from_str("payload type must be a string"))?;
@@ -102,6 +540,22 @@ fn js_to_payload(value: &JsValue) -> Result<Payload, JsValue> {
                         Err(JsValue::from_str("float32 payload must be Float32Array"))
                     }
                 }
+                "float64" => {
+                    let data = js_sys::Reflect::get(obj, &"data".into())?;
+                    if let Ok(float64_array) = data.dyn_into::<js_sys::Float64Array>() {
+                        Ok(Payload::Float64Array(float64_array.to_vec()))
+                    } else {
+                        Err(JsValue::from_str("float64 payload must be Float64Array"))
+                    }
+                }
+                "int32" => {
+                    let data = js_sys::Reflect::get(obj, &"data".into())?;
+                    if let Ok(int32_array) = data.dyn_into::<js_sys::Int32Array>() {
+                        Ok(Payload::Int32Array(int32_array.to_vec()))
+                    } else {
+                        Err(JsValue::from_str("int32 payload must be Int32Array"))
+                    }
+                }
                 "control" => {
                     let msg = js_sys::Reflect::get(obj, &"message".into())?;
                     let control = js_to_control_message(&msg)?;
@@ -122,7 +576,7 @@ fn js_to_payload(value: &JsValue) -> Result<Payload, JsValue> {
 }
 
 fn js_to_control_message(value: &JsValue) -> Result<ControlMessage, JsValue> {
-    if let Ok(obj) = value.dyn_ref::<Object>() {
+    if let Some(obj) = value.dyn_ref::<Object>() {
         let msg_type = js_sys::Reflect::get(obj, &"type".into())?
             .as_string()
             .ok_or_else(|| JsValue::from_str("control message type must be a string"))?;
@@ -155,6 +609,19 @@ fn js_to_control_message(value: &JsValue) -> Result<ControlMessage, JsValue> {
                     .ok_or_else(|| JsValue::from_str("stream_id must be a string"))?;
                 Ok(ControlMessage::Unsubscribe { stream_id })
             }
+            "heartbeat" => {
+                let timestamp = js_sys::Reflect::get(obj, &"timestamp".into())?
+                    .as_f64()
+                    .ok_or_else(|| JsValue::from_str("heartbeat timestamp must be a number"))? as u64;
+                Ok(ControlMessage::Heartbeat { timestamp })
+            }
+            "flush" => Ok(ControlMessage::Flush),
+            "seek" => {
+                let sequence = js_sys::Reflect::get(obj, &"sequence".into())?
+                    .as_f64()
+                    .ok_or_else(|| JsValue::from_str("seek sequence must be a number"))? as u64;
+                Ok(ControlMessage::Seek { sequence })
+            }
             _ => Err(JsValue::from_str("Unknown control message type"))
         }
     } else {
@@ -182,7 +649,7 @@ fn js_to_error_info(obj: &Object) -> Result<ErrorInfo, JsValue> {
     })
 }
 
-fn frame_to_js(frame: &Frame) -> JsValue {
+pub(crate) fn frame_to_js(frame: &Frame) -> JsValue {
     let obj = Object::new();
     
     js_sys::Reflect::set(&obj, &"sequence".into(), &JsValue::from(frame.sequence as f64)).unwrap();
@@ -208,6 +675,18 @@ fn payload_to_js(payload: &Payload) -> JsValue {
             array.copy_from(floats);
             js_sys::Reflect::set(&obj, &"data".into(), &array.into()).unwrap();
         }
+        Payload::Float64Array(floats) => {
+            js_sys::Reflect::set(&obj, &"type".into(), &"float64".into()).unwrap();
+            let array = js_sys::Float64Array::new_with_length(floats.len() as u32);
+            array.copy_from(floats);
+            js_sys::Reflect::set(&obj, &"data".into(), &array.into()).unwrap();
+        }
+        Payload::Int32Array(ints) => {
+            js_sys::Reflect::set(&obj, &"type".into(), &"int32".into()).unwrap();
+            let array = js_sys::Int32Array::new_with_length(ints.len() as u32);
+            array.copy_from(ints);
+            js_sys::Reflect::set(&obj, &"data".into(), &array.into()).unwrap();
+        }
         Payload::Control(msg) => {
             js_sys::Reflect::set(&obj, &"type".into(), &"control".into()).unwrap();
             js_sys::Reflect::set(&obj, &"message".into(), &control_message_to_js(msg)).unwrap();
@@ -252,6 +731,17 @@ fn control_message_to_js(msg: &ControlMessage) -> JsValue {
             js_sys::Reflect::set(&obj, &"type".into(), &"unsubscribe".into()).unwrap();
             js_sys::Reflect::set(&obj, &"streamId".into(), &stream_id.as_str().into()).unwrap();
         }
+        ControlMessage::Heartbeat { timestamp } => {
+            js_sys::Reflect::set(&obj, &"type".into(), &"heartbeat".into()).unwrap();
+            js_sys::Reflect::set(&obj, &"timestamp".into(), &JsValue::from(*timestamp as f64)).unwrap();
+        }
+        ControlMessage::Flush => {
+            js_sys::Reflect::set(&obj, &"type".into(), &"flush".into()).unwrap();
+        }
+        ControlMessage::Seek { sequence } => {
+            js_sys::Reflect::set(&obj, &"type".into(), &"seek".into()).unwrap();
+            js_sys::Reflect::set(&obj, &"sequence".into(), &JsValue::from(*sequence as f64)).unwrap();
+        }
     }
     
     obj.into()
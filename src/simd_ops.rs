@@ -2,7 +2,7 @@
 use core::arch::wasm32::*;
 
 #[cfg(feature = "simd")]
-#[target_feature(enable = "simd128")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
 pub fn f32x_map_mul_add(data: &[f32], a: f32, b: f32) -> Vec<f32> {
     #[cfg(target_arch = "wasm32")]
     unsafe {
@@ -13,14 +13,12 @@ pub fn f32x_map_mul_add(data: &[f32], a: f32, b: f32) -> Vec<f32> {
         let b_vec = f32x4_splat(b);
         
         let chunks = len / 4;
-        let remainder = len % 4;
         
         for i in 0..chunks {
             let offset = i * 4;
             let v = v128_load(data.as_ptr().add(offset) as *const v128);
-            let v_f32 = v128_bitcast_f32x4(v);
             
-            let mul_result = f32x4_mul(v_f32, a_vec);
+            let mul_result = f32x4_mul(v, a_vec);
             let add_result = f32x4_add(mul_result, b_vec);
             
             let mut temp = [0f32; 4];
@@ -42,7 +40,7 @@ pub fn f32x_map_mul_add(data: &[f32], a: f32, b: f32) -> Vec<f32> {
 }
 
 #[cfg(feature = "simd")]
-#[target_feature(enable = "simd128")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
 pub fn f32x_dot_product(a: &[f32], b: &[f32]) -> f32 {
     #[cfg(target_arch = "wasm32")]
     unsafe {
@@ -50,7 +48,6 @@ pub fn f32x_dot_product(a: &[f32], b: &[f32]) -> f32 {
         let len = a.len();
         
         let chunks = len / 4;
-        let remainder = len % 4;
         
         let mut sum_vec = f32x4_splat(0.0);
         
@@ -59,10 +56,7 @@ pub fn f32x_dot_product(a: &[f32], b: &[f32]) -> f32 {
             let a_vec = v128_load(a.as_ptr().add(offset) as *const v128);
             let b_vec = v128_load(b.as_ptr().add(offset) as *const v128);
             
-            let a_f32 = v128_bitcast_f32x4(a_vec);
-            let b_f32 = v128_bitcast_f32x4(b_vec);
-            
-            let mul = f32x4_mul(a_f32, b_f32);
+            let mul = f32x4_mul(a_vec, b_vec);
             sum_vec = f32x4_add(sum_vec, mul);
         }
         
@@ -84,7 +78,7 @@ pub fn f32x_dot_product(a: &[f32], b: &[f32]) -> f32 {
 }
 
 #[cfg(feature = "simd")]
-#[target_feature(enable = "simd128")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
 pub fn f32x_vector_add(a: &[f32], b: &[f32]) -> Vec<f32> {
     #[cfg(target_arch = "wasm32")]
     unsafe {
@@ -93,17 +87,13 @@ pub fn f32x_vector_add(a: &[f32], b: &[f32]) -> Vec<f32> {
         let mut result = Vec::with_capacity(len);
         
         let chunks = len / 4;
-        let remainder = len % 4;
         
         for i in 0..chunks {
             let offset = i * 4;
             let a_vec = v128_load(a.as_ptr().add(offset) as *const v128);
             let b_vec = v128_load(b.as_ptr().add(offset) as *const v128);
             
-            let a_f32 = v128_bitcast_f32x4(a_vec);
-            let b_f32 = v128_bitcast_f32x4(b_vec);
-            
-            let sum = f32x4_add(a_f32, b_f32);
+            let sum = f32x4_add(a_vec, b_vec);
             
             let mut temp = [0f32; 4];
             v128_store(temp.as_mut_ptr() as *mut v128, sum);
@@ -124,9 +114,9 @@ pub fn f32x_vector_add(a: &[f32], b: &[f32]) -> Vec<f32> {
 }
 
 #[cfg(feature = "simd")]
-#[target_feature(enable = "simd128")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
 pub fn f32x_rolling_mean(data: &[f32], window_size: usize) -> Vec<f32> {
-    if window_size > data.len() {
+    if window_size == 0 || window_size > data.len() {
         return vec![];
     }
     
@@ -135,21 +125,17 @@ pub fn f32x_rolling_mean(data: &[f32], window_size: usize) -> Vec<f32> {
     
     #[cfg(target_arch = "wasm32")]
     unsafe {
-        let inv_window_vec = f32x4_splat(inv_window);
-        
         for i in 0..=(data.len() - window_size) {
             let window = &data[i..i + window_size];
             
             let chunks = window_size / 4;
-            let remainder = window_size % 4;
             
             let mut sum_vec = f32x4_splat(0.0);
             
             for j in 0..chunks {
                 let offset = j * 4;
                 let v = v128_load(window.as_ptr().add(offset) as *const v128);
-                let v_f32 = v128_bitcast_f32x4(v);
-                sum_vec = f32x4_add(sum_vec, v_f32);
+                sum_vec = f32x4_add(sum_vec, v);
             }
             
             let mut temp = [0f32; 4];
@@ -178,7 +164,7 @@ pub fn f32x_rolling_mean(data: &[f32], window_size: usize) -> Vec<f32> {
 }
 
 #[cfg(feature = "simd")]
-#[target_feature(enable = "simd128")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
 pub fn f32x_min_max(data: &[f32]) -> (f32, f32) {
     if data.is_empty() {
         return (0.0, 0.0);
@@ -188,21 +174,24 @@ pub fn f32x_min_max(data: &[f32]) -> (f32, f32) {
     unsafe {
         let len = data.len();
         let chunks = len / 4;
-        let remainder = len % 4;
+        
+        if chunks == 0 {
+            let min = data.iter().cloned().fold(f32::INFINITY, f32::min);
+            let max = data.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+            return (min, max);
+        }
         
         let first_vec = v128_load(data.as_ptr() as *const v128);
-        let first_f32 = v128_bitcast_f32x4(first_vec);
         
-        let mut min_vec = first_f32;
-        let mut max_vec = first_f32;
+        let mut min_vec = first_vec;
+        let mut max_vec = first_vec;
         
         for i in 1..chunks {
             let offset = i * 4;
             let v = v128_load(data.as_ptr().add(offset) as *const v128);
-            let v_f32 = v128_bitcast_f32x4(v);
             
-            min_vec = f32x4_min(min_vec, v_f32);
-            max_vec = f32x4_max(max_vec, v_f32);
+            min_vec = f32x4_min(min_vec, v);
+            max_vec = f32x4_max(max_vec, v);
         }
         
         let mut min_temp = [0f32; 4];
@@ -227,4 +216,937 @@ pub fn f32x_min_max(data: &[f32]) -> (f32, f32) {
         let max = data.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
         (min, max)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_cumsum(data: &[f32]) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let len = data.len();
+        let mut result = Vec::with_capacity(len);
+        
+        let chunks = len / 4;
+        let zero = f32x4_splat(0.0);
+        let mut carry = 0.0f32;
+        
+        // Per-block prefix sum in two shift-and-add steps, then propagate the
+        // running total of the previous blocks as a carry.
+        for i in 0..chunks {
+            let offset = i * 4;
+            let mut v = v128_load(data.as_ptr().add(offset) as *const v128);
+            
+            v = f32x4_add(v, i32x4_shuffle::<0, 4, 5, 6>(zero, v));
+            v = f32x4_add(v, i32x4_shuffle::<0, 1, 4, 5>(zero, v));
+            v = f32x4_add(v, f32x4_splat(carry));
+            
+            let mut temp = [0f32; 4];
+            v128_store(temp.as_mut_ptr() as *mut v128, v);
+            result.extend_from_slice(&temp);
+            carry = temp[3];
+        }
+        
+        for x in &data[chunks * 4..] {
+            carry += x;
+            result.push(carry);
+        }
+        
+        result
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let mut sum = 0.0f32;
+        data.iter().map(|x| {
+            sum += x;
+            sum
+        }).collect()
+    }
+}
+
+/// Z-score normalization, dividing by `std + epsilon`. With a positive
+/// epsilon a constant input (zero standard deviation) yields all zeros
+/// instead of NaN.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_normalize(data: &[f32], epsilon: f32) -> Vec<f32> {
+    if crate::dispatch::is_constant(data) {
+        return vec![0.0; data.len()];
+    }
+    
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let len = data.len();
+        let chunks = len / 4;
+        
+        let mut sum_vec = f32x4_splat(0.0);
+        for i in 0..chunks {
+            let v = v128_load(data.as_ptr().add(i * 4) as *const v128);
+            sum_vec = f32x4_add(sum_vec, v);
+        }
+        let mut temp = [0f32; 4];
+        v128_store(temp.as_mut_ptr() as *mut v128, sum_vec);
+        let mut sum = temp[0] + temp[1] + temp[2] + temp[3];
+        for x in &data[chunks * 4..] {
+            sum += x;
+        }
+        let mean = sum / len as f32;
+        
+        let mean_vec = f32x4_splat(mean);
+        let mut sq_vec = f32x4_splat(0.0);
+        for i in 0..chunks {
+            let v = v128_load(data.as_ptr().add(i * 4) as *const v128);
+            let d = f32x4_sub(v, mean_vec);
+            sq_vec = f32x4_add(sq_vec, f32x4_mul(d, d));
+        }
+        v128_store(temp.as_mut_ptr() as *mut v128, sq_vec);
+        let mut sq_sum = temp[0] + temp[1] + temp[2] + temp[3];
+        for x in &data[chunks * 4..] {
+            sq_sum += (x - mean) * (x - mean);
+        }
+        let std_dev = (sq_sum / len as f32).sqrt() + epsilon;
+        
+        if std_dev == 0.0 {
+            return vec![0.0; len];
+        }
+        
+        let inv_std_vec = f32x4_splat(1.0 / std_dev);
+        let mut result = Vec::with_capacity(len);
+        for i in 0..chunks {
+            let v = v128_load(data.as_ptr().add(i * 4) as *const v128);
+            let scaled = f32x4_mul(f32x4_sub(v, mean_vec), inv_std_vec);
+            v128_store(temp.as_mut_ptr() as *mut v128, scaled);
+            result.extend_from_slice(&temp);
+        }
+        for x in &data[chunks * 4..] {
+            result.push((x - mean) / std_dev);
+        }
+        
+        result
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let mean = data.iter().sum::<f32>() / data.len() as f32;
+        let variance = data.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / data.len() as f32;
+        let std_dev = variance.sqrt() + epsilon;
+        if std_dev == 0.0 {
+            return vec![0.0; data.len()];
+        }
+        data.iter().map(|x| (x - mean) / std_dev).collect()
+    }
+}
+
+// Applies a lane-wise SIMD op to full 4-lane chunks and the scalar op to the tail.
+#[cfg(all(feature = "simd", target_arch = "wasm32"))]
+#[target_feature(enable = "simd128")]
+unsafe fn map_lanes(
+    data: &[f32],
+    vector_op: impl Fn(v128) -> v128,
+    scalar_op: impl Fn(f32) -> f32,
+) -> Vec<f32> {
+    let len = data.len();
+    let mut result = Vec::with_capacity(len);
+    let chunks = len / 4;
+    
+    for i in 0..chunks {
+        let v = v128_load(data.as_ptr().add(i * 4) as *const v128);
+        let mut temp = [0f32; 4];
+        v128_store(temp.as_mut_ptr() as *mut v128, vector_op(v));
+        result.extend_from_slice(&temp);
+    }
+    
+    for x in &data[chunks * 4..] {
+        result.push(scalar_op(*x));
+    }
+    
+    result
+}
+
+// exp(x) via range reduction x = k*ln2 + r with |r| <= ln2/2, a degree-6
+// polynomial for exp(r) and 2^k assembled directly in the exponent bits.
+// Relative error is below 2e-7 over the clamped input range.
+#[cfg(all(feature = "simd", target_arch = "wasm32"))]
+#[target_feature(enable = "simd128")]
+fn f32x4_exp_approx(x: v128) -> v128 {
+    let x = f32x4_min(f32x4_max(x, f32x4_splat(-87.0)), f32x4_splat(88.0));
+    let k = f32x4_nearest(f32x4_mul(x, f32x4_splat(std::f32::consts::LOG2_E)));
+    let r = f32x4_sub(x, f32x4_mul(k, f32x4_splat(std::f32::consts::LN_2)));
+    
+    let mut p = f32x4_splat(1.0 / 720.0);
+    p = f32x4_add(f32x4_mul(p, r), f32x4_splat(1.0 / 120.0));
+    p = f32x4_add(f32x4_mul(p, r), f32x4_splat(1.0 / 24.0));
+    p = f32x4_add(f32x4_mul(p, r), f32x4_splat(1.0 / 6.0));
+    p = f32x4_add(f32x4_mul(p, r), f32x4_splat(0.5));
+    p = f32x4_add(f32x4_mul(p, r), f32x4_splat(1.0));
+    p = f32x4_add(f32x4_mul(p, r), f32x4_splat(1.0));
+    
+    let exponent = i32x4_shl(i32x4_add(i32x4_trunc_sat_f32x4(k), i32x4_splat(127)), 23);
+    f32x4_mul(p, exponent)
+}
+
+#[cfg(all(feature = "simd", target_arch = "wasm32"))]
+#[target_feature(enable = "simd128")]
+fn f32x4_sigmoid(x: v128) -> v128 {
+    let one = f32x4_splat(1.0);
+    f32x4_div(one, f32x4_add(one, f32x4_exp_approx(f32x4_neg(x))))
+}
+
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_relu(data: &[f32]) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let zero = f32x4_splat(0.0);
+        // `pmax(0, v)` keeps 0 unless `0 < v`, so NaN maps to 0 like the
+        // scalar `x.max(0.0)`; `f32x4_max` would propagate it.
+        map_lanes(data, |v| f32x4_pmax(zero, v), |x| x.max(0.0))
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        data.iter().map(|x| x.max(0.0)).collect()
+    }
+}
+
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_leaky_relu(data: &[f32], alpha: f32) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let zero = f32x4_splat(0.0);
+        let alpha_vec = f32x4_splat(alpha);
+        map_lanes(
+            data,
+            |v| v128_bitselect(v, f32x4_mul(v, alpha_vec), f32x4_gt(v, zero)),
+            |x| if x > 0.0 { x } else { x * alpha },
+        )
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        data.iter().map(|&x| if x > 0.0 { x } else { x * alpha }).collect()
+    }
+}
+
+/// Limits each value to `[lo, hi]`; NaN stays NaN. Requires `lo <= hi`.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_clamp(data: &[f32], lo: f32, hi: f32) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let lo_vec = f32x4_splat(lo);
+        let hi_vec = f32x4_splat(hi);
+        map_lanes(data, |v| f32x4_max(f32x4_min(v, hi_vec), lo_vec), |x| x.clamp(lo, hi))
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        data.iter().map(|x| x.clamp(lo, hi)).collect()
+    }
+}
+
+/// Absolute value by clearing the sign bit, so `-0.0` becomes `0.0` and NaN
+/// stays NaN, exactly like `f32::abs`.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_abs(data: &[f32]) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let magnitude = u32x4_splat(0x7fff_ffff);
+        map_lanes(data, |v| v128_and(v, magnitude), |x| x.abs())
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        data.iter().map(|x| x.abs()).collect()
+    }
+}
+
+/// `x + scalar` for every element.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_scalar_add(data: &[f32], scalar: f32) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let s = f32x4_splat(scalar);
+        map_lanes(data, |v| f32x4_add(v, s), |x| x + scalar)
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        data.iter().map(|x| x + scalar).collect()
+    }
+}
+
+/// `x - scalar` for every element.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_scalar_sub(data: &[f32], scalar: f32) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let s = f32x4_splat(scalar);
+        map_lanes(data, |v| f32x4_sub(v, s), |x| x - scalar)
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        data.iter().map(|x| x - scalar).collect()
+    }
+}
+
+/// `x * scalar` for every element.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_scalar_mul(data: &[f32], scalar: f32) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let s = f32x4_splat(scalar);
+        map_lanes(data, |v| f32x4_mul(v, s), |x| x * scalar)
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        data.iter().map(|x| x * scalar).collect()
+    }
+}
+
+/// `x / scalar` for every element. Dividing by zero follows IEEE 754 like
+/// the scalar path: ±inf for non-zero `x`, NaN for `0 / 0`.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_scalar_div(data: &[f32], scalar: f32) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let s = f32x4_splat(scalar);
+        map_lanes(data, |v| f32x4_div(v, s), |x| x / scalar)
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        data.iter().map(|x| x / scalar).collect()
+    }
+}
+
+/// Sum of all elements; 0 for an empty slice. The SIMD path keeps a Kahan
+/// compensation term per lane and merges lanes and tail in f64, so the error
+/// stays within a few ulps of the sum of magnitudes however long the input
+/// (plain f32 accumulation drifts as the total outgrows each element).
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_sum(data: &[f32]) -> f32 {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let chunks = data.len() / 4;
+        let mut sum = f32x4_splat(0.0);
+        let mut compensation = f32x4_splat(0.0);
+        for i in 0..chunks {
+            let v = v128_load(data.as_ptr().add(i * 4) as *const v128);
+            let y = f32x4_sub(v, compensation);
+            let t = f32x4_add(sum, y);
+            compensation = f32x4_sub(f32x4_sub(t, sum), y);
+            sum = t;
+        }
+
+        let mut sums = [0f32; 4];
+        let mut compensations = [0f32; 4];
+        v128_store(sums.as_mut_ptr() as *mut v128, sum);
+        v128_store(compensations.as_mut_ptr() as *mut v128, compensation);
+        let lanes: f64 = sums.iter().zip(&compensations).map(|(&s, &c)| s as f64 - c as f64).sum();
+        let tail: f64 = data[chunks * 4..].iter().map(|&x| x as f64).sum();
+        let total = lanes + tail;
+        if total.is_finite() {
+            return total as f32;
+        }
+        // An infinite input makes the compensation `inf - inf`, so redo the
+        // sum plainly to get the infinity (or NaN) the scalar path gives.
+        data.iter().map(|&x| x as f64).sum::<f64>() as f32
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        data.iter().map(|&x| x as f64).sum::<f64>() as f32
+    }
+}
+
+/// `f32x_sum` divided by the length; NaN for an empty slice.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_mean(data: &[f32]) -> f32 {
+    (f32x_sum(data) as f64 / data.len() as f64) as f32
+}
+
+/// Logistic sigmoid using a polynomial exp approximation on the SIMD path;
+/// absolute error versus `1 / (1 + exp(-x))` is below 1e-6.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_sigmoid(data: &[f32]) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        map_lanes(data, |v| f32x4_sigmoid(v), |x| 1.0 / (1.0 + (-x).exp()))
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        data.iter().map(|x| 1.0 / (1.0 + (-x).exp())).collect()
+    }
+}
+
+/// Hyperbolic tangent computed as `2 * sigmoid(2x) - 1` on the SIMD path;
+/// absolute error versus `f32::tanh` is below 1e-5.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_tanh(data: &[f32]) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let one = f32x4_splat(1.0);
+        let two = f32x4_splat(2.0);
+        map_lanes(
+            data,
+            |v| f32x4_sub(f32x4_mul(two, f32x4_sigmoid(f32x4_mul(two, v))), one),
+            |x| x.tanh(),
+        )
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        data.iter().map(|x| x.tanh()).collect()
+    }
+}
+
+// Folds the four (value, index) lanes into one, breaking ties by lowest index.
+#[cfg(all(feature = "simd", target_arch = "wasm32"))]
+fn reduce_arg_lanes(values: [f32; 4], indices: [u32; 4], better: impl Fn(f32, f32) -> bool) -> (f32, usize) {
+    let mut best = (values[0], indices[0]);
+    for lane in 1..4 {
+        let (v, i) = (values[lane], indices[lane]);
+        if better(v, best.0) || (v == best.0 && i < best.1) {
+            best = (v, i);
+        }
+    }
+    (best.0, best.1 as usize)
+}
+
+// Shared SIMD body of argmax/argmin. Each lane keeps its own best value and the
+// index it came from; lanes only move on a strict improvement, so within a lane
+// the earliest index wins, and the final lane fold prefers the lowest index.
+#[cfg(all(feature = "simd", target_arch = "wasm32"))]
+#[target_feature(enable = "simd128")]
+unsafe fn arg_extremum(data: &[f32], find_max: bool) -> usize {
+    let len = data.len();
+    let chunks = len / 4;
+    let better = |a: f32, b: f32| if find_max { a > b } else { a < b };
+    
+    if chunks == 0 {
+        let mut best = 0;
+        for (i, &x) in data.iter().enumerate().skip(1) {
+            if better(x, data[best]) {
+                best = i;
+            }
+        }
+        return best;
+    }
+    
+    let mut best_v = v128_load(data.as_ptr() as *const v128);
+    let mut best_i = u32x4(0, 1, 2, 3);
+    let mut idx = best_i;
+    let step = u32x4_splat(4);
+    
+    for i in 1..chunks {
+        let v = v128_load(data.as_ptr().add(i * 4) as *const v128);
+        idx = i32x4_add(idx, step);
+        let mask = if find_max { f32x4_gt(v, best_v) } else { f32x4_lt(v, best_v) };
+        best_v = v128_bitselect(v, best_v, mask);
+        best_i = v128_bitselect(idx, best_i, mask);
+    }
+    
+    let mut values = [0f32; 4];
+    let mut indices = [0u32; 4];
+    v128_store(values.as_mut_ptr() as *mut v128, best_v);
+    v128_store(indices.as_mut_ptr() as *mut v128, best_i);
+    let (mut best_value, mut best) = reduce_arg_lanes(values, indices, better);
+    
+    // Tail indices are larger than any lane index, so only a strict improvement counts.
+    for (i, &x) in data.iter().enumerate().skip(chunks * 4) {
+        if better(x, best_value) {
+            best_value = x;
+            best = i;
+        }
+    }
+    
+    best
+}
+
+/// Index of the largest element. Ties resolve to the lowest index; an empty
+/// slice returns 0.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_argmax(data: &[f32]) -> usize {
+    if data.is_empty() {
+        return 0;
+    }
+    
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        arg_extremum(data, true)
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        crate::dispatch::arg_extremum_scalar(data, |x, best| x > best)
+    }
+}
+
+/// Index of the smallest element. Ties resolve to the lowest index; an empty
+/// slice returns 0.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_argmin(data: &[f32]) -> usize {
+    if data.is_empty() {
+        return 0;
+    }
+    
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        arg_extremum(data, false)
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        crate::dispatch::arg_extremum_scalar(data, |x, best| x < best)
+    }
+}
+
+/// 1-D convolution (kernel flipped, as in `numpy.convolve`). Four adjacent
+/// outputs are accumulated per SIMD register while looping over the taps,
+/// which keeps small kernels (3-15 taps) entirely in registers.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_conv1d(signal: &[f32], kernel: &[f32], mode: crate::dispatch::ConvMode) -> Vec<f32> {
+    if kernel.is_empty() {
+        return vec![];
+    }
+    
+    let padded;
+    let input = match mode {
+        crate::dispatch::ConvMode::Valid => signal,
+        crate::dispatch::ConvMode::Same => {
+            padded = crate::dispatch::pad_for_same(signal, kernel.len());
+            &padded[..]
+        }
+    };
+    if kernel.len() > input.len() {
+        return vec![];
+    }
+    
+    let taps: Vec<f32> = kernel.iter().rev().cloned().collect();
+    let out_len = input.len() - taps.len() + 1;
+    
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let mut result = Vec::with_capacity(out_len);
+        let chunks = out_len / 4;
+        
+        for i in 0..chunks {
+            let offset = i * 4;
+            let mut acc = f32x4_splat(0.0);
+            for (j, &tap) in taps.iter().enumerate() {
+                let v = v128_load(input.as_ptr().add(offset + j) as *const v128);
+                acc = f32x4_add(acc, f32x4_mul(v, f32x4_splat(tap)));
+            }
+            
+            let mut temp = [0f32; 4];
+            v128_store(temp.as_mut_ptr() as *mut v128, acc);
+            result.extend_from_slice(&temp);
+        }
+        
+        for i in (chunks * 4)..out_len {
+            let window = &input[i..i + taps.len()];
+            result.push(window.iter().zip(taps.iter()).map(|(x, k)| x * k).sum());
+        }
+        
+        result
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        input.windows(taps.len())
+            .take(out_len)
+            .map(|window| window.iter().zip(taps.iter()).map(|(x, k)| x * k).sum())
+            .collect()
+    }
+}
+
+/// Row-major `m×k` by `k×n` product. Iterates i-p-j so each step streams a
+/// contiguous row of `b` into a contiguous row of the output, four columns
+/// per SIMD register.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_matmul(a: &[f32], b: &[f32], m: usize, k: usize, n: usize) -> Vec<f32> {
+    assert_eq!(a.len(), m * k);
+    assert_eq!(b.len(), k * n);
+    let mut result = vec![0f32; m * n];
+    
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let chunks = n / 4;
+        
+        for i in 0..m {
+            let out_row = &mut result[i * n..(i + 1) * n];
+            for p in 0..k {
+                let a_ip = a[i * k + p];
+                let a_vec = f32x4_splat(a_ip);
+                let b_row = &b[p * n..(p + 1) * n];
+                
+                for c in 0..chunks {
+                    let offset = c * 4;
+                    let b_vec = v128_load(b_row.as_ptr().add(offset) as *const v128);
+                    let out_ptr = out_row.as_mut_ptr().add(offset) as *mut v128;
+                    let acc = f32x4_add(v128_load(out_ptr), f32x4_mul(a_vec, b_vec));
+                    v128_store(out_ptr, acc);
+                }
+                
+                for j in (chunks * 4)..n {
+                    out_row[j] += a_ip * b_row[j];
+                }
+            }
+        }
+        
+        result
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        for i in 0..m {
+            for p in 0..k {
+                let a_ip = a[i * k + p];
+                for j in 0..n {
+                    result[i * n + j] += a_ip * b[p * n + j];
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Autocorrelation of the mean-removed signal at lags `0..=max_lag` (capped
+/// at `len - 1`), each lag a SIMD dot product of the signal with a shifted
+/// copy of itself. Normalized by the lag-0 value, so lag 0 is 1 and every
+/// lag lies in [-1, 1]; longer lags sum fewer terms and shrink toward 0. A
+/// constant signal has no variance to normalize by and yields all zeros.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_autocorr(data: &[f32], max_lag: usize) -> Vec<f32> {
+    if data.is_empty() {
+        return vec![];
+    }
+    let len = data.len();
+    let max_lag = max_lag.min(len - 1);
+    let mean = data.iter().sum::<f32>() / len as f32;
+    let centered = f32x_map_mul_add(data, 1.0, -mean);
+
+    let energy = f32x_dot_product(&centered, &centered);
+    if energy == 0.0 || crate::dispatch::is_constant(data) {
+        return vec![0.0; max_lag + 1];
+    }
+    (0..=max_lag)
+        .map(|lag| f32x_dot_product(&centered[lag..], &centered[..len - lag]) / energy)
+        .collect()
+}
+
+/// Symmetric per-tensor int8 quantization: `scale = max(|x|) / 127` and
+/// `q = round(x / scale)` (ties to even), so values map into `-127..=127`.
+/// Dequantize with `q * scale`; each value then lies within `scale / 2` of
+/// the original. An all-zero input has scale 0 and quantizes to zeros.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_quantize_i8(data: &[f32]) -> (Vec<i8>, f32) {
+    let max_abs = data.iter().fold(0.0f32, |m, x| m.max(x.abs()));
+    let scale = max_abs / 127.0;
+    if scale == 0.0 {
+        return (vec![0; data.len()], 0.0);
+    }
+    let inv_scale = 1.0 / scale;
+
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let len = data.len();
+        let mut result = Vec::with_capacity(len);
+        let inv_vec = f32x4_splat(inv_scale);
+        let lo = f32x4_splat(-127.0);
+        let hi = f32x4_splat(127.0);
+        let chunks = len / 4;
+
+        for i in 0..chunks {
+            let v = v128_load(data.as_ptr().add(i * 4) as *const v128);
+            let scaled = f32x4_nearest(f32x4_mul(v, inv_vec));
+            let clamped = f32x4_pmin(f32x4_pmax(scaled, lo), hi);
+            let q = i32x4_trunc_sat_f32x4(clamped);
+            result.extend_from_slice(&[
+                i32x4_extract_lane::<0>(q) as i8,
+                i32x4_extract_lane::<1>(q) as i8,
+                i32x4_extract_lane::<2>(q) as i8,
+                i32x4_extract_lane::<3>(q) as i8,
+            ]);
+        }
+
+        for x in &data[chunks * 4..] {
+            result.push((x * inv_scale).round_ties_even().clamp(-127.0, 127.0) as i8);
+        }
+
+        (result, scale)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let values = data
+            .iter()
+            .map(|x| (x * inv_scale).round_ties_even().clamp(-127.0, 127.0) as i8)
+            .collect();
+        (values, scale)
+    }
+}
+
+// Lane-wise binary op over two equal-length slices, scalar op for the tail.
+#[cfg(all(feature = "simd", target_arch = "wasm32"))]
+#[target_feature(enable = "simd128")]
+unsafe fn zip_lanes(
+    a: &[f32],
+    b: &[f32],
+    vector_op: impl Fn(v128, v128) -> v128,
+    scalar_op: impl Fn(f32, f32) -> f32,
+) -> Vec<f32> {
+    assert_eq!(a.len(), b.len());
+    let len = a.len();
+    let mut result = Vec::with_capacity(len);
+    let chunks = len / 4;
+    
+    for i in 0..chunks {
+        let offset = i * 4;
+        let a_vec = v128_load(a.as_ptr().add(offset) as *const v128);
+        let b_vec = v128_load(b.as_ptr().add(offset) as *const v128);
+        let mut temp = [0f32; 4];
+        v128_store(temp.as_mut_ptr() as *mut v128, vector_op(a_vec, b_vec));
+        result.extend_from_slice(&temp);
+    }
+    
+    for (x, y) in a[chunks * 4..].iter().zip(&b[chunks * 4..]) {
+        result.push(scalar_op(*x, *y));
+    }
+    
+    result
+}
+
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_vector_sub(a: &[f32], b: &[f32]) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        zip_lanes(a, b, |x, y| f32x4_sub(x, y), |x, y| x - y)
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        a.iter().zip(b.iter()).map(|(x, y)| x - y).collect()
+    }
+}
+
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_vector_mul(a: &[f32], b: &[f32]) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        zip_lanes(a, b, |x, y| f32x4_mul(x, y), |x, y| x * y)
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        a.iter().zip(b.iter()).map(|(x, y)| x * y).collect()
+    }
+}
+
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_vector_div(a: &[f32], b: &[f32]) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        zip_lanes(a, b, |x, y| f32x4_div(x, y), |x, y| x / y)
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        a.iter().zip(b.iter()).map(|(x, y)| x / y).collect()
+    }
+}
+
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_vector_min(a: &[f32], b: &[f32]) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        // `pmin` already returns `x` when `y` is NaN; take `y` where `x` is.
+        zip_lanes(a, b, |x, y| v128_bitselect(y, f32x4_pmin(x, y), f32x4_ne(x, x)), |x, y| x.min(y))
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        a.iter().zip(b.iter()).map(|(x, y)| x.min(*y)).collect()
+    }
+}
+
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f32x_vector_max(a: &[f32], b: &[f32]) -> Vec<f32> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        zip_lanes(a, b, |x, y| v128_bitselect(y, f32x4_pmax(x, y), f32x4_ne(x, x)), |x, y| x.max(y))
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        a.iter().zip(b.iter()).map(|(x, y)| x.max(*y)).collect()
+    }
+}
+
+// f64 kernels process two lanes per v128, so they gain less than the f32
+// ones but still halve the instruction count of the scalar loops.
+
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f64x_square(data: &[f64]) -> Vec<f64> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let len = data.len();
+        let mut result = Vec::with_capacity(len);
+        let chunks = len / 2;
+        
+        for i in 0..chunks {
+            let v = v128_load(data.as_ptr().add(i * 2) as *const v128);
+            let mut temp = [0f64; 2];
+            v128_store(temp.as_mut_ptr() as *mut v128, f64x2_mul(v, v));
+            result.extend_from_slice(&temp);
+        }
+        
+        for x in &data[chunks * 2..] {
+            result.push(x * x);
+        }
+        
+        result
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        data.iter().map(|x| x * x).collect()
+    }
+}
+
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f64x_cumsum(data: &[f64]) -> Vec<f64> {
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let len = data.len();
+        let mut result = Vec::with_capacity(len);
+        
+        let chunks = len / 2;
+        let zero = f64x2_splat(0.0);
+        let mut carry = 0.0f64;
+        
+        // Per-pair prefix sum in one shift-and-add, then the running total of
+        // the previous pairs as a carry.
+        for i in 0..chunks {
+            let mut v = v128_load(data.as_ptr().add(i * 2) as *const v128);
+            v = f64x2_add(v, i64x2_shuffle::<0, 2>(zero, v));
+            v = f64x2_add(v, f64x2_splat(carry));
+            
+            let mut temp = [0f64; 2];
+            v128_store(temp.as_mut_ptr() as *mut v128, v);
+            result.extend_from_slice(&temp);
+            carry = temp[1];
+        }
+        
+        for x in &data[chunks * 2..] {
+            carry += x;
+            result.push(carry);
+        }
+        
+        result
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let mut sum = 0.0f64;
+        data.iter().map(|x| {
+            sum += x;
+            sum
+        }).collect()
+    }
+}
+
+/// `f32x_normalize` for f64 data.
+#[cfg(feature = "simd")]
+#[cfg_attr(target_arch = "wasm32", target_feature(enable = "simd128"))]
+pub fn f64x_normalize(data: &[f64], epsilon: f64) -> Vec<f64> {
+    if crate::dispatch::is_constant(data) {
+        return vec![0.0; data.len()];
+    }
+    
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        let len = data.len();
+        let chunks = len / 2;
+        let mut temp = [0f64; 2];
+        
+        let mut sum_vec = f64x2_splat(0.0);
+        for i in 0..chunks {
+            sum_vec = f64x2_add(sum_vec, v128_load(data.as_ptr().add(i * 2) as *const v128));
+        }
+        v128_store(temp.as_mut_ptr() as *mut v128, sum_vec);
+        let mut sum = temp[0] + temp[1];
+        for x in &data[chunks * 2..] {
+            sum += x;
+        }
+        let mean = sum / len as f64;
+        
+        let mean_vec = f64x2_splat(mean);
+        let mut sq_vec = f64x2_splat(0.0);
+        for i in 0..chunks {
+            let d = f64x2_sub(v128_load(data.as_ptr().add(i * 2) as *const v128), mean_vec);
+            sq_vec = f64x2_add(sq_vec, f64x2_mul(d, d));
+        }
+        v128_store(temp.as_mut_ptr() as *mut v128, sq_vec);
+        let mut sq_sum = temp[0] + temp[1];
+        for x in &data[chunks * 2..] {
+            sq_sum += (x - mean) * (x - mean);
+        }
+        let std_dev = (sq_sum / len as f64).sqrt() + epsilon;
+        
+        if std_dev == 0.0 {
+            return vec![0.0; len];
+        }
+        
+        let inv_std_vec = f64x2_splat(1.0 / std_dev);
+        let mut result = Vec::with_capacity(len);
+        for i in 0..chunks {
+            let v = v128_load(data.as_ptr().add(i * 2) as *const v128);
+            v128_store(temp.as_mut_ptr() as *mut v128, f64x2_mul(f64x2_sub(v, mean_vec), inv_std_vec));
+            result.extend_from_slice(&temp);
+        }
+        for x in &data[chunks * 2..] {
+            result.push((x - mean) / std_dev);
+        }
+        
+        result
+    }
+    
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let mean = data.iter().sum::<f64>() / data.len() as f64;
+        let variance = data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / data.len() as f64;
+        let std_dev = variance.sqrt() + epsilon;
+        if std_dev == 0.0 {
+            return vec![0.0; data.len()];
+        }
+        data.iter().map(|x| (x - mean) / std_dev).collect()
+    }
+}
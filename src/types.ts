@@ -25,6 +25,8 @@ export interface Frame {
 export type Payload = 
   | { type: 'data'; data: Uint8Array }
   | { type: 'float32'; data: Float32Array }
+  | { type: 'float64'; data: Float64Array }
+  | { type: 'int32'; data: Int32Array }
   | { type: 'control'; message: ControlMessage }
   | { type: 'error'; code: number; message: string; recoverable: boolean };
 
@@ -35,7 +37,10 @@ export type ControlMessage =
   | { type: 'resume' }
   | { type: 'complete' }
   | { type: 'subscribe'; streamId: string }
-  | { type: 'unsubscribe'; streamId: string };
+  | { type: 'unsubscribe'; streamId: string }
+  | { type: 'heartbeat'; timestamp: number }
+  | { type: 'flush' }
+  | { type: 'seek'; sequence: number };
 
 export interface CreditController {
   consumeCredit(amount: number): boolean;
@@ -0,0 +1,500 @@
+use nagare::backpressure::{
+    AdaptiveBackpressure, CircuitBreaker, CircuitState, CreditManager, CreditStats, LeakyBucket, MultiStreamCreditManager,
+    PidBackpressure, PressureSignal, TokenBucket, TypedCreditManager, WeightedFairScheduler, WindowedRateLimiter,
+};
+use futures::FutureExt;
+use std::collections::HashMap;
+
+#[test]
+fn token_bucket_allows_a_burst_then_throttles() {
+    let mut bucket = TokenBucket::new(5.0, 10.0);
+    for _ in 0..5 {
+        assert!(bucket.try_acquire(0, 1.0));
+    }
+    assert!(!bucket.try_acquire(0, 1.0));
+    assert_eq!(bucket.time_until_available(1.0), 100);
+    assert_eq!(bucket.time_until_available(6.0), u64::MAX);
+}
+
+#[test]
+fn token_bucket_refills_at_a_steady_rate() {
+    let mut bucket = TokenBucket::new(2.0, 10.0);
+    assert!(bucket.try_acquire(0, 2.0));
+
+    // One token every 100 ms, never more than capacity.
+    let granted = (1..=10).filter(|i| bucket.try_acquire(i * 100, 1.0)).count();
+    assert_eq!(granted, 10);
+    assert!(!bucket.try_acquire(1000, 1.0));
+    assert!((bucket.available_tokens(1050) - 0.5).abs() < 1e-9);
+    assert_eq!(bucket.available_tokens(60_000), 2.0);
+}
+
+// Plant for the controller tests: latency grows linearly with the send rate
+// and hits the 50 ms target at 100 msg/s.
+fn simulated_latency(rate: f64) -> f64 {
+    50.0 * rate / 100.0
+}
+
+// First step after which the rate stays within 2% of the ideal 100 msg/s.
+fn settling_step(mut step: impl FnMut(f64) -> f64, initial_rate: f64) -> usize {
+    let mut rate = initial_rate;
+    let rates: Vec<f64> = (0..200).map(|_| {
+        rate = step(simulated_latency(rate));
+        rate
+    }).collect();
+    (0..rates.len())
+        .find(|&i| rates[i..].iter().all(|r| (r - 100.0).abs() < 2.0))
+        .expect("controller never settled")
+}
+
+#[test]
+fn pid_settles_faster_than_proportional() {
+    for initial_rate in [20.0, 300.0] {
+        let mut p = AdaptiveBackpressure::new(initial_rate, 50.0, 1.0, 1000.0);
+        let p_steps = settling_step(|latency| { p.update(latency); p.get_rate() }, initial_rate);
+
+        let mut pid = PidBackpressure::new(initial_rate, 50.0, 1.0, 1000.0, 0.03, 0.05, 0.005);
+        let pid_steps = settling_step(|latency| { pid.update(latency); pid.get_rate() }, initial_rate);
+
+        assert!(pid_steps < p_steps, "pid {} vs p {} steps from {}", pid_steps, p_steps, initial_rate);
+    }
+}
+
+#[test]
+fn pid_integral_does_not_wind_up() {
+    let mut pid = PidBackpressure::new(100.0, 50.0, 1.0, 1000.0, 0.0, 1.0, 0.0)
+        .with_integral_limit(2.0);
+    for _ in 0..1000 {
+        pid.update(0.0);
+    }
+    assert_eq!(pid.get_rate(), 1000.0);
+
+    // With a clamped integral a handful of high-latency samples pull it back.
+    for _ in 0..4 {
+        pid.update(100.0);
+    }
+    assert!(pid.get_rate() < 1000.0);
+    assert_eq!(pid.get_delay_ms(), (1000.0 / pid.get_rate()) as u64);
+}
+
+#[test]
+fn credit_refill_catches_up_on_missed_ticks() {
+    let mut credits = CreditManager::with_auto_refill(0, 5, 100);
+    assert_eq!(credits.tick(1_000), 0);
+    assert_eq!(credits.tick(1_099), 0);
+    assert_eq!(credits.tick(1_100), 5);
+
+    // Ticks at 1200 and 1300 were missed; the remainder carries over.
+    assert_eq!(credits.tick(1_350), 10);
+    assert_eq!(credits.tick(1_400), 5);
+    assert_eq!(credits.available(), 20);
+}
+
+#[test]
+fn credit_refill_respects_the_cap() {
+    let mut credits = CreditManager::with_auto_refill(8, 5, 100).with_refill_cap(10);
+    credits.tick(0);
+    assert_eq!(credits.tick(1_000), 2);
+    assert_eq!(credits.available(), 10);
+
+    credits.add(5);
+    assert_eq!(credits.tick(2_000), 0);
+    assert_eq!(credits.available(), 15);
+    assert!(credits.consume(15));
+}
+
+#[test]
+fn credits_are_distributed_by_weight() {
+    let mut manager = MultiStreamCreditManager::new(0);
+    manager.register_stream_weighted("video".to_string(), 3);
+    manager.register_stream_weighted("audio".to_string(), 2);
+    manager.register_stream("chat".to_string(), None);
+    assert_eq!(manager.total_weight(), 6);
+
+    let grants = manager.distribute(100);
+    assert_eq!(grants.values().sum::<u32>(), 100);
+    // Floors of 50 / 33.3 / 16.7 leave one credit, which goes to the heaviest stream.
+    assert_eq!(grants["video"], 51);
+    assert_eq!(grants["audio"], 33);
+    assert_eq!(grants["chat"], 16);
+    assert_eq!(manager.available_credits("video"), Some(51));
+}
+
+#[test]
+fn unregistered_streams_receive_nothing() {
+    let mut manager = MultiStreamCreditManager::new(0);
+    manager.register_stream_weighted("a".to_string(), 1);
+    manager.register_stream_weighted("b".to_string(), 1);
+    manager.unregister_stream("b");
+
+    let grants = manager.distribute(7);
+    assert_eq!(grants.len(), 1);
+    assert_eq!(manager.available_credits("a"), Some(7));
+    assert_eq!(manager.available_credits("b"), None);
+}
+
+#[test]
+fn borrow_moves_credits_only_when_available() {
+    let mut manager = MultiStreamCreditManager::new(10);
+    manager.register_stream("a".to_string(), None);
+    manager.register_stream("b".to_string(), Some(0));
+
+    assert!(manager.borrow("a", "b", 4));
+    assert_eq!(manager.available_credits("a"), Some(6));
+    assert_eq!(manager.available_credits("b"), Some(4));
+    assert!(!manager.borrow("a", "b", 7));
+    assert!(!manager.borrow("a", "missing", 1));
+    assert_eq!(manager.total_available_credits(), 10);
+}
+
+#[test]
+fn rebalance_unblocks_exhausted_streams() {
+    let mut manager = MultiStreamCreditManager::new(10);
+    manager.register_stream("idle".to_string(), Some(50));
+    manager.register_stream("busy".to_string(), None);
+    while manager.consume("busy", 1) {}
+    assert!(manager.is_stream_exhausted("busy"));
+
+    assert_eq!(manager.rebalance(), 10);
+    assert!(manager.consume("busy", 1));
+    assert_eq!(manager.available_credits("idle"), Some(40));
+    assert_eq!(manager.total_available_credits(), 49);
+    assert_eq!(manager.rebalance(), 0);
+}
+
+fn tripped_breaker() -> CircuitBreaker {
+    let mut breaker = CircuitBreaker::new(3, 1_000, 2);
+    for t in 0..3 {
+        assert!(breaker.allow_request(t));
+        breaker.record_failure();
+    }
+    assert_eq!(breaker.state(), CircuitState::Open);
+    breaker
+}
+
+#[test]
+fn circuit_opens_after_consecutive_failures() {
+    let mut breaker = CircuitBreaker::new(3, 1_000, 1);
+    breaker.record_failure();
+    breaker.record_failure();
+    breaker.record_success();
+    breaker.record_failure();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+
+    let mut breaker = tripped_breaker();
+    assert!(!breaker.allow_request(500));
+    assert!(!breaker.allow_request(1_001));
+}
+
+#[test]
+fn half_open_probes_close_the_circuit() {
+    let mut breaker = tripped_breaker();
+    assert!(breaker.allow_request(1_002));
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    assert!(breaker.allow_request(1_003));
+    assert!(!breaker.allow_request(1_004), "only two probes are admitted");
+
+    breaker.record_success();
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    breaker.record_success();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert!(breaker.allow_request(1_005));
+}
+
+#[test]
+fn failed_probe_reopens_the_circuit() {
+    let mut breaker = tripped_breaker();
+    assert!(breaker.allow_request(1_500));
+    breaker.record_failure();
+    assert_eq!(breaker.state(), CircuitState::Open);
+    assert!(!breaker.allow_request(2_000));
+    assert!(breaker.allow_request(2_500));
+}
+
+#[test]
+fn open_circuit_throttles_adaptive_backpressure() {
+    let backpressure = AdaptiveBackpressure::new(100.0, 50.0, 1.0, 1000.0)
+        .with_circuit_breaker(tripped_breaker());
+    assert!(backpressure.should_throttle(1.0));
+
+    let breaker = backpressure.circuit_breaker().unwrap();
+    assert!(breaker.lock().unwrap().allow_request(5_000));
+    assert!(!backpressure.should_throttle(1.0));
+}
+
+#[test]
+fn watermarks_pause_and_resume_with_hysteresis() {
+    let mut credits = CreditManager::new(10).with_watermarks(2, 8);
+    assert_eq!(credits.pressure_signal(), PressureSignal::None);
+
+    assert!(credits.consume(7));
+    assert_eq!(credits.pressure_signal(), PressureSignal::None);
+    assert!(credits.consume(1));
+    assert_eq!(credits.pressure_signal(), PressureSignal::Pause);
+    assert_eq!(credits.pressure_signal(), PressureSignal::None);
+    assert!(credits.is_paused());
+
+    // Recovering into the band does not resume.
+    credits.add(5);
+    assert_eq!(credits.pressure_signal(), PressureSignal::None);
+    credits.add(1);
+    assert_eq!(credits.pressure_signal(), PressureSignal::Resume);
+    assert!(!credits.is_paused());
+
+    // Dipping into the band does not pause again.
+    assert!(credits.consume(3));
+    assert_eq!(credits.pressure_signal(), PressureSignal::None);
+}
+
+#[test]
+fn consume_async_blocks_until_add_grants_credits() {
+    let mut credits = CreditManager::new(2);
+    let waiter = credits.clone();
+    let handle = std::thread::spawn(move || futures::executor::block_on(waiter.consume_async(5)));
+
+    while credits.pending_waiters() == 0 {
+        std::thread::yield_now();
+    }
+    assert_eq!(credits.available(), 2);
+    credits.add(3);
+    assert!(handle.join().unwrap());
+    assert_eq!(credits.available(), 0);
+}
+
+#[test]
+fn consume_async_serves_waiters_in_order() {
+    let mut credits = CreditManager::new(1);
+    let mut first = Box::pin(credits.consume_async(3));
+    let mut second = Box::pin(credits.consume_async(1));
+
+    // The head waiter holds back the smaller request behind it.
+    assert_eq!(first.as_mut().now_or_never(), None);
+    assert_eq!(second.as_mut().now_or_never(), None);
+
+    credits.add(2);
+    assert_eq!(first.as_mut().now_or_never(), Some(true));
+    assert_eq!(second.as_mut().now_or_never(), None);
+    assert_eq!(credits.available(), 0);
+
+    credits.add(1);
+    assert_eq!(second.as_mut().now_or_never(), Some(true));
+    assert_eq!(credits.pending_waiters(), 0);
+}
+
+#[test]
+fn dropped_consume_async_gives_up_its_place() {
+    let mut credits = CreditManager::new(0);
+    let abandoned = credits.consume_async(10);
+    let mut next = Box::pin(credits.consume_async(1));
+    drop(abandoned);
+
+    credits.add(1);
+    assert_eq!(next.as_mut().now_or_never(), Some(true));
+    assert_eq!(credits.available(), 0);
+}
+
+#[test]
+fn stats_count_failures_only_for_insufficient_credits() {
+    let mut credits = CreditManager::new(5);
+    assert!(credits.consume(3));
+    assert!(credits.consume(2));
+    assert!(!credits.consume(1));
+    assert!(credits.consume(0));
+    credits.add(4);
+    assert!(!credits.consume(5));
+    assert!(credits.consume(4));
+
+    assert_eq!(
+        credits.stats(),
+        CreditStats {
+            total_consumed: 9,
+            total_added: 4,
+            consume_failures: 2,
+            exhaustion_events: 2,
+        }
+    );
+}
+
+#[test]
+fn consume_up_to_takes_what_is_available() {
+    let mut credits = CreditManager::new(5);
+    assert_eq!(credits.consume_up_to(3), 3);
+    assert_eq!(credits.consume_up_to(10), 2);
+    assert!(credits.is_exhausted());
+    assert_eq!(credits.consume_up_to(1), 0);
+
+    credits.add(4);
+    assert_eq!(credits.consume_up_to(4), 4);
+    assert!(credits.is_exhausted());
+    assert_eq!(credits.stats().consume_failures, 0);
+}
+
+#[test]
+fn consume_all_counts_and_drain_discards() {
+    let mut credits = CreditManager::new(6);
+    assert_eq!(credits.consume_all(), 6);
+    assert_eq!(credits.consume_all(), 0);
+
+    credits.add(4);
+    assert_eq!(credits.drain(), 4);
+    assert_eq!(credits.available(), 0);
+    assert_eq!(credits.drain(), 0);
+    assert_eq!(credits.stats().total_consumed, 6);
+}
+
+#[test]
+fn stats_per_stream_tracks_each_stream() {
+    let mut manager = MultiStreamCreditManager::new(2);
+    manager.register_stream("a".to_string(), None);
+    manager.register_stream("b".to_string(), None);
+    assert!(manager.consume("a", 2));
+    assert!(!manager.consume("a", 1));
+
+    let stats = manager.stats_per_stream();
+    assert_eq!(stats["a"].consume_failures, 1);
+    assert_eq!(stats["a"].exhaustion_events, 1);
+    assert_eq!(stats["b"], CreditStats::default());
+}
+
+#[test]
+fn leaky_bucket_accepts_submissions_at_its_rate() {
+    let mut bucket = LeakyBucket::new(10.0, 1.0);
+    for i in 0..50 {
+        assert_eq!(bucket.submit(i * 100), Ok(()));
+    }
+    assert_eq!(bucket.submit(4_950), Err(50));
+}
+
+#[test]
+fn leaky_bucket_bursts_wait_longer_the_larger_they_are() {
+    let mut bucket = LeakyBucket::new(10.0, 4.0);
+    assert_eq!(bucket.submit_burst(0, 4), Ok(()));
+
+    assert_eq!(bucket.submit(0), Err(100));
+    assert_eq!(bucket.submit_burst(0, 2), Err(200));
+    assert_eq!(bucket.submit_burst(0, 3), Err(300));
+    assert_eq!(bucket.submit_burst(0, 5), Err(u64::MAX));
+
+    // Waiting out the reported delay is enough.
+    assert_eq!(bucket.submit_burst(300, 3), Ok(()));
+    assert_eq!(bucket.level(300), 4.0);
+}
+
+#[test]
+fn rate_limiter_rejects_a_zero_window() {
+    assert!(WindowedRateLimiter::new(0, 3).is_err());
+    let limiter = WindowedRateLimiter::new(1_000, 3).unwrap();
+    assert_eq!(limiter.current_rate(0), 0.0);
+}
+
+#[test]
+fn rate_limiter_tolerates_out_of_order_timestamps() {
+    let mut limiter = WindowedRateLimiter::new(1_000, 2).unwrap();
+    assert!(limiter.try_acquire(5_000));
+    // Earlier than the latest event: counted at 5_000, not in an older window.
+    assert!(limiter.try_acquire(1_000));
+    assert!(!limiter.try_acquire(2_000));
+    assert_eq!(limiter.current_rate(0), 2.0);
+
+    assert!(!limiter.try_acquire(5_999));
+    assert!(limiter.try_acquire(6_000));
+    assert_eq!(limiter.current_rate(6_000), 1.0);
+}
+
+fn typed_credits() -> TypedCreditManager {
+    let mut credits = TypedCreditManager::new();
+    credits.register("bytes", 1024);
+    credits.register("messages", 2);
+    credits
+}
+
+#[test]
+fn typed_credits_consume_all_resources_together() {
+    let mut credits = typed_credits();
+    assert!(credits.consume(&[("bytes", 512), ("messages", 1)]));
+    assert_eq!(credits.available("bytes"), Some(512));
+    assert_eq!(credits.available("messages"), Some(1));
+    assert_eq!(credits.available("frames"), None);
+}
+
+#[test]
+fn typed_credits_consume_nothing_when_any_resource_is_short() {
+    let mut credits = typed_credits();
+    assert!(!credits.consume(&[("bytes", 100), ("messages", 3)]));
+    assert!(!credits.consume(&[("bytes", 100), ("frames", 1)]));
+    // Repeated labels are checked against their combined amount.
+    assert!(!credits.consume(&[("messages", 1), ("bytes", 1), ("messages", 2)]));
+    assert_eq!(credits.available("bytes"), Some(1024));
+    assert_eq!(credits.available("messages"), Some(2));
+
+    credits.add("messages", 1);
+    assert!(credits.consume(&[("messages", 1), ("bytes", 1), ("messages", 2)]));
+    assert_eq!(credits.available("messages"), Some(0));
+}
+
+#[test]
+fn fair_scheduler_serves_in_proportion_to_weight() {
+    let mut scheduler = WeightedFairScheduler::new(1);
+    scheduler.register("bulk", 3);
+    scheduler.register("chat", 1);
+    scheduler.register("idle", 0);
+
+    let mut served = HashMap::new();
+    let mut since_chat = 0;
+    for _ in 0..4000 {
+        let id = scheduler.next_stream().unwrap();
+        scheduler.record_served(&id, 1);
+        since_chat = if id == "chat" { 0 } else { since_chat + 1 };
+        assert!(since_chat <= 3, "chat starved");
+        *served.entry(id).or_insert(0u32) += 1;
+    }
+    assert_eq!(served.get("idle"), None);
+    let ratio = f64::from(served["bulk"]) / f64::from(served["chat"]);
+    assert!((ratio - 3.0).abs() < 0.05, "ratio {ratio}");
+}
+
+#[test]
+fn fair_scheduler_starts_with_the_first_stream() {
+    let mut manager = MultiStreamCreditManager::new(10);
+    for id in ["c", "a", "b"] {
+        manager.register_stream_weighted(id.to_string(), 1);
+    }
+    let mut scheduler = manager.fair_scheduler(1);
+
+    let mut order = Vec::new();
+    for _ in 0..6 {
+        let id = scheduler.next_stream().unwrap();
+        scheduler.record_served(&id, 1);
+        order.push(id);
+    }
+    assert_eq!(order, ["a", "b", "c", "a", "b", "c"]);
+
+    // Unregistering the stream being served passes the turn to the next one.
+    assert_eq!(scheduler.next_stream().as_deref(), Some("a"));
+    scheduler.unregister("a");
+    assert_eq!(scheduler.next_stream().as_deref(), Some("b"));
+}
+
+#[test]
+fn fair_scheduler_charges_cost_against_the_budget() {
+    let mut manager = MultiStreamCreditManager::new(10);
+    manager.register_stream_weighted("a".to_string(), 1);
+    manager.register_stream_weighted("b".to_string(), 1);
+    let mut scheduler = manager.fair_scheduler(100);
+
+    // "a" sends 300-byte messages, "b" 100-byte ones: equal weights share
+    // bytes, not messages.
+    let mut bytes = HashMap::new();
+    for _ in 0..1000 {
+        let id = scheduler.next_stream().unwrap();
+        let cost = if id == "a" { 300 } else { 100 };
+        scheduler.record_served(&id, cost);
+        *bytes.entry(id).or_insert(0u32) += cost;
+    }
+    let ratio = f64::from(bytes["a"]) / f64::from(bytes["b"]);
+    assert!((ratio - 1.0).abs() < 0.05, "ratio {ratio}");
+
+    scheduler.unregister("a");
+    assert_eq!(scheduler.next_stream().as_deref(), Some("b"));
+    assert_eq!(WeightedFairScheduler::new(1).next_stream(), None);
+}
@@ -0,0 +1,42 @@
+use nagare::operators::{batch_process, fold, par_batch_process, par_fold, try_fold};
+
+#[test]
+fn par_batch_process_preserves_order() {
+    let input: Vec<u32> = (0..10_000).collect();
+    let square = |chunk: &[u32]| chunk.iter().map(|x| x * 2).collect::<Vec<_>>();
+
+    let sequential = batch_process(input.clone(), 64, square);
+    let parallel = par_batch_process(input, 64, square);
+    assert_eq!(parallel, sequential);
+    assert_eq!(parallel[1234], 2468);
+}
+
+#[test]
+fn par_fold_matches_sequential_fold() {
+    let input: Vec<u64> = (1..=10_000).collect();
+    let sum = |acc: u64, x: &u64| acc + x;
+
+    assert_eq!(fold(&input, 0, sum), 50_005_000);
+    assert_eq!(par_fold(&input, 0, sum, |a, b| a + b), 50_005_000);
+
+    // Max is associative with identity 0 for unsigned values.
+    let max = par_fold(&input, 0, |acc, x| acc.max(*x), u64::max);
+    assert_eq!(max, 10_000);
+
+    // Grouping partial results differently must not change the total.
+    let (left, right) = input.split_at(3_333);
+    assert_eq!(fold(left, 0, sum) + fold(right, 0, sum), fold(&input, 0, sum));
+}
+
+#[test]
+fn try_fold_short_circuits_on_error() {
+    let mut steps = 0;
+    let result = try_fold(&[1u32, 2, 3, 4], 0u32, |acc, x| {
+        steps += 1;
+        if *x == 3 { Err(format!("rejected {}", x)) } else { Ok(acc + x) }
+    });
+    assert_eq!(result, Err("rejected 3".to_string()));
+    assert_eq!(steps, 3);
+
+    assert_eq!(try_fold(&[1u32, 2, 3], 0u32, |acc, x| acc.checked_add(*x).ok_or(())), Ok(6));
+}
@@ -0,0 +1,232 @@
+#![cfg(all(target_arch = "wasm32", feature = "byob"))]
+
+use nagare::byob::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+#[wasm_bindgen_test]
+fn float32_view_aliases_the_slice() {
+    let data = vec![1.0f32, 2.0, 3.0];
+    let view = create_float32_view(&data);
+    assert_eq!(view.to_vec(), data);
+}
+
+#[wasm_bindgen_test]
+fn float64_and_int32_views_alias_the_slice() {
+    let doubles = vec![1.5f64, -2.25, 3.0];
+    assert_eq!(create_float64_view(&doubles).to_vec(), doubles);
+    let ints = vec![i32::MIN, 0, 7, i32::MAX];
+    assert_eq!(create_int32_view(&ints).to_vec(), ints);
+}
+
+#[wasm_bindgen_test]
+fn copy_survives_memory_growth() {
+    let data = vec![1.0f32, 2.0, 3.0];
+    let copy = copy_float32_out(&data);
+    let checked = create_float32_view_checked(&data);
+    assert!(checked.is_valid());
+
+    wasm_bindgen::memory()
+        .unchecked_into::<js_sys::WebAssembly::Memory>()
+        .grow(1);
+
+    assert!(!checked.is_valid());
+    assert!(checked.get().is_err());
+    assert_eq!(copy.to_vec(), data);
+}
+
+fn byte_stream(chunk: &[u8]) -> web_sys::ReadableStream {
+    let source = js_sys::Object::new();
+    js_sys::Reflect::set(&source, &"type".into(), &"bytes".into()).unwrap();
+    let start = js_sys::Function::new_with_args(
+        "c",
+        &format!("c.enqueue(new Uint8Array({:?})); c.close();", chunk),
+    );
+    js_sys::Reflect::set(&source, &"start".into(), &start).unwrap();
+    web_sys::ReadableStream::new_with_underlying_source(&source).unwrap()
+}
+
+fn field(result: &wasm_bindgen::JsValue, name: &str) -> wasm_bindgen::JsValue {
+    js_sys::Reflect::get(result, &name.into()).unwrap()
+}
+
+#[wasm_bindgen_test]
+async fn read_into_fills_the_callers_buffer() {
+    let mut reader = BYOBStreamReader::new(byte_stream(&[1, 2, 3]), 8).unwrap();
+
+    let result = reader.read_into(js_sys::Uint8Array::new_with_length(8)).await.unwrap();
+    assert_eq!(field(&result, "done"), false);
+    let value: js_sys::Uint8Array = field(&result, "value").unchecked_into();
+    assert_eq!(value.to_vec(), vec![1, 2, 3]);
+    assert_eq!(value.buffer().byte_length(), 8);
+
+    let result = reader.read_with_reusable_buffer().await.unwrap();
+    assert_eq!(field(&result, "done"), true);
+}
+
+#[wasm_bindgen_test]
+async fn read_into_rejects_default_streams() {
+    let stream = web_sys::ReadableStream::new().unwrap();
+    let mut reader = BYOBStreamReader::new(stream, 8).unwrap();
+    assert!(reader.read_into(js_sys::Uint8Array::new_with_length(8)).await.is_err());
+}
+
+fn chunked_byte_stream(chunks: &[&[u8]]) -> web_sys::ReadableStream {
+    let source = js_sys::Object::new();
+    js_sys::Reflect::set(&source, &"type".into(), &"bytes".into()).unwrap();
+    let body: String = chunks
+        .iter()
+        .map(|chunk| format!("c.enqueue(new Uint8Array({:?}));", chunk))
+        .collect();
+    let start = js_sys::Function::new_with_args("c", &format!("{} c.close();", body));
+    js_sys::Reflect::set(&source, &"start".into(), &start).unwrap();
+    web_sys::ReadableStream::new_with_underlying_source(&source).unwrap()
+}
+
+#[wasm_bindgen_test]
+async fn read_exact_spans_chunks() {
+    let stream = chunked_byte_stream(&[&[1, 2], &[3], &[4, 5, 6]]);
+    let mut reader = BYOBStreamReader::new(stream, 2).unwrap();
+
+    let first = reader.read_exact(4).await.unwrap();
+    assert_eq!(first.to_vec(), vec![1, 2, 3, 4]);
+    let second = reader.read_exact(2).await.unwrap();
+    assert_eq!(second.to_vec(), vec![5, 6]);
+    assert_eq!(first.to_vec(), vec![1, 2, 3, 4]);
+}
+
+#[wasm_bindgen_test]
+async fn read_exact_reports_early_eof() {
+    let mut reader = BYOBStreamReader::new(byte_stream(&[1, 2, 3]), 8).unwrap();
+    let err = reader.read_exact(5).await.unwrap_err();
+    assert!(err.as_string().unwrap().contains("unexpected EOF"));
+}
+
+#[wasm_bindgen_test]
+fn wasm_buffer_pool_recycles_views() {
+    let mut pool = WasmBufferPool::new(16, 1);
+    let a = pool.acquire();
+    let b = pool.acquire();
+    assert_eq!(a.length(), 16);
+    assert_eq!(pool.available(), 0);
+
+    pool.release(&a).unwrap();
+    pool.release(&b).unwrap();
+    assert_eq!(pool.available(), 1);
+    assert_eq!(pool.capacity(), 1);
+    assert!(pool.release(&js_sys::Uint8Array::new_with_length(16)).is_err());
+}
+
+#[wasm_bindgen_test]
+fn wasm_buffer_pool_views_are_attached_when_returned() {
+    // Enough large buffers that acquiring them grows memory along the way.
+    let mut pool = WasmBufferPool::new(64 * 1024, 0);
+    let views: Vec<_> = (0..200)
+        .map(|_| {
+            let view = pool.acquire();
+            assert_eq!(view.byte_length(), 64 * 1024);
+            view
+        })
+        .collect();
+    for view in &views {
+        pool.release(view).unwrap();
+    }
+}
+
+#[wasm_bindgen_test]
+fn wasm_buffer_pool_releases_detached_views() {
+    let mut pool = WasmBufferPool::new(16, 2);
+    let detached = pool.acquire();
+    let live = pool.acquire();
+
+    wasm_bindgen::memory()
+        .unchecked_into::<js_sys::WebAssembly::Memory>()
+        .grow(1);
+    assert_eq!(detached.byte_length(), 0);
+
+    pool.release(&detached).unwrap();
+    assert_eq!(pool.available(), 1);
+    assert!(pool.release(&detached).is_err());
+
+    pool.release(&live).unwrap();
+    assert_eq!(pool.available(), 2);
+}
+
+#[wasm_bindgen_test]
+fn buffer_pool_misses_only_when_empty() {
+    let mut pool: BufferPool = BufferPool::new(4, 2);
+    pool.preallocate(5);
+    assert_eq!(pool.available(), 2);
+
+    let a = pool.acquire();
+    let b = pool.acquire();
+    assert_eq!(pool.stats(), PoolStats { hits: 2, misses: 0 });
+    let c = pool.acquire();
+    assert_eq!(pool.stats(), PoolStats { hits: 2, misses: 1 });
+
+    pool.release(a);
+    pool.release(b);
+    pool.release(c);
+    pool.acquire();
+    assert_eq!(pool.stats(), PoolStats { hits: 3, misses: 1 });
+}
+
+#[wasm_bindgen_test]
+fn wasm_buffer_pool_reports_stats() {
+    let mut pool = WasmBufferPool::new(16, 1);
+    pool.preallocate(1);
+    pool.acquire();
+    pool.acquire();
+    let stats = pool.stats().unwrap();
+    assert_eq!(js_sys::Reflect::get(&stats, &"hits".into()).unwrap(), 1);
+    assert_eq!(js_sys::Reflect::get(&stats, &"misses".into()).unwrap(), 1);
+}
+
+#[wasm_bindgen_test]
+fn buffer_pool_release_keeps_contents() {
+    let mut pool: BufferPool = BufferPool::new(4, 2);
+    let mut buffer = pool.acquire();
+    buffer.copy_from_slice(&[1, 2, 3, 4]);
+    buffer.truncate(2);
+    pool.release(buffer);
+    assert_eq!(pool.acquire(), vec![1, 2, 0, 0]);
+}
+
+#[wasm_bindgen_test]
+async fn stalled_read_times_out_and_unlocks() {
+    let source = js_sys::Object::new();
+    js_sys::Reflect::set(&source, &"type".into(), &"bytes".into()).unwrap();
+    let stream = web_sys::ReadableStream::new_with_underlying_source(&source).unwrap();
+    let mut reader = BYOBStreamReader::new(stream.clone(), 8).unwrap();
+    reader.set_read_timeout(Some(20));
+
+    let err = reader.read_into(js_sys::Uint8Array::new_with_length(8)).await.unwrap_err();
+    assert!(err.as_string().unwrap().contains("timed out"));
+    assert!(!stream.locked());
+}
+
+#[wasm_bindgen_test]
+async fn controller_stream_serves_enqueued_chunks() {
+    let controller = BYOBStreamController::new(4, 8);
+    let stream = controller.create_readable_stream(js_sys::Function::new_no_args(""));
+    controller.enqueue(&[1, 2, 3]).unwrap();
+    controller.enqueue(&[4, 5, 6, 7, 8, 9]).unwrap();
+    controller.close().unwrap();
+
+    let mut reader = BYOBStreamReader::new(stream, 4).unwrap();
+    assert_eq!(reader.read_exact(2).await.unwrap().to_vec(), vec![1, 2]);
+    assert_eq!(reader.read_exact(5).await.unwrap().to_vec(), vec![3, 4, 5, 6, 7]);
+    assert_eq!(reader.read_exact(2).await.unwrap().to_vec(), vec![8, 9]);
+    assert!(reader.read_exact(1).await.is_err());
+    assert_eq!(controller.pending_bytes(), 0);
+}
+
+#[wasm_bindgen_test]
+async fn controller_stream_falls_back_to_pull_fn() {
+    let controller = BYOBStreamController::new(4, 0);
+    let pull_fn = js_sys::Function::new_with_args("c", "c.enqueue(new Uint8Array([7, 7]));");
+    let stream = controller.create_readable_stream(pull_fn);
+
+    let mut reader = BYOBStreamReader::new(stream, 4).unwrap();
+    assert_eq!(reader.read_exact(6).await.unwrap().to_vec(), vec![7; 6]);
+}
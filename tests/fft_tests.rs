@@ -0,0 +1,33 @@
+#![cfg(feature = "fft")]
+
+use nagare::fft::magnitude_spectrum;
+
+fn tone(n: usize, bin: usize) -> Vec<f32> {
+    (0..n)
+        .map(|i| (2.0 * std::f32::consts::PI * bin as f32 * i as f32 / n as f32).sin())
+        .collect()
+}
+
+#[test]
+fn pure_tone_concentrates_in_one_bin() {
+    let spectrum = magnitude_spectrum(&tone(64, 5)).unwrap();
+    assert_eq!(spectrum.len(), 33);
+    assert!((spectrum[5] - 32.0).abs() < 1e-3, "peak {}", spectrum[5]);
+
+    let leakage: f32 = spectrum.iter().enumerate().filter(|(k, _)| *k != 5).map(|(_, m)| m).sum();
+    assert!(leakage < 1e-2, "leakage {}", leakage);
+}
+
+#[test]
+fn constant_signal_is_all_dc() {
+    let spectrum = magnitude_spectrum(&[2.0; 8]).unwrap();
+    assert_eq!(spectrum[0], 16.0);
+    assert!(spectrum[1..].iter().all(|m| m.abs() < 1e-5));
+}
+
+#[test]
+fn rejects_lengths_that_are_not_powers_of_two() {
+    assert!(magnitude_spectrum(&[1.0; 12]).is_err());
+    assert!(magnitude_spectrum(&[]).is_err());
+    assert_eq!(magnitude_spectrum(&[3.0]).unwrap(), vec![3.0]);
+}
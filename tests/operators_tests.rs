@@ -0,0 +1,233 @@
+#![cfg(target_arch = "wasm32")]
+
+use js_sys::{Float32Array, Float64Array, Int32Array, Object, Reflect};
+use nagare::operators::*;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+fn params(entries: &[(&str, f64)]) -> JsValue {
+    let obj = Object::new();
+    for (key, value) in entries {
+        Reflect::set(&obj, &(*key).into(), &JsValue::from_f64(*value)).unwrap();
+    }
+    obj.into()
+}
+
+#[wasm_bindgen_test]
+fn int32_batch_saturates() {
+    let data = Int32Array::from(&[i32::MIN, -3, i32::MAX, 4][..]);
+
+    let abs = process_int32_batch(&data, "abs", &JsValue::UNDEFINED).unwrap();
+    assert_eq!(abs.to_vec(), vec![i32::MAX, 3, i32::MAX, 4]);
+
+    let cumsum = process_int32_batch(&data, "cumsum", &JsValue::UNDEFINED).unwrap();
+    assert_eq!(cumsum.to_vec(), vec![i32::MIN, i32::MIN, -1, 3]);
+
+    let added = process_int32_batch(&data, "saturating_add_scalar", &params(&[("value", 10.0)])).unwrap();
+    assert_eq!(added.to_vec(), vec![i32::MIN + 10, 7, i32::MAX, 14]);
+}
+
+#[wasm_bindgen_test]
+fn int32_clamp_validates_params() {
+    let data = Int32Array::from(&[-5, 0, 5][..]);
+    let clamped = process_int32_batch(&data, "clamp", &params(&[("min", -1.0), ("max", 2.0)])).unwrap();
+    assert_eq!(clamped.to_vec(), vec![-1, 0, 2]);
+
+    assert!(process_int32_batch(&data, "clamp", &params(&[("min", 3.0), ("max", 2.0)])).is_err());
+    assert!(process_int32_batch(&data, "clamp", &JsValue::UNDEFINED).is_err());
+
+    for bad in [1.9, f64::NAN, f64::INFINITY, 3e9, -3e9] {
+        assert!(process_int32_batch(&data, "clamp", &params(&[("min", bad), ("max", 2.0)])).is_err(), "{bad}");
+        assert!(process_int32_batch(&data, "saturating_add_scalar", &params(&[("value", bad)])).is_err(), "{bad}");
+    }
+}
+
+#[wasm_bindgen_test]
+fn int32_batch_rejects_unknown_operations() {
+    let data = Int32Array::from(&[1, 2][..]);
+    let err = process_int32_batch(&data, "cumsun", &JsValue::UNDEFINED).unwrap_err().as_string().unwrap();
+    assert_eq!(err, "Unsupported int32 operation: cumsun");
+}
+
+#[wasm_bindgen_test]
+fn combine_float32_elementwise() {
+    let a = Float32Array::from(&[1.0, -2.0, 3.0, 8.0, 5.0][..]);
+    let b = Float32Array::from(&[2.0, 2.0, -1.0, 4.0, 5.0][..]);
+
+    assert_eq!(combine_float32(&a, &b, "add").unwrap().to_vec(), vec![3.0, 0.0, 2.0, 12.0, 10.0]);
+    assert_eq!(combine_float32(&a, &b, "sub").unwrap().to_vec(), vec![-1.0, -4.0, 4.0, 4.0, 0.0]);
+    assert_eq!(combine_float32(&a, &b, "mul").unwrap().to_vec(), vec![2.0, -4.0, -3.0, 32.0, 25.0]);
+    assert_eq!(combine_float32(&a, &b, "div").unwrap().to_vec(), vec![0.5, -1.0, -3.0, 2.0, 1.0]);
+    assert_eq!(combine_float32(&a, &b, "min").unwrap().to_vec(), vec![1.0, -2.0, -1.0, 4.0, 5.0]);
+    assert_eq!(combine_float32(&a, &b, "max").unwrap().to_vec(), vec![2.0, 2.0, 3.0, 8.0, 5.0]);
+}
+
+#[wasm_bindgen_test]
+fn combine_float32_rejects_length_mismatch() {
+    let a = Float32Array::from(&[1.0, 2.0, 3.0][..]);
+    let b = Float32Array::from(&[1.0, 2.0][..]);
+    assert!(combine_float32(&a, &b, "add").is_err());
+    assert!(combine_float32(&a, &a, "pow").is_err());
+}
+
+#[wasm_bindgen_test]
+fn normalize_constant_and_single_element_inputs_are_finite() {
+    let constant = Float32Array::from(&[3.0f32; 7][..]);
+    assert_eq!(process_float32_batch(&constant, "normalize").to_vec(), vec![0.0; 7]);
+
+    let single = Float32Array::from(&[42.0f32][..]);
+    assert_eq!(process_float32_batch(&single, "normalize").to_vec(), vec![0.0]);
+
+    let constant64 = Float64Array::from(&[-1.5f64; 5][..]);
+    assert_eq!(process_float64_batch(&constant64, "normalize").to_vec(), vec![0.0; 5]);
+
+    let single64 = Float64Array::from(&[42.0f64][..]);
+    assert_eq!(process_float64_batch(&single64, "normalize").to_vec(), vec![0.0]);
+
+    // Values f32 can't represent exactly, whose rounded mean is off by an ulp.
+    for constant in [Float32Array::from(&[0.1f32; 9][..]), Float32Array::from(&[1.7f32; 17][..])] {
+        let len = constant.length() as usize;
+        assert_eq!(process_float32_batch(&constant, "normalize").to_vec(), vec![0.0; len]);
+        assert_eq!(normalize_float32(&constant, 0.0).to_vec(), vec![0.0; len]);
+    }
+    for constant in [&[0.1f64; 9][..], &[0.1; 3], &[1.7; 17]].map(Float64Array::from) {
+        let len = constant.length() as usize;
+        assert_eq!(process_float64_batch(&constant, "normalize").to_vec(), vec![0.0; len]);
+        assert_eq!(normalize_float64(&constant, 0.0).to_vec(), vec![0.0; len]);
+    }
+}
+
+#[wasm_bindgen_test]
+fn autocorr_of_a_constant_input_is_zero() {
+    // Scalar or SIMD, whichever this build dispatches to.
+    assert_eq!(nagare::dispatch::autocorr(&[0.1; 8], 3), vec![0.0; 4]);
+    assert_eq!(nagare::dispatch::autocorr(&[1.7; 17], 2), vec![0.0; 3]);
+}
+
+#[wasm_bindgen_test]
+fn normalize_with_explicit_epsilon() {
+    let data = Float64Array::from(&[1.0f64, 3.0][..]);
+    let normalized = normalize_float64(&data, 0.0).to_vec();
+    assert_eq!(normalized, vec![-1.0, 1.0]);
+
+    let constant = Float32Array::from(&[2.0f32; 3][..]);
+    assert_eq!(normalize_float32(&constant, 0.0).to_vec(), vec![0.0; 3]);
+}
+
+#[wasm_bindgen_test]
+fn checked_batch_rejects_unknown_operations() {
+    let data = Float32Array::from(&[1.0, 4.0, 9.0][..]);
+    assert_eq!(process_float32_batch_checked(&data, "sqrt").unwrap().to_vec(), vec![1.0, 2.0, 3.0]);
+
+    let err = process_float32_batch_checked(&data, "sqr").unwrap_err().as_string().unwrap();
+    assert!(err.contains("sqr ("), "{}", err);
+    assert!(err.contains("square, sqrt"), "{}", err);
+
+    // The unchecked entry point still passes unknown operations through.
+    assert_eq!(process_float32_batch(&data, "sqr").to_vec(), vec![1.0, 4.0, 9.0]);
+
+    let data64 = Float64Array::from(&[1.0, 4.0, 9.0][..]);
+    assert_eq!(process_float64_batch_checked(&data64, "sqrt").unwrap().to_vec(), vec![1.0, 2.0, 3.0]);
+    let err = process_float64_batch_checked(&data64, "sqr").unwrap_err().as_string().unwrap();
+    assert!(err.contains("sqr ("), "{}", err);
+    assert!(process_float64_batch_checked(&data64, "mean").is_err());
+    assert_eq!(process_float64_batch(&data64, "sqr").to_vec(), vec![1.0, 4.0, 9.0]);
+}
+
+#[wasm_bindgen_test]
+fn supported_operations_lists_every_checked_operation() {
+    let data = Float32Array::from(&[0.5, 1.0][..]);
+    let ops = supported_operations();
+    assert_eq!(ops.length() as usize, FLOAT32_OPERATIONS.len());
+    for op in ops.iter() {
+        assert!(process_float32_batch_checked(&data, &op.as_string().unwrap()).is_ok());
+    }
+}
+
+#[wasm_bindgen_test]
+fn float32_clamp_validates_params() {
+    let data = Float32Array::from(&[-2.5, 0.25, 3.0][..]);
+    let clamped = process_float32_batch_with_params(&data, "clamp", &params(&[("min", -1.0), ("max", 1.0)])).unwrap();
+    assert_eq!(clamped.to_vec(), vec![-1.0, 0.25, 1.0]);
+
+    assert!(process_float32_batch_with_params(&data, "clamp", &params(&[("min", 1.0), ("max", -1.0)])).is_err());
+    assert!(process_float32_batch_with_params(&data, "clamp", &params(&[("min", 1.0)])).is_err());
+}
+
+#[wasm_bindgen_test]
+fn f32x_clamp_export_checks_its_bounds() {
+    let data = Float32Array::from(&[-1.0, -0.5, 0.0, 0.5, 1.0][..]);
+    assert_eq!(nagare::f32x_clamp(&data, -0.5, 0.5).unwrap().to_vec(), vec![-0.5, -0.5, 0.0, 0.5, 0.5]);
+    assert_eq!(nagare::f32x_clamp(&data, -1.0, 1.0).unwrap().to_vec(), data.to_vec());
+
+    let err = nagare::f32x_clamp(&data, 1.0, -1.0).unwrap_err();
+    assert_eq!(err.as_string().unwrap(), "clamp requires lo <= hi");
+    assert!(nagare::f32x_clamp(&data, f32::NAN, 1.0).is_err());
+}
+
+#[wasm_bindgen_test]
+fn float32_scale_and_offset() {
+    let data = Float32Array::from(&[-2.0, 0.5, 4.0][..]);
+    let scaled = process_float32_batch_with_params(&data, "scale", &params(&[("value", 2.0)])).unwrap();
+    assert_eq!(scaled.to_vec(), vec![-4.0, 1.0, 8.0]);
+
+    let shifted = process_float32_batch_with_params(&data, "offset", &params(&[("value", -0.5)])).unwrap();
+    assert_eq!(shifted.to_vec(), vec![-2.5, 0.0, 3.5]);
+
+    assert!(process_float32_batch_with_params(&data, "scale", &JsValue::UNDEFINED).is_err());
+    // Parameterless operations fall through and ignore params.
+    let squared = process_float32_batch_with_params(&data, "square", &JsValue::UNDEFINED).unwrap();
+    assert_eq!(squared.to_vec(), vec![4.0, 0.25, 16.0]);
+}
+
+#[wasm_bindgen_test]
+fn minmax_scales_to_unit_or_given_range() {
+    let data = Float32Array::from(&[2.0, 4.0, 6.0, 10.0][..]);
+    assert_eq!(process_float32_batch(&data, "minmax").to_vec(), vec![0.0, 0.25, 0.5, 1.0]);
+    let ranged = process_float32_batch_with_params(&data, "minmax_range", &params(&[("lo", -1.0), ("hi", 1.0)])).unwrap();
+    assert_eq!(ranged.to_vec(), vec![-1.0, -0.5, 0.0, 1.0]);
+    let data64 = Float64Array::from(&[2.0, 4.0, 6.0, 10.0][..]);
+    assert_eq!(process_float64_batch(&data64, "minmax").to_vec(), vec![0.0, 0.25, 0.5, 1.0]);
+
+    // A constant input has no range: it maps to the bottom of the target.
+    let constant = Float32Array::from(&[3.0; 5][..]);
+    assert_eq!(process_float32_batch(&constant, "minmax").to_vec(), vec![0.0; 5]);
+    let ranged = process_float32_batch_with_params(&constant, "minmax_range", &params(&[("lo", 2.0), ("hi", 4.0)])).unwrap();
+    assert_eq!(ranged.to_vec(), vec![2.0; 5]);
+    assert!(process_float32_batch_with_params(&data, "minmax_range", &params(&[("lo", 1.0), ("hi", 0.0)])).is_err());
+    for (lo, hi) in [(f64::NAN, 1.0), (0.0, f64::NAN), (f64::NEG_INFINITY, 1.0), (0.0, f64::INFINITY), (0.0, 1e39)] {
+        let bounds = params(&[("lo", lo), ("hi", hi)]);
+        assert!(process_float32_batch_with_params(&data, "minmax_range", &bounds).is_err(), "{lo}..{hi}");
+    }
+}
+
+#[wasm_bindgen_test]
+fn diff_drops_or_prepends_one_element() {
+    let values: Vec<f32> = (0..11).map(|i| (i * i) as f32).collect();
+    let data = Float32Array::from(&values[..]);
+    let expected: Vec<f32> = (1..11).map(|i| (2 * i - 1) as f32).collect();
+    assert_eq!(process_float32_batch(&data, "diff").to_vec(), expected);
+
+    let padded = process_float32_batch(&data, "diff_prepend_zero").to_vec();
+    assert_eq!(padded.len(), values.len());
+    assert_eq!(padded[0], 0.0);
+    assert_eq!(&padded[1..], &expected[..]);
+
+    let data64 = Float64Array::from(&[1.0, 4.0, 2.0][..]);
+    assert_eq!(process_float64_batch(&data64, "diff").to_vec(), vec![3.0, -2.0]);
+    assert_eq!(process_float64_batch(&data64, "diff_prepend_zero").to_vec(), vec![0.0, 3.0, -2.0]);
+}
+
+#[wasm_bindgen_test]
+fn diff_handles_short_inputs() {
+    let empty = Float32Array::new_with_length(0);
+    let single = Float32Array::from(&[5.0][..]);
+    assert_eq!(process_float32_batch(&empty, "diff").length(), 0);
+    assert_eq!(process_float32_batch(&empty, "diff_prepend_zero").length(), 0);
+    assert_eq!(process_float32_batch(&single, "diff").length(), 0);
+    assert_eq!(process_float32_batch(&single, "diff_prepend_zero").to_vec(), vec![0.0]);
+
+    let single64 = Float64Array::from(&[5.0][..]);
+    assert_eq!(process_float64_batch(&single64, "diff").length(), 0);
+    assert_eq!(process_float64_batch(&single64, "diff_prepend_zero").to_vec(), vec![0.0]);
+}
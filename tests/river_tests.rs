@@ -0,0 +1,1299 @@
+#![cfg(all(target_arch = "wasm32", feature = "river"))]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use futures::channel::{mpsc, oneshot};
+use js_sys::{Array, Function};
+use nagare::backpressure::CreditManager;
+use nagare::errors::{self, ErrorInfo};
+use nagare::river::{AsyncMapOrder, RiverCore, RiverValue, SubscriptionHandle};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::*;
+
+fn numbers(values: impl IntoIterator<Item = f64>) -> RiverCore {
+    RiverCore::from_js_array(values.into_iter().map(JsValue::from_f64).collect::<Array>())
+}
+
+struct Subscription<H = SubscriptionHandle> {
+    values: Rc<RefCell<Vec<JsValue>>>,
+    errors: Rc<RefCell<Vec<JsValue>>>,
+    completed: oneshot::Receiver<()>,
+    _handle: H,
+}
+
+// Subscribes to `river`, recording every emitted value. The callbacks are
+// leaked so they outlive the test's borrow of them.
+fn subscribe(river: &RiverCore) -> Subscription {
+    record(|next, error, complete| river.observe(next, Some(error), Some(complete), None))
+}
+
+fn subscribe_with_credits(river: &RiverCore, credits: &CreditManager) -> Subscription {
+    record(|next, error, complete| river.observe_with_credits(credits, next, Some(error), Some(complete), None))
+}
+
+fn record<H>(observe: impl FnOnce(Function, Function, Function) -> H) -> Subscription<H> {
+    let values = Rc::new(RefCell::new(Vec::new()));
+    let sink = values.clone();
+    let next = Closure::<dyn FnMut(JsValue)>::new(move |value| sink.borrow_mut().push(value));
+    let errors = Rc::new(RefCell::new(Vec::new()));
+    let error_sink = errors.clone();
+    let error = Closure::<dyn FnMut(JsValue)>::new(move |e| error_sink.borrow_mut().push(e));
+
+    let (done, completed) = oneshot::channel();
+    let done = RefCell::new(Some(done));
+    let complete = Closure::<dyn FnMut()>::new(move || {
+        if let Some(done) = done.borrow_mut().take() {
+            let _ = done.send(());
+        }
+    });
+
+    let next_fn: Function = next.into_js_value().unchecked_into();
+    let error_fn: Function = error.into_js_value().unchecked_into();
+    let complete_fn: Function = complete.into_js_value().unchecked_into();
+    let handle = observe(next_fn, error_fn, complete_fn);
+    Subscription { values, errors, completed, _handle: handle }
+}
+
+// Resolves with everything `river` emits before completing.
+async fn collect(river: &RiverCore) -> Vec<JsValue> {
+    let subscription = subscribe(river);
+    subscription.completed.await.unwrap();
+    subscription.values.take()
+}
+
+// Lets spawned subscriptions run until they are waiting on their sources.
+async fn settle() {
+    for _ in 0..4 {
+        JsFuture::from(js_sys::Promise::resolve(&JsValue::UNDEFINED)).await.unwrap();
+    }
+}
+
+fn channel() -> (RiverCore, mpsc::UnboundedSender<RiverValue>) {
+    let (sender, receiver) = mpsc::unbounded();
+    (RiverCore::from_channel(receiver), sender)
+}
+
+fn pairs(values: &[JsValue]) -> Vec<(f64, f64)> {
+    values
+        .iter()
+        .map(|pair| {
+            let pair = Array::from(pair);
+            (pair.get(0).as_f64().unwrap(), pair.get(1).as_f64().unwrap())
+        })
+        .collect()
+}
+
+async fn collect_f64(river: &RiverCore) -> Vec<f64> {
+    collect(river).await.iter().map(|v| v.as_f64().unwrap()).collect()
+}
+
+#[wasm_bindgen_test]
+async fn sample_every_keeps_every_nth_value() {
+    let river = numbers((0..10).map(f64::from)).sample_every(3);
+    assert_eq!(collect_f64(&river).await, vec![0.0, 3.0, 6.0, 9.0]);
+    // A second subscription starts counting from scratch.
+    assert_eq!(collect_f64(&river).await, vec![0.0, 3.0, 6.0, 9.0]);
+}
+
+#[wasm_bindgen_test]
+async fn decimate_mean_averages_complete_groups() {
+    let river = numbers((0..10).map(f64::from)).decimate_mean(3);
+    assert_eq!(collect_f64(&river).await, vec![1.0, 4.0, 7.0]);
+    assert_eq!(collect_f64(&river).await, vec![1.0, 4.0, 7.0]);
+}
+
+// Observes `river` through `observe_transferable`, recording each value as
+// `{ view, value }` while it is still valid: `view` says whether it was a view
+// into WASM memory, and typed arrays are copied into plain arrays.
+async fn collect_transferable(river: &RiverCore) -> Vec<(bool, JsValue)> {
+    let summarize = Function::new_with_args(
+        "sink, memory",
+        "return v => sink({ view: ArrayBuffer.isView(v) && v.buffer === memory.buffer, \
+         value: ArrayBuffer.isView(v) ? Array.from(v) : v })",
+    );
+    let subscription = record(|next, error, complete| {
+        let next = summarize.call2(&JsValue::NULL, &next, &wasm_bindgen::memory()).unwrap();
+        river.observe_transferable(next.unchecked_into(), Some(error), Some(complete), None)
+    });
+    subscription.completed.await.unwrap();
+    let values = subscription.values.borrow();
+    values
+        .iter()
+        .map(|v| {
+            let view = js_sys::Reflect::get(v, &"view".into()).unwrap().is_truthy();
+            (view, js_sys::Reflect::get(v, &"value".into()).unwrap())
+        })
+        .collect()
+}
+
+#[wasm_bindgen_test]
+async fn observe_transferable_hands_out_views_of_typed_values() {
+    let data = js_sys::Float32Array::from(&[1.0f32, 2.0, 3.0, 4.0][..]);
+    let chunks = collect_transferable(&RiverCore::from_float32_array(&data, Some(2))).await;
+    assert_eq!(chunks.len(), 2);
+    for ((view, chunk), expected) in chunks.iter().zip([[1.0, 2.0], [3.0, 4.0]]) {
+        assert!(view);
+        assert_eq!(Array::from(chunk).iter().map(|x| x.as_f64().unwrap()).collect::<Vec<_>>(), expected);
+    }
+
+    // Anything else is emitted as it would be by `observe`.
+    let object = js("return { a: 1 }");
+    let values: Array = [JsValue::from(1.5), "text".into(), object.clone()].into_iter().collect();
+    let plain = collect_transferable(&RiverCore::from_js_array(values)).await;
+    assert!(plain.iter().all(|(view, _)| !view));
+    assert_eq!(plain[0].1, 1.5);
+    assert_eq!(plain[1].1, "text");
+    assert!(js_sys::Object::is(&plain[2].1, &object));
+}
+
+#[wasm_bindgen_test]
+async fn ema_seeds_with_the_first_number_and_skips_non_numbers() {
+    let values: Array = [JsValue::from("a"), 10.0.into(), JsValue::NULL, 20.0.into(), "b".into(), 30.0.into()]
+        .into_iter()
+        .collect();
+    let river = RiverCore::from_js_array(values).ema(0.5).unwrap();
+    assert_eq!(collect_f64(&river).await, vec![10.0, 15.0, 22.5]);
+    // A second subscription seeds again rather than continuing the average.
+    assert_eq!(collect_f64(&river).await, vec![10.0, 15.0, 22.5]);
+}
+
+#[wasm_bindgen_test]
+async fn windowed_aggregate_emits_once_the_window_fills() {
+    let river = numbers([1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(collect_f64(&river.windowed_aggregate(3, "mean").unwrap()).await, vec![2.0, 3.0, 4.0]);
+    assert_eq!(collect_f64(&river.windowed_aggregate(2, "max").unwrap()).await, vec![2.0, 3.0, 4.0, 5.0]);
+
+    let message = |window, operation| river.windowed_aggregate(window, operation).err().unwrap().as_string().unwrap();
+    assert_eq!(message(3, "avg"), "Unsupported operation: avg");
+    assert_eq!(message(3, "sqrt"), "sqrt is not an aggregate operation");
+    assert!(river.windowed_aggregate(0, "mean").is_err());
+}
+
+// Regression: chained builders used to drop the source, so every operator
+// after the first produced an empty river.
+#[wasm_bindgen_test]
+async fn chained_operators_keep_the_source() {
+    let doubled = numbers([1.0, 2.0, 3.0, 4.0]).map(Function::new_with_args("x", "return x * 2"));
+    let river = doubled.filter(Function::new_with_args("x", "return x > 2"));
+    assert_eq!(collect_f64(&river).await, vec![4.0, 6.0, 8.0]);
+    assert_eq!(collect_f64(&river.ema(0.5).unwrap()).await, vec![4.0, 5.0, 6.5]);
+}
+
+fn counts(histogram: &JsValue) -> Vec<f64> {
+    Array::from(histogram).iter().map(|c| c.as_f64().unwrap()).collect()
+}
+
+fn field(value: &JsValue, name: &str) -> f64 {
+    js_sys::Reflect::get(value, &name.into()).unwrap().as_f64().unwrap()
+}
+
+#[wasm_bindgen_test]
+async fn histogram_emits_on_complete_with_out_of_range_counts() {
+    let river = numbers([0.0, 2.5, 5.0, 7.5, 10.0, -1.0, 11.0, 12.0]).histogram(0.0, 10.0, 4, 0).unwrap();
+    let emitted = collect(&river).await;
+    assert_eq!(emitted.len(), 1);
+    assert_eq!(counts(&emitted[0]), vec![1.0, 1.0, 1.0, 2.0]);
+    assert_eq!(field(&emitted[0], "underflow"), 1.0);
+    assert_eq!(field(&emitted[0], "overflow"), 2.0);
+}
+
+#[wasm_bindgen_test]
+async fn histogram_emits_at_its_interval() {
+    let river = numbers([1.0, 1.0, 3.0, 3.0, 3.0]).histogram(0.0, 4.0, 2, 2).unwrap();
+    let emitted: Vec<Vec<f64>> = collect(&river).await.iter().map(counts).collect();
+    assert_eq!(emitted, vec![vec![2.0, 0.0], vec![2.0, 2.0], vec![2.0, 3.0]]);
+    assert!(numbers([]).histogram(1.0, 0.0, 2, 0).is_err());
+}
+
+#[cfg(feature = "fft")]
+#[wasm_bindgen_test]
+async fn fft_emits_a_spectrum_per_window() {
+    let tone = (0..40).map(|i| (2.0 * std::f64::consts::PI * 2.0 * i as f64 / 16.0).sin());
+    let spectra = collect(&numbers(tone).fft(16).unwrap()).await;
+    // 40 samples fill two windows of 16; the remaining 8 are dropped.
+    assert_eq!(spectra.len(), 2);
+    for spectrum in &spectra {
+        let magnitudes = js_sys::Float32Array::from(spectrum.clone()).to_vec();
+        assert_eq!(magnitudes.len(), 9);
+        let peak = (0..9).max_by(|&a, &b| magnitudes[a].total_cmp(&magnitudes[b])).unwrap();
+        assert_eq!(peak, 2);
+    }
+    assert!(numbers([]).fft(12).is_err());
+}
+
+#[wasm_bindgen_test]
+async fn zip_pairs_values_and_stops_with_the_shorter_river() {
+    let zipped = numbers([1.0, 2.0, 3.0, 4.0]).zip(&numbers([10.0, 20.0]).map(Function::new_with_args("x", "return x + 1")));
+    assert_eq!(pairs(&collect(&zipped).await), vec![(1.0, 11.0), (2.0, 21.0)]);
+}
+
+#[wasm_bindgen_test]
+async fn zip_buffers_the_faster_river() {
+    let (left, left_tx) = channel();
+    let (right, right_tx) = channel();
+    let subscription = subscribe(&left.zip(&right));
+
+    for x in [1.0, 2.0, 3.0] {
+        left_tx.unbounded_send(RiverValue::Number(x)).unwrap();
+    }
+    settle().await;
+    assert!(subscription.values.borrow().is_empty());
+
+    right_tx.unbounded_send(RiverValue::Number(10.0)).unwrap();
+    right_tx.unbounded_send(RiverValue::Number(20.0)).unwrap();
+    settle().await;
+    assert_eq!(pairs(&subscription.values.borrow()), vec![(1.0, 10.0), (2.0, 20.0)]);
+
+    drop(right_tx);
+    subscription.completed.await.unwrap();
+    assert_eq!(subscription.values.borrow().len(), 2);
+}
+
+#[wasm_bindgen_test]
+async fn combine_latest_emits_on_either_side_after_both_emitted() {
+    let (left, left_tx) = channel();
+    let (right, right_tx) = channel();
+    let subscription = subscribe(&left.combine_latest(&right));
+
+    left_tx.unbounded_send(RiverValue::Number(1.0)).unwrap();
+    left_tx.unbounded_send(RiverValue::Number(2.0)).unwrap();
+    settle().await;
+    assert!(subscription.values.borrow().is_empty());
+
+    right_tx.unbounded_send(RiverValue::Number(10.0)).unwrap();
+    settle().await;
+    left_tx.unbounded_send(RiverValue::Number(3.0)).unwrap();
+    settle().await;
+    right_tx.unbounded_send(RiverValue::Number(20.0)).unwrap();
+    settle().await;
+    assert_eq!(pairs(&subscription.values.borrow()), vec![(2.0, 10.0), (3.0, 10.0), (3.0, 20.0)]);
+
+    // One side completing keeps the other one combining.
+    drop(left_tx);
+    right_tx.unbounded_send(RiverValue::Number(30.0)).unwrap();
+    drop(right_tx);
+    subscription.completed.await.unwrap();
+    assert_eq!(pairs(&subscription.values.borrow()).last(), Some(&(3.0, 30.0)));
+}
+
+#[wasm_bindgen_test]
+async fn sample_on_emits_the_latest_value_per_trigger() {
+    let (primary, primary_tx) = channel();
+    let (trigger, trigger_tx) = channel();
+    let subscription = subscribe(&primary.sample_on(&trigger));
+
+    // Nothing to sample yet.
+    trigger_tx.unbounded_send(RiverValue::Number(0.0)).unwrap();
+    settle().await;
+    assert!(subscription.values.borrow().is_empty());
+
+    for x in 1..=5 {
+        primary_tx.unbounded_send(RiverValue::Number(f64::from(x))).unwrap();
+    }
+    settle().await;
+    trigger_tx.unbounded_send(RiverValue::Number(0.0)).unwrap();
+    settle().await;
+    primary_tx.unbounded_send(RiverValue::Number(6.0)).unwrap();
+    primary_tx.unbounded_send(RiverValue::Number(7.0)).unwrap();
+    settle().await;
+    trigger_tx.unbounded_send(RiverValue::Number(0.0)).unwrap();
+    settle().await;
+
+    // The last value stays sampleable after the primary completes.
+    drop(primary_tx);
+    trigger_tx.unbounded_send(RiverValue::Number(0.0)).unwrap();
+    drop(trigger_tx);
+    subscription.completed.await.unwrap();
+    let values: Vec<f64> = subscription.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(values, vec![5.0, 7.0, 7.0]);
+}
+
+#[wasm_bindgen_test]
+async fn collect_entries_pairs_values_with_their_index() {
+    let river = RiverCore::from_js_array(["a", "b", "c"].into_iter().map(JsValue::from).collect::<Array>());
+    let entries = Array::from(&JsFuture::from(river.collect_entries()).await.unwrap());
+    let entries: Vec<(f64, String)> = entries
+        .iter()
+        .map(|entry| {
+            let entry = Array::from(&entry);
+            (entry.get(0).as_f64().unwrap(), entry.get(1).as_string().unwrap())
+        })
+        .collect();
+    assert_eq!(entries, vec![(0.0, "a".to_string()), (1.0, "b".to_string()), (2.0, "c".to_string())]);
+
+    let empty = Array::from(&JsFuture::from(numbers([]).collect_entries()).await.unwrap());
+    assert_eq!(empty.length(), 0);
+}
+
+fn is_even() -> Function {
+    Function::new_with_args("x", "return x % 2 === 0")
+}
+
+#[wasm_bindgen_test]
+async fn count_first_and_last_reduce_to_one_value() {
+    let river = numbers([1.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+    assert_eq!(JsFuture::from(river.count()).await.unwrap(), 6.0);
+    assert_eq!(JsFuture::from(river.first(None)).await.unwrap(), 1.0);
+    assert_eq!(JsFuture::from(river.first(Some(is_even()))).await.unwrap(), 4.0);
+    assert_eq!(JsFuture::from(river.last(None)).await.unwrap(), 7.0);
+    assert_eq!(JsFuture::from(river.last(Some(is_even()))).await.unwrap(), 6.0);
+}
+
+#[wasm_bindgen_test]
+async fn reducers_on_empty_rivers() {
+    let empty = numbers([]);
+    assert_eq!(JsFuture::from(empty.count()).await.unwrap(), 0.0);
+    assert!(JsFuture::from(empty.first(None)).await.is_err());
+    assert!(JsFuture::from(empty.last(None)).await.is_err());
+    assert!(JsFuture::from(numbers([1.0, 3.0]).first(Some(is_even()))).await.is_err());
+}
+
+#[wasm_bindgen_test]
+async fn first_stops_reading_an_endless_source() {
+    let (river, sender) = channel();
+    for x in [1.0, 2.0] {
+        sender.unbounded_send(RiverValue::Number(x)).unwrap();
+    }
+    // The channel never closes, so this only resolves by stopping early.
+    assert_eq!(JsFuture::from(river.first(Some(is_even()))).await.unwrap(), 2.0);
+}
+
+fn sleep(ms: u32) -> gloo_timers::future::TimeoutFuture {
+    gloo_timers::future::TimeoutFuture::new(ms)
+}
+
+#[wasm_bindgen_test]
+async fn range_emits_a_finite_sequence() {
+    assert_eq!(collect_f64(&RiverCore::range(0.0, 5.0, 2.0).unwrap()).await, vec![0.0, 2.0, 4.0]);
+    assert_eq!(collect_f64(&RiverCore::range(3.0, 0.0, -1.0).unwrap()).await, vec![3.0, 2.0, 1.0]);
+    assert!(RiverCore::range(0.0, 3.0, 0.0).is_err());
+    assert!(RiverCore::range(0.0, 3.0, -1.0).is_err());
+    assert!(RiverCore::range(2.0, 2.0, 1.0).is_err());
+    // Too small a step to ever get there.
+    assert!(RiverCore::range(0.0, f64::MAX, f64::MIN_POSITIVE).is_err());
+
+    // Values are produced as they are read, so a huge range costs nothing
+    // until it is observed.
+    let huge = RiverCore::range(5.0, 1e12, 1.0).unwrap();
+    let match_at = Function::new_with_args("x", "return x === 7");
+    assert_eq!(JsFuture::from(huge.first(Some(match_at))).await.unwrap(), 7.0);
+}
+
+#[wasm_bindgen_test]
+async fn unsubscribing_an_interval_stops_emissions() {
+    let subscription = subscribe(&RiverCore::interval(5));
+    while subscription.values.borrow().len() < 3 {
+        sleep(5).await;
+    }
+    subscription._handle.unsubscribe();
+    let emitted = subscription.values.borrow().len();
+    sleep(40).await;
+
+    assert_eq!(subscription.values.borrow().len(), emitted);
+    let ticks: Vec<f64> = subscription.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(&ticks[..3], &[0.0, 1.0, 2.0]);
+}
+
+#[wasm_bindgen_test]
+async fn aborting_an_interval_stops_emissions() {
+    let controller = web_sys::AbortController::new().unwrap();
+    let values = Rc::new(RefCell::new(0));
+    let sink = values.clone();
+    let next = Closure::<dyn FnMut(JsValue)>::new(move |_| *sink.borrow_mut() += 1);
+    let _handle = RiverCore::interval(5).observe(
+        next.into_js_value().unchecked_into(),
+        None,
+        None,
+        Some(controller.signal()),
+    );
+
+    sleep(30).await;
+    controller.abort();
+    let emitted = *values.borrow();
+    assert!(emitted > 0);
+    sleep(40).await;
+    assert_eq!(*values.borrow(), emitted);
+}
+
+// What was originally thrown, from the `RiverError` wrapping it.
+fn cause(error: &JsValue) -> JsValue {
+    js_sys::Reflect::get(error, &"cause".into()).unwrap()
+}
+
+fn js(body: &str) -> JsValue {
+    Function::new_no_args(body).call0(&JsValue::NULL).unwrap()
+}
+
+#[wasm_bindgen_test]
+async fn async_iterator_values_flow_through_operators() {
+    let generator = js("return (async function* () { for (let i = 1; i <= 4; i++) yield i; })()");
+    let river = RiverCore::from_async_iterator(generator).filter(Function::new_with_args("x", "return x > 1"));
+    assert_eq!(collect_f64(&river).await, vec![2.0, 3.0, 4.0]);
+}
+
+#[wasm_bindgen_test]
+async fn async_iterator_errors_reach_the_error_callback() {
+    let generator = js("return (async function* () { yield 1; throw new Error('boom'); })()");
+    let errors = Rc::new(RefCell::new(Vec::new()));
+    let sink = errors.clone();
+    let on_error = Closure::<dyn FnMut(JsValue)>::new(move |e: JsValue| sink.borrow_mut().push(e));
+    let (done, completed) = oneshot::channel::<()>();
+    let done = RefCell::new(Some(done));
+    let on_complete = Closure::<dyn FnMut()>::new(move || {
+        let _ = done.borrow_mut().take().unwrap().send(());
+    });
+
+    let values = Rc::new(RefCell::new(Vec::new()));
+    let values_sink = values.clone();
+    let next = Closure::<dyn FnMut(JsValue)>::new(move |v: JsValue| values_sink.borrow_mut().push(v));
+    let _handle = RiverCore::from_async_iterator(generator).observe(
+        next.into_js_value().unchecked_into(),
+        Some(on_error.into_js_value().unchecked_into()),
+        Some(on_complete.into_js_value().unchecked_into()),
+        None,
+    );
+    completed.await.unwrap();
+
+    assert_eq!(values.borrow().len(), 1);
+    assert_eq!(errors.borrow().len(), 1);
+    let message = js_sys::Reflect::get(&errors.borrow()[0], &"message".into()).unwrap();
+    assert_eq!(message, "boom");
+}
+
+#[wasm_bindgen_test]
+async fn stopping_early_returns_the_async_iterator() {
+    let generator = js(
+        "globalThis.__riverCleanup = false; \
+         return (async function* () { try { let i = 0; while (true) yield i++; } \
+         finally { globalThis.__riverCleanup = true; } })()",
+    );
+    let first = JsFuture::from(RiverCore::from_async_iterator(generator).first(None)).await.unwrap();
+    assert_eq!(first, 0.0);
+    settle().await;
+    assert_eq!(js("return globalThis.__riverCleanup"), true);
+}
+
+#[wasm_bindgen_test]
+async fn shared_observers_see_the_same_emissions() {
+    let calls = Rc::new(RefCell::new(0));
+    let counter = calls.clone();
+    let count_calls = Closure::<dyn FnMut(JsValue) -> JsValue>::new(move |x: JsValue| {
+        *counter.borrow_mut() += 1;
+        x
+    });
+    let shared = numbers([1.0, 2.0, 3.0])
+        .map(count_calls.into_js_value().unchecked_into())
+        .share();
+
+    let first = subscribe(&shared);
+    let second = subscribe(&shared);
+    first.completed.await.unwrap();
+    second.completed.await.unwrap();
+
+    let first: Vec<f64> = first.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect();
+    let second: Vec<f64> = second.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(first, vec![1.0, 2.0, 3.0]);
+    assert_eq!(second, first);
+    // The upstream operators ran once, not once per observer.
+    assert_eq!(*calls.borrow(), 3);
+}
+
+#[wasm_bindgen_test]
+async fn late_shared_observers_only_see_live_values() {
+    let (river, sender) = channel();
+    let shared = river.share();
+    let early = subscribe(&shared);
+    sender.unbounded_send(RiverValue::Number(1.0)).unwrap();
+    settle().await;
+
+    let late = subscribe(&shared);
+    sender.unbounded_send(RiverValue::Number(2.0)).unwrap();
+    settle().await;
+
+    assert_eq!(early.values.borrow().len(), 2);
+    assert_eq!(late.values.borrow().len(), 1);
+    assert_eq!(late.values.borrow()[0], 2.0);
+}
+
+#[wasm_bindgen_test]
+async fn shared_upstream_stops_with_the_last_observer() {
+    let shared = RiverCore::interval(5).share();
+    let first = subscribe(&shared);
+    let second = subscribe(&shared);
+    while second.values.borrow().len() < 2 {
+        sleep(5).await;
+    }
+
+    first._handle.unsubscribe();
+    let before = second.values.borrow().len();
+    sleep(20).await;
+    assert!(second.values.borrow().len() > before, "one observer keeps it running");
+
+    second._handle.unsubscribe();
+    settle().await;
+    // A fresh observer restarts the interval from 0.
+    let third = subscribe(&shared);
+    while third.values.borrow().is_empty() {
+        sleep(5).await;
+    }
+    assert_eq!(third.values.borrow()[0], 0.0);
+    third._handle.unsubscribe();
+}
+
+#[wasm_bindgen_test]
+async fn replay_hands_late_observers_the_latest_values() {
+    let (river, sender) = channel();
+    let replayed = river.replay(2);
+    let early = subscribe(&replayed);
+    for x in 1..=5 {
+        sender.unbounded_send(RiverValue::Number(f64::from(x))).unwrap();
+    }
+    settle().await;
+    assert_eq!(early.values.borrow().len(), 5);
+
+    let late = subscribe(&replayed);
+    settle().await;
+    let seen = |s: &Subscription| s.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect::<Vec<_>>();
+    assert_eq!(seen(&late), vec![4.0, 5.0]);
+
+    sender.unbounded_send(RiverValue::Number(6.0)).unwrap();
+    settle().await;
+    assert_eq!(seen(&late), vec![4.0, 5.0, 6.0]);
+    assert_eq!(seen(&early), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+}
+
+fn throwing_mapper() -> Function {
+    Function::new_with_args("x", "if (x === 2) throw new Error('boom'); return x * 10;")
+}
+
+#[wasm_bindgen_test]
+async fn array_operator_errors_reach_the_error_callback() {
+    let river = numbers([1.0, 2.0, 3.0]).map(throwing_mapper());
+    let mut subscription = subscribe(&river);
+    (&mut subscription.completed).await.unwrap();
+    let values: Vec<f64> = subscription.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(values, vec![10.0, 30.0]);
+    let errors = subscription.errors.borrow();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(js_sys::Error::from(errors[0].clone()).message(), "boom");
+}
+
+#[wasm_bindgen_test]
+async fn array_operator_errors_terminate_when_asked() {
+    let river = numbers([1.0, 2.0, 3.0]).map(throwing_mapper()).terminate_on_error();
+    let mut subscription = subscribe(&river);
+    settle().await;
+    assert_eq!(subscription.values.borrow().len(), 1);
+    assert_eq!(subscription.errors.borrow().len(), 1);
+    assert_eq!(subscription.completed.try_recv(), Ok(None));
+}
+
+#[wasm_bindgen_test]
+async fn rescue_replaces_values_whose_operator_threw() {
+    let recover = Function::new_with_args("e", "return e.message === 'boom' ? -1 : undefined;");
+    let river = numbers([1.0, 2.0, 3.0]).map(throwing_mapper()).rescue(recover);
+    let subscription = subscribe(&river);
+    subscription.completed.await.unwrap();
+    let values: Vec<f64> = subscription.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(values, vec![10.0, -1.0, 30.0]);
+    assert!(subscription.errors.borrow().is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn credits_throttle_a_fast_source_without_dropping_values() {
+    let credits = CreditManager::new(2);
+    let mut subscription = subscribe_with_credits(&numbers((0..10).map(f64::from)), &credits);
+    settle().await;
+    assert_eq!(subscription.values.borrow().len(), 2);
+    assert_eq!(credits.pending_waiters(), 1);
+
+    let mut consumer = credits.clone();
+    consumer.add(3);
+    settle().await;
+    assert_eq!(subscription.values.borrow().len(), 5);
+    assert_eq!(subscription.completed.try_recv(), Ok(None));
+
+    consumer.add(100);
+    (&mut subscription.completed).await.unwrap();
+    let values: Vec<f64> = subscription.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(values, (0..10).map(f64::from).collect::<Vec<_>>());
+    assert_eq!(credits.available(), 95);
+}
+
+fn batches(values: &[JsValue]) -> Vec<Vec<f64>> {
+    values.iter().map(counts).collect()
+}
+
+#[wasm_bindgen_test]
+async fn buffer_time_emits_values_from_one_window_together() {
+    let (river, sender) = channel();
+    let subscription = subscribe(&river.buffer_time(50, false));
+    sender.unbounded_send(RiverValue::Number(1.0)).unwrap();
+    sender.unbounded_send(RiverValue::Number(2.0)).unwrap();
+    sleep(80).await;
+    assert_eq!(batches(&subscription.values.borrow()), vec![vec![1.0, 2.0]]);
+
+    sender.unbounded_send(RiverValue::Number(3.0)).unwrap();
+    drop(sender);
+    subscription.completed.await.unwrap();
+    assert_eq!(batches(&subscription.values.borrow()), vec![vec![1.0, 2.0], vec![3.0]]);
+}
+
+#[wasm_bindgen_test]
+async fn buffer_time_can_emit_empty_windows() {
+    let (river, _sender) = channel();
+    let quiet = subscribe(&river.buffer_time(20, false));
+    let empty = subscribe(&river.buffer_time(20, true));
+    sleep(70).await;
+    assert!(quiet.values.borrow().is_empty());
+    assert!(empty.values.borrow().len() >= 2);
+    assert!(batches(&empty.values.borrow()).iter().all(Vec::is_empty));
+    quiet._handle.unsubscribe();
+    empty._handle.unsubscribe();
+}
+
+#[wasm_bindgen_test]
+async fn delay_shifts_values_and_completes_after_the_last() {
+    let (river, sender) = channel();
+    let subscription = subscribe(&river.delay(30));
+    for x in 1..=3 {
+        sender.unbounded_send(RiverValue::Number(f64::from(x))).unwrap();
+        sleep(5).await;
+    }
+    drop(sender);
+    settle().await;
+    assert!(subscription.values.borrow().is_empty());
+
+    subscription.completed.await.unwrap();
+    let values: Vec<f64> = subscription.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(values, vec![1.0, 2.0, 3.0]);
+}
+
+#[wasm_bindgen_test]
+async fn unsubscribe_drops_values_still_delayed() {
+    let (river, sender) = channel();
+    let subscription = subscribe(&river.delay(20));
+    sender.unbounded_send(RiverValue::Number(1.0)).unwrap();
+    sleep(40).await;
+    sender.unbounded_send(RiverValue::Number(2.0)).unwrap();
+    settle().await;
+    subscription._handle.unsubscribe();
+    sleep(40).await;
+    let values: Vec<f64> = subscription.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(values, vec![1.0]);
+}
+
+// Resolves `x * 10` after a delay that shrinks as `x` grows, so later values
+// settle first.
+fn slower_for_smaller() -> Function {
+    Function::new_with_args("x", "return new Promise(resolve => setTimeout(() => resolve(x * 10), 40 - x * 10));")
+}
+
+#[wasm_bindgen_test]
+async fn map_async_concat_keeps_input_order() {
+    let river = numbers([1.0, 2.0, 3.0]).map_async(slower_for_smaller(), AsyncMapOrder::Concat);
+    assert_eq!(collect_f64(&river).await, vec![10.0, 20.0, 30.0]);
+}
+
+#[wasm_bindgen_test]
+async fn map_async_merge_emits_as_promises_resolve() {
+    let river = numbers([1.0, 2.0, 3.0]).map_async(slower_for_smaller(), AsyncMapOrder::Merge(3));
+    assert_eq!(collect_f64(&river).await, vec![30.0, 20.0, 10.0]);
+    assert!("parallel".parse::<AsyncMapOrder>().is_err());
+}
+
+#[wasm_bindgen_test]
+async fn map_async_merge_bounds_calls_in_flight() {
+    let calls = js("return { now: 0, max: 0 }");
+    let mapper = Function::new_with_args(
+        "calls",
+        "return x => { calls.now++; calls.max = Math.max(calls.max, calls.now); \
+         return new Promise(resolve => setTimeout(() => { calls.now--; resolve(x); }, 5)); };",
+    )
+    .call1(&JsValue::NULL, &calls)
+    .unwrap();
+    let river = numbers((0..10).map(f64::from)).map_async(mapper.unchecked_into(), AsyncMapOrder::Merge(3));
+    assert_eq!(collect_f64(&river).await.len(), 10);
+    assert_eq!(js_sys::Reflect::get(&calls, &"max".into()).unwrap(), 3.0);
+}
+
+#[wasm_bindgen_test]
+async fn map_async_rejections_reach_the_error_callback() {
+    let mapper = Function::new_with_args("x", "return x === 2 ? Promise.reject(new Error('boom')) : Promise.resolve(x);");
+    let subscription = subscribe(&numbers([1.0, 2.0, 3.0]).map_async(mapper, AsyncMapOrder::Concat));
+    subscription.completed.await.unwrap();
+    let values: Vec<f64> = subscription.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(values, vec![1.0, 3.0]);
+    assert_eq!(js_sys::Error::from(subscription.errors.borrow()[0].clone()).message(), "boom");
+}
+
+#[wasm_bindgen_test]
+async fn running_aggregates_emit_with_each_value() {
+    let river = numbers([2.0, 4.0, 6.0]).running_mean();
+    assert_eq!(collect_f64(&river).await, vec![2.0, 3.0, 4.0]);
+    assert_eq!(collect_f64(&river).await, vec![2.0, 3.0, 4.0]);
+
+    let input = numbers([3.0, -1.0, 5.0]);
+    assert_eq!(collect_f64(&input.running_sum()).await, vec![3.0, 2.0, 7.0]);
+    assert_eq!(collect_f64(&input.running_min()).await, vec![3.0, -1.0, -1.0]);
+    assert_eq!(collect_f64(&input.running_max()).await, vec![3.0, 3.0, 5.0]);
+}
+
+#[wasm_bindgen_test]
+async fn skip_errors_drops_failing_elements_and_continues() {
+    let reported = Rc::new(RefCell::new(Vec::new()));
+    let sink = reported.clone();
+    let on_error = Closure::<dyn FnMut(JsValue)>::new(move |e| sink.borrow_mut().push(e));
+    let on_error: Function = on_error.into_js_value().unchecked_into();
+
+    let river = numbers([1.0, 2.0, 3.0]).map(throwing_mapper()).skip_errors(Some(on_error));
+    let subscription = subscribe(&river);
+    subscription.completed.await.unwrap();
+    let values: Vec<f64> = subscription.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(values, vec![10.0, 30.0]);
+    assert!(subscription.errors.borrow().is_empty());
+    assert_eq!(reported.borrow().len(), 1);
+
+    // The modes replace each other: terminating after skipping stops at the error.
+    let terminating = subscribe(&river.terminate_on_error());
+    settle().await;
+    assert_eq!(terminating.values.borrow().len(), 1);
+    assert_eq!(terminating.errors.borrow().len(), 1);
+}
+
+#[wasm_bindgen_test]
+async fn channel_sender_reports_unread_values() {
+    let (river, sender) = RiverCore::channel();
+    assert!(sender.is_empty());
+    for x in 1..=3 {
+        assert!(sender.push(RiverValue::Number(f64::from(x))));
+    }
+    assert_eq!(sender.len(), 3);
+
+    let subscription = subscribe(&river);
+    settle().await;
+    assert_eq!(sender.len(), 0);
+    assert_eq!(subscription.values.borrow().len(), 3);
+
+    sender.clone().push(RiverValue::Number(4.0));
+    sender.close();
+    assert!(sender.is_closed());
+    assert!(!sender.push(RiverValue::Number(5.0)));
+    subscription.completed.await.unwrap();
+    assert_eq!(subscription.values.borrow().len(), 4);
+    assert_eq!(sender.len(), 0);
+}
+
+#[wasm_bindgen_test]
+async fn subject_drives_observers_until_complete() {
+    let subject = nagare::Subject::new();
+    let subscription = record(|next, error, complete| subject.observe(next, Some(error), Some(complete), None));
+    subject.next(JsValue::from_f64(1.0));
+    subject.next(JsValue::from_f64(2.0));
+    settle().await;
+    assert_eq!(subscription.values.borrow().len(), 2);
+
+    subject.complete();
+    subject.complete();
+    subject.error(JsValue::from_str("ignored"));
+    subject.next(JsValue::from_f64(3.0));
+    assert!(subject.is_closed());
+    subscription.completed.await.unwrap();
+    assert_eq!(subscription.values.borrow().len(), 2);
+    assert!(subscription.errors.borrow().is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn subject_error_is_reported_after_queued_values() {
+    let subject = nagare::Subject::new();
+    subject.next(JsValue::from_f64(1.0));
+    subject.error(JsValue::from_str("broken"));
+    subject.error(JsValue::from_str("again"));
+    subject.complete();
+
+    let subscription = record(|next, error, complete| subject.observe(next, Some(error), Some(complete), None));
+    subscription.completed.await.unwrap();
+    assert_eq!(subscription.values.borrow().len(), 1);
+    let errors = subscription.errors.borrow();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(cause(&errors[0]).as_string().as_deref(), Some("broken"));
+}
+
+#[wasm_bindgen_test]
+async fn dedupe_window_drops_repeats_until_they_fall_out() {
+    let river = numbers([1.0, 2.0, 1.0, 3.0, 4.0, 1.0, 4.0]).dedupe_window(2, None);
+    assert_eq!(collect_f64(&river).await, vec![1.0, 2.0, 3.0, 4.0, 1.0]);
+    assert_eq!(collect_f64(&river.dedupe_window(0, None)).await.len(), 5);
+
+    let events = js("return [{ id: 'a' }, { id: 'b' }, { id: 'a' }, { id: 'c' }];");
+    let by_id = Function::new_with_args("event", "return event.id;");
+    let river = RiverCore::from_js_array(events.into()).dedupe_window(3, Some(by_id));
+    let ids: Vec<String> = collect(&river)
+        .await
+        .iter()
+        .map(|event| js_sys::Reflect::get(event, &"id".into()).unwrap().as_string().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["a", "b", "c"]);
+}
+
+// A stream whose every read takes 20ms, pulled only on demand.
+fn slow_stream(chunks: u32) -> web_sys::ReadableStream {
+    js(&format!(
+        "let i = 0; return new ReadableStream({{ async pull(controller) {{
+            await new Promise(resolve => setTimeout(resolve, 20));
+            if (i >= {chunks}) controller.close(); else controller.enqueue(i++);
+        }} }}, {{ highWaterMark: 0 }});"
+    ))
+    .unchecked_into()
+}
+
+#[wasm_bindgen_test]
+async fn prefetch_overlaps_reads_with_processing() {
+    // 20ms of async work per value.
+    let process = || Function::new_with_args("x", "return new Promise(resolve => setTimeout(() => resolve(x), 20));");
+    let timed = |river: RiverCore| async move {
+        let start = js_sys::Date::now();
+        let values = collect_f64(&river).await;
+        (values, js_sys::Date::now() - start)
+    };
+
+    let serial = RiverCore::from_readable_stream(slow_stream(6)).map_async(process(), AsyncMapOrder::Concat);
+    let (values, serial_ms) = timed(serial).await;
+    assert_eq!(values, vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+
+    let prefetched = RiverCore::from_readable_stream(slow_stream(6)).prefetch(2).map_async(process(), AsyncMapOrder::Concat);
+    let (values, prefetched_ms) = timed(prefetched).await;
+    assert_eq!(values, vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert!(prefetched_ms < serial_ms * 0.8, "{prefetched_ms}ms with prefetch vs {serial_ms}ms without");
+}
+
+#[wasm_bindgen_test]
+async fn concat_drains_the_first_river_before_the_second() {
+    let (first, sender) = channel();
+    let river = first.concat(&numbers([3.0, 4.0]));
+    let subscription = subscribe(&river);
+    sender.unbounded_send(RiverValue::Number(1.0)).unwrap();
+    sender.unbounded_send(RiverValue::Number(2.0)).unwrap();
+    settle().await;
+    assert_eq!(subscription.values.borrow().len(), 2);
+
+    drop(sender);
+    subscription.completed.await.unwrap();
+    let values: Vec<f64> = subscription.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(values, vec![1.0, 2.0, 3.0, 4.0]);
+}
+
+#[wasm_bindgen_test]
+async fn concat_stops_at_an_error_unless_skipping() {
+    let failing = numbers([1.0, 2.0, 3.0]).map(throwing_mapper());
+    let river = failing.concat(&numbers([4.0]));
+    let subscription = subscribe(&river);
+    subscription.completed.await.unwrap();
+    let values: Vec<f64> = subscription.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(values, vec![10.0]);
+    assert_eq!(subscription.errors.borrow().len(), 1);
+
+    assert_eq!(collect_f64(&river.skip_errors(None)).await, vec![10.0, 30.0, 4.0]);
+}
+
+#[wasm_bindgen_test]
+async fn partition_routes_each_value_to_exactly_one_branch() {
+    // A predicate with a side effect, to show it runs once per value.
+    let calls = Rc::new(RefCell::new(0));
+    let counter = calls.clone();
+    let is_even = Closure::<dyn FnMut(JsValue) -> bool>::new(move |x: JsValue| {
+        *counter.borrow_mut() += 1;
+        x.as_f64().unwrap() % 2.0 == 0.0
+    });
+    let (even, odd) = numbers((1..=9).map(f64::from)).partition(is_even.into_js_value().unchecked_into());
+
+    let mut evens = subscribe(&even);
+    let mut odds = subscribe(&odd);
+    (&mut evens.completed).await.unwrap();
+    (&mut odds.completed).await.unwrap();
+    let seen = |s: &Subscription| s.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect::<Vec<_>>();
+    assert_eq!(seen(&evens), vec![2.0, 4.0, 6.0, 8.0]);
+    assert_eq!(seen(&odds), vec![1.0, 3.0, 5.0, 7.0, 9.0]);
+    assert_eq!(*calls.borrow(), 9);
+}
+
+#[wasm_bindgen_test]
+async fn sliding_window_emits_overlapping_full_windows() {
+    let river = numbers((0..5).map(f64::from));
+    let windows = collect(&river.sliding_window(3, 1).unwrap()).await;
+    assert_eq!(batches(&windows), vec![vec![0.0, 1.0, 2.0], vec![1.0, 2.0, 3.0], vec![2.0, 3.0, 4.0]]);
+    let windows = collect(&river.sliding_window(3, 2).unwrap()).await;
+    assert_eq!(batches(&windows), vec![vec![0.0, 1.0, 2.0], vec![2.0, 3.0, 4.0]]);
+    assert!(collect(&river.sliding_window(6, 1).unwrap()).await.is_empty());
+    assert!(river.sliding_window(3, 0).is_err());
+}
+
+#[wasm_bindgen_test]
+async fn tap_error_sees_errors_without_handling_them() {
+    let tapped = Rc::new(RefCell::new(Vec::new()));
+    let sink = tapped.clone();
+    let tap = Closure::<dyn FnMut(JsValue)>::new(move |e| sink.borrow_mut().push(e));
+    let tap: Function = tap.into_js_value().unchecked_into();
+
+    let river = numbers([1.0, 2.0, 3.0]).map(throwing_mapper()).tap_error(tap.clone());
+    let subscription = subscribe(&river);
+    subscription.completed.await.unwrap();
+    assert_eq!(tapped.borrow().len(), 1);
+    assert_eq!(subscription.errors.borrow().len(), 1);
+    assert_eq!(subscription.values.borrow().len(), 2);
+
+    // Read errors from the source are tapped too.
+    let (source, sender) = RiverCore::channel();
+    sender.fail(JsValue::from_str("read failed"));
+    let subscription = subscribe(&source.tap_error(tap));
+    subscription.completed.await.unwrap();
+    assert_eq!(cause(&tapped.borrow()[1]).as_string().as_deref(), Some("read failed"));
+    assert_eq!(subscription.errors.borrow().len(), 1);
+}
+
+#[wasm_bindgen_test]
+async fn resample_emits_timestamped_grid_points() {
+    let samples: Array = [(0.0, 0.0), (25.0, 5.0)]
+        .iter()
+        .map(|&(t, value)| js(&format!("return {{ t: {}, value: {} }}", t, value)))
+        .collect();
+    let river = RiverCore::from_js_array(samples).resample(10, "linear").unwrap();
+    let points: Vec<_> = collect(&river).await.iter().map(|p| (field(p, "t"), field(p, "value"))).collect();
+    assert_eq!(points, vec![(0.0, 0.0), (10.0, 2.0), (20.0, 4.0)]);
+
+    let subscription = subscribe(&numbers([1.0]).resample(10, "hold").unwrap());
+    subscription.completed.await.unwrap();
+    assert_eq!(subscription.errors.borrow().len(), 1);
+    assert!(numbers([]).resample(10, "spline").is_err());
+}
+
+#[wasm_bindgen_test]
+async fn every_registered_kernel_runs_through_map_wasm() {
+    let data = js_sys::Float32Array::from(&[1.0f32, -2.0, 3.0, 4.0][..]);
+    let params = js("return { a: 2, b: 1, scalar: 2, other: new Float32Array([1, 1, 1, 1]), window: 2, max_lag: 1, alpha: 0.5, lo: -1, hi: 1 }");
+    let river = RiverCore::from_js_array(Array::of1(&data));
+
+    let names = nagare::kernels::kernel_names();
+    assert!(names.iter().any(|name| name == "f32x_rolling_mean"));
+    for name in &names {
+        let output = collect(&river.map_wasm(name, params.clone()).unwrap()).await;
+        assert_eq!(output.len(), 1, "{} emitted nothing", name);
+    }
+
+    let output = collect(&river.map_wasm("f32x_dot_product", params.clone()).unwrap()).await;
+    assert_eq!(output[0].as_f64(), Some(6.0));
+    let output = collect(&river.map_wasm("f32x_min_max", params.clone()).unwrap()).await;
+    assert_eq!(js_sys::Float32Array::from(output[0].clone()).to_vec(), vec![-2.0, 4.0]);
+}
+
+// Pipes ten values that are all pushed up front through a three-value buffer
+// to an observer that has no credits until the pipe has settled.
+async fn pipe_to_stalled_observer(overflow: &str) -> (Vec<f64>, usize) {
+    let (river, sender) = RiverCore::channel();
+    for x in 0..10 {
+        sender.push(RiverValue::Number(f64::from(x)));
+    }
+    sender.close();
+    let credits = CreditManager::new(0);
+    let subscription = subscribe_with_credits(&river.pipe_through(3, overflow).unwrap(), &credits);
+    settle().await;
+    assert!(subscription.values.borrow().is_empty());
+    let unread = sender.len();
+
+    credits.clone().add(10);
+    subscription.completed.await.unwrap();
+    let values = subscription.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect();
+    (values, unread)
+}
+
+#[wasm_bindgen_test]
+async fn pipe_through_drop_oldest_keeps_the_latest_values() {
+    assert_eq!(pipe_to_stalled_observer("drop_oldest").await, (vec![7.0, 8.0, 9.0], 0));
+}
+
+#[wasm_bindgen_test]
+async fn pipe_through_drop_newest_keeps_the_earliest_values() {
+    assert_eq!(pipe_to_stalled_observer("drop_newest").await, (vec![0.0, 1.0, 2.0], 0));
+}
+
+#[wasm_bindgen_test]
+async fn pipe_through_block_holds_upstream_back() {
+    // Three values buffered, one held by the observer waiting for credit and
+    // one read by the producer waiting for a free slot.
+    let (values, unread) = pipe_to_stalled_observer("block").await;
+    assert_eq!(values, (0..10).map(f64::from).collect::<Vec<_>>());
+    assert_eq!(unread, 5);
+    assert!(numbers([]).pipe_through(3, "spill").is_err());
+}
+
+#[wasm_bindgen_test]
+async fn pre_aborted_signal_reads_nothing() {
+    let controller = web_sys::AbortController::new().unwrap();
+    controller.abort();
+    let (river, sender) = RiverCore::channel();
+    sender.push(RiverValue::Number(1.0));
+    // `prefetch` would start reading as soon as the output is built.
+    let river = river.prefetch(4);
+    let subscription = record(|next, error, complete| {
+        river.observe(next, Some(error), Some(complete), Some(controller.signal()))
+    });
+    settle().await;
+    assert!(subscription.values.borrow().is_empty());
+    assert_eq!(sender.len(), 1);
+}
+
+#[wasm_bindgen_test]
+async fn timeout_signal_stops_an_interval() {
+    let signal = RiverCore::timeout_signal(30).unwrap();
+    assert!(!signal.aborted());
+    let subscription = record(|next, error, complete| {
+        RiverCore::interval(5).observe(next, Some(error), Some(complete), Some(signal.clone()))
+    });
+
+    sleep(50).await;
+    assert!(signal.aborted());
+    let reason: js_sys::Error = signal.reason().unchecked_into();
+    assert_eq!(reason.name(), "TimeoutError");
+    let emitted = subscription.values.borrow().len();
+    assert!(emitted > 0);
+    sleep(30).await;
+    assert_eq!(subscription.values.borrow().len(), emitted);
+}
+
+#[wasm_bindgen_test]
+async fn float32_array_chunks_feed_simd_kernels() {
+    let data: Vec<f32> = (1..=8).map(|x| x as f32).collect();
+    let river = RiverCore::from_float32_array(&js_sys::Float32Array::from(&data[..]), Some(3));
+    let doubled = collect(&river.map_wasm("f32x_scalar_mul", js("return { scalar: 2 }")).unwrap()).await;
+    let chunks: Vec<Vec<f32>> = doubled.iter().map(|c| js_sys::Float32Array::from(c.clone()).to_vec()).collect();
+    assert_eq!(chunks, vec![vec![2.0, 4.0, 6.0], vec![8.0, 10.0, 12.0], vec![14.0, 16.0]]);
+    assert_eq!(collect_f64(&river.map_wasm("f32x_sum", JsValue::UNDEFINED).unwrap()).await, vec![6.0, 15.0, 15.0]);
+
+    let whole = RiverCore::from_float32_array(&js_sys::Float32Array::from(&data[..]), None);
+    assert_eq!(collect_f64(&whole.map_wasm("f32x_sum", JsValue::UNDEFINED).unwrap()).await, vec![36.0]);
+}
+
+#[wasm_bindgen_test]
+async fn errors_carry_a_code_and_recoverability() {
+    let info = |error: &JsValue| ErrorInfo::from_js(error).expect("a RiverError");
+
+    let subscription = subscribe(&numbers([2.0]).map(throwing_mapper()));
+    subscription.completed.await.unwrap();
+    let thrown = info(&subscription.errors.borrow()[0]);
+    assert_eq!((thrown.code, thrown.recoverable), (errors::OPERATOR_THREW, false));
+    assert_eq!(thrown.message, "boom");
+
+    let timing_out = js("return () => { const e = new Error('too slow'); e.name = 'TimeoutError'; return Promise.reject(e); }");
+    let subscription = subscribe(&numbers([1.0]).map_async(timing_out.unchecked_into(), AsyncMapOrder::Concat));
+    subscription.completed.await.unwrap();
+    let timeout = info(&subscription.errors.borrow()[0]);
+    assert_eq!((timeout.code, timeout.recoverable), (errors::TIMEOUT, true));
+
+    let (source, sender) = RiverCore::channel();
+    sender.fail(JsValue::from_str("read failed"));
+    let subscription = subscribe(&source);
+    subscription.completed.await.unwrap();
+    let read = info(&subscription.errors.borrow()[0]);
+    assert_eq!((read.code, read.recoverable), (errors::READ_ERROR, true));
+}
+
+#[wasm_bindgen_test]
+async fn gate_forwards_only_with_credit() {
+    let mut credits = CreditManager::new(0);
+    let (river, sender) = RiverCore::channel();
+    let subscription = subscribe(&river.gate(&credits, "drop").unwrap().map(Function::new_with_args("x", "return x * 10")));
+    for x in 1..=3 {
+        sender.push(RiverValue::Number(f64::from(x)));
+    }
+    settle().await;
+    assert!(subscription.values.borrow().is_empty());
+
+    credits.add(1);
+    for x in 4..=5 {
+        sender.push(RiverValue::Number(f64::from(x)));
+    }
+    settle().await;
+    let values: Vec<f64> = subscription.values.borrow().iter().map(|v| v.as_f64().unwrap()).collect();
+    assert_eq!(values, vec![40.0]);
+    assert_eq!(credits.available(), 0);
+
+    // "buffer" holds the value until a credit arrives.
+    let (river, sender) = RiverCore::channel();
+    let subscription = subscribe(&river.gate(&credits, "buffer").unwrap());
+    sender.push(RiverValue::Number(6.0));
+    settle().await;
+    assert!(subscription.values.borrow().is_empty());
+    credits.add(1);
+    settle().await;
+    assert_eq!(subscription.values.borrow().len(), 1);
+    assert!(river.gate(&credits, "wait").is_err());
+}
+
+#[wasm_bindgen_test]
+async fn map_wasm_validates_params_when_built() {
+    let river = RiverCore::from_float32_array(&js_sys::Float32Array::from(&[1.0f32, 2.0][..]), None);
+    let message = |params: &str, kernel: &str| river.map_wasm(kernel, js(params)).err().unwrap().as_string().unwrap();
+
+    assert_eq!(message("return { a: 2 }", "f32x_map_mul_add"), "f32x_map_mul_add: missing parameter `b`");
+    assert_eq!(message("return { a: 2, b: '1' }", "f32x_map_mul_add"), "f32x_map_mul_add: parameter `b` must be a number");
+    assert_eq!(message("return { window: 1.5 }", "f32x_rolling_mean"), "f32x_rolling_mean: parameter `window` must be a positive integer");
+    assert_eq!(message("return { window: 0 }", "f32x_rolling_mean"), "f32x_rolling_mean: parameter `window` must be a positive integer");
+    assert_eq!(message("return { other: [1, 2] }", "f32x_vector_add"), "f32x_vector_add: parameter `other` must be a Float32Array");
+    assert_eq!(message("return {}", "f32x_unknown"), "Unknown kernel: f32x_unknown");
+
+    // A well-formed operand of the wrong length can only fail per value.
+    let mismatched = river.map_wasm("f32x_vector_add", js("return { other: new Float32Array(3) }")).unwrap();
+    let subscription = subscribe(&mismatched);
+    subscription.completed.await.unwrap();
+    let error = ErrorInfo::from_js(&subscription.errors.borrow()[0]).unwrap();
+    assert_eq!(error.message, "f32x_vector_add: `other` has 3 elements, expected 2");
+}
+
+#[cfg(feature = "serialization")]
+fn frame_bytes(sequences: std::ops::Range<u64>) -> Vec<u8> {
+    use nagare::serialization::{Frame, FrameCodec, Payload};
+    sequences
+        .flat_map(|sequence| {
+            let frame = Frame { sequence, timestamp: 1, payload: Payload::Float32Array(vec![sequence as f32]) };
+            FrameCodec::encode(&frame).unwrap()
+        })
+        .collect()
+}
+
+// A byte stream enqueueing one chunk per pull, counting the pulls in `pulls.count`.
+#[cfg(feature = "serialization")]
+fn pulled_byte_stream(chunks: Vec<Vec<u8>>, pulls: &js_sys::Object) -> web_sys::ReadableStream {
+    let chunks: Array = chunks.iter().map(|chunk| JsValue::from(js_sys::Uint8Array::from(&chunk[..]))).collect();
+    let source = Function::new_with_args(
+        "chunks, pulls",
+        "pulls.count = 0; return { pull(c) { pulls.count++; chunks.length ? c.enqueue(chunks.shift()) : c.close(); } };",
+    )
+    .call2(&JsValue::NULL, &chunks, pulls)
+    .unwrap();
+    web_sys::ReadableStream::new_with_underlying_source(source.unchecked_ref()).unwrap()
+}
+
+#[cfg(feature = "serialization")]
+#[wasm_bindgen_test]
+async fn frame_stream_decodes_frames_split_across_chunks() {
+    use nagare::frame_stream::FrameStream;
+
+    // Chunk boundaries fall inside length prefixes and payloads.
+    let bytes = frame_bytes(0..10);
+    let pulls = js_sys::Object::new();
+    let stream = pulled_byte_stream(bytes.chunks(7).map(<[u8]>::to_vec).collect(), &pulls);
+    let river = FrameStream::pipe(&stream, 1, nagare::serialization::DEFAULT_MAX_PAYLOAD_BYTES);
+
+    // Unobserved, reading stops once the channel is full.
+    settle().await;
+    let read_ahead = js_sys::Reflect::get(&pulls, &"count".into()).unwrap().as_f64().unwrap();
+    assert!(read_ahead < (bytes.len() / 7) as f64, "read {} chunks ahead", read_ahead);
+
+    let sequences: Vec<f64> = collect(&river)
+        .await
+        .iter()
+        .map(|frame| js_sys::Reflect::get(frame, &"sequence".into()).unwrap().as_f64().unwrap())
+        .collect();
+    assert_eq!(sequences, (0..10).map(f64::from).collect::<Vec<_>>());
+}
+
+#[cfg(feature = "serialization")]
+#[wasm_bindgen_test]
+async fn frame_stream_reports_a_truncated_last_frame() {
+    let mut bytes = frame_bytes(0..2);
+    bytes.truncate(bytes.len() - 1);
+    let stream = pulled_byte_stream(vec![bytes], &js_sys::Object::new());
+    let subscription = subscribe(&nagare::frame_stream::FrameStream::pipe(&stream, 4, nagare::serialization::DEFAULT_MAX_PAYLOAD_BYTES));
+    subscription.completed.await.unwrap();
+    assert_eq!(subscription.values.borrow().len(), 1);
+    let error = ErrorInfo::from_js(&subscription.errors.borrow()[0]).unwrap();
+    assert_eq!(error.code, errors::READ_ERROR);
+    assert!(error.message.starts_with("Truncated frame"), "{}", error.message);
+}
+
+#[cfg(feature = "serialization")]
+#[wasm_bindgen_test]
+async fn frame_stream_delivers_frames_ahead_of_a_corrupt_one() {
+    let mut bytes = frame_bytes(0..2);
+    bytes.extend_from_slice(&[0, 0, 0, 3, 1, 1, 0xff]);
+    bytes.extend(frame_bytes(2..3));
+    let stream = pulled_byte_stream(vec![bytes], &js_sys::Object::new());
+    let subscription = subscribe(&nagare::frame_stream::FrameStream::pipe(&stream, 4, nagare::serialization::DEFAULT_MAX_PAYLOAD_BYTES));
+    subscription.completed.await.unwrap();
+    assert_eq!(subscription.values.borrow().len(), 2);
+    let error = ErrorInfo::from_js(&subscription.errors.borrow()[0]).unwrap();
+    assert!(error.message.starts_with("Deserialization error"), "{}", error.message);
+}
+
+#[wasm_bindgen_test]
+async fn map_indexed_counts_from_zero_per_subscription() {
+    let river = numbers([10.0, 20.0, 30.0]).map_indexed(Function::new_with_args("x, i", "return x * 100 + i"));
+    assert_eq!(collect_f64(&river).await, vec![1000.0, 2001.0, 3002.0]);
+    assert_eq!(collect_f64(&river).await, vec![1000.0, 2001.0, 3002.0]);
+
+    // Values filtered out before it are not counted.
+    let indices = numbers([1.0, 2.0, 3.0, 4.0]).filter(is_even()).map_indexed(Function::new_with_args("x, i", "return i"));
+    assert_eq!(collect_f64(&indices).await, vec![0.0, 1.0]);
+}
+
+#[wasm_bindgen_test]
+async fn profiling_counts_calls_per_operator() {
+    let double = Function::new_with_args("x", "return x * 2");
+    let river = numbers([1.0, 2.0, 3.0, 4.0, 5.0]).map(double.clone()).filter(Function::new_with_args("x", "return x > 6"));
+    let river = river.delay(0).map(double).enable_profiling();
+    let subscription = subscribe(&river);
+    subscription.completed.await.unwrap();
+
+    let profile = subscription._handle.profile();
+    let calls: Vec<(&str, u64)> = profile.iter().map(|(key, timing)| (key.as_str(), timing.calls)).collect();
+    // `delay` is a stream stage and is not timed.
+    assert_eq!(calls, vec![("filter#1", 5), ("map#0", 5), ("map#3", 2)]);
+    assert!(profile.values().all(|timing| timing.total_ms >= 0.0));
+
+    // Each subscription starts from zero; unprofiled rivers record nothing.
+    let again = subscribe(&river);
+    again.completed.await.unwrap();
+    assert_eq!(again._handle.profile()["map#0"].calls, 5);
+    let plain = subscribe(&numbers([1.0]).map(Function::new_with_args("x", "return x")));
+    plain.completed.await.unwrap();
+    assert!(plain._handle.profile().is_empty());
+}
+
+#[wasm_bindgen_test]
+async fn distinct_emits_each_value_once() {
+    let river = numbers([1.0, 2.0, 1.0, 3.0, 2.0, 4.0, 1.0, 0.0, -0.0]).distinct(None, 100);
+    assert_eq!(collect_f64(&river).await, vec![1.0, 2.0, 3.0, 4.0, 0.0]);
+
+    // Objects compare by identity, or by the selected key.
+    let objects = js("const a = { id: 1 }; return [a, a, { id: 1 }, { id: 2 }]");
+    let river = RiverCore::from_js_array(objects.unchecked_into());
+    assert_eq!(collect(&river.distinct(None, 100)).await.len(), 3);
+    let by_id = river.distinct(Some(Function::new_with_args("x", "return x.id")), 100);
+    assert_eq!(collect(&by_id).await.len(), 2);
+}
+
+#[wasm_bindgen_test]
+async fn distinct_evicts_the_least_recently_seen_key_at_the_cap() {
+    // Adding 3 evicts 1, which then passes again.
+    let river = numbers([1.0, 2.0, 3.0, 1.0]).distinct(None, 2);
+    assert_eq!(collect_f64(&river).await, vec![1.0, 2.0, 3.0, 1.0]);
+
+    // Seeing 1 again makes 2 the least recently seen, so 2 is evicted instead.
+    let river = numbers([1.0, 2.0, 1.0, 3.0, 1.0, 2.0]).distinct(None, 2);
+    assert_eq!(collect_f64(&river).await, vec![1.0, 2.0, 3.0, 2.0]);
+}
+
+#[wasm_bindgen_test]
+async fn changes_emits_when_the_selected_key_changes() {
+    let readings = js("return [['idle', 1], ['idle', 2], ['busy', 3], ['busy', 4], ['busy', 5], ['idle', 6], ['idle', 7]]
+        .map(([state, at]) => ({ state, at }))");
+    let river = RiverCore::from_js_array(readings.unchecked_into()).changes(Function::new_with_args("x", "return x.state"));
+    let emitted: Vec<f64> = collect(&river)
+        .await
+        .iter()
+        .map(|reading| js_sys::Reflect::get(reading, &"at".into()).unwrap().as_f64().unwrap())
+        .collect();
+    assert_eq!(emitted, vec![1.0, 3.0, 6.0]);
+}
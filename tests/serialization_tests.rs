@@ -0,0 +1,384 @@
+#![cfg(all(target_arch = "wasm32", feature = "serialization"))]
+
+use js_sys::{Object, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_test::*;
+
+fn frame(payload: &Object) -> JsValue {
+    let frame = Object::new();
+    Reflect::set(&frame, &"sequence".into(), &JsValue::from(7.0)).unwrap();
+    Reflect::set(&frame, &"timestamp".into(), &JsValue::from(1234.0)).unwrap();
+    Reflect::set(&frame, &"payload".into(), payload).unwrap();
+    frame.into()
+}
+
+fn get(value: &JsValue, key: &str) -> JsValue {
+    Reflect::get(value, &key.into()).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn exported_codec_round_trips_float32_frames() {
+    let payload = Object::new();
+    Reflect::set(&payload, &"type".into(), &"float32".into()).unwrap();
+    let data = js_sys::Float32Array::from(&[1.5f32, -2.0, 3.25][..]);
+    Reflect::set(&payload, &"data".into(), &data).unwrap();
+
+    let bytes = nagare::encode_postcard(frame(&payload)).unwrap();
+    assert!(bytes.length() > 0);
+
+    let decoded = nagare::decode_postcard(&bytes).unwrap();
+    assert_eq!(get(&decoded, "sequence"), 7.0);
+    let payload = get(&decoded, "payload");
+    assert_eq!(get(&payload, "type"), "float32");
+    let floats: js_sys::Float32Array = wasm_bindgen::JsCast::unchecked_into(get(&payload, "data"));
+    assert_eq!(floats.to_vec(), vec![1.5, -2.0, 3.25]);
+}
+
+#[wasm_bindgen_test]
+fn decode_rejects_garbage() {
+    let bytes = js_sys::Uint8Array::from(&[0xff, 0xff, 0xff][..]);
+    assert!(nagare::decode_postcard(&bytes).is_err());
+}
+
+fn typed_payload(kind: &str, data: &JsValue) -> Object {
+    let payload = Object::new();
+    Reflect::set(&payload, &"type".into(), &kind.into()).unwrap();
+    Reflect::set(&payload, &"data".into(), data).unwrap();
+    payload
+}
+
+#[wasm_bindgen_test]
+fn float64_payload_round_trips_bit_for_bit() {
+    let values = [0.1f64, -0.0, f64::MAX, f64::MIN_POSITIVE, f64::NAN, 1e-300];
+    let data = js_sys::Float64Array::from(&values[..]);
+
+    let bytes = nagare::encode_postcard(frame(&typed_payload("float64", &data))).unwrap();
+    let payload = get(&nagare::decode_postcard(&bytes).unwrap(), "payload");
+    assert_eq!(get(&payload, "type"), "float64");
+
+    let decoded: js_sys::Float64Array = wasm_bindgen::JsCast::unchecked_into(get(&payload, "data"));
+    let bits: Vec<u64> = decoded.to_vec().iter().map(|x| x.to_bits()).collect();
+    assert_eq!(bits, values.iter().map(|x| x.to_bits()).collect::<Vec<_>>());
+}
+
+#[wasm_bindgen_test]
+fn int32_payload_round_trips() {
+    let values = [i32::MIN, -1, 0, 1, i32::MAX];
+    let data = js_sys::Int32Array::from(&values[..]);
+
+    let bytes = nagare::encode_postcard(frame(&typed_payload("int32", &data))).unwrap();
+    let payload = get(&nagare::decode_postcard(&bytes).unwrap(), "payload");
+    assert_eq!(get(&payload, "type"), "int32");
+
+    let decoded: js_sys::Int32Array = wasm_bindgen::JsCast::unchecked_into(get(&payload, "data"));
+    assert_eq!(decoded.to_vec(), values);
+}
+
+#[wasm_bindgen_test]
+fn typed_payload_rejects_mismatched_array() {
+    let data = js_sys::Float32Array::new_with_length(2);
+    assert!(nagare::encode_postcard(frame(&typed_payload("float64", &data))).is_err());
+}
+
+fn data_frame(sequence: u32) -> JsValue {
+    let payload = typed_payload("data", &js_sys::Uint8Array::from(&sequence.to_le_bytes()[..]));
+    let frame = frame(&payload);
+    Reflect::set(&frame, &"sequence".into(), &JsValue::from(sequence)).unwrap();
+    frame
+}
+
+#[wasm_bindgen_test]
+fn frame_batches_round_trip() {
+    let frames: js_sys::Array = (0..1000).map(data_frame).collect();
+    let bytes = nagare::encode_frames(&frames).unwrap();
+
+    let decoded = nagare::decode_frames(&bytes).unwrap();
+    assert_eq!(decoded.length(), 1000);
+    for (i, frame) in decoded.iter().enumerate() {
+        assert_eq!(get(&frame, "sequence"), i as f64);
+        let data: js_sys::Uint8Array = wasm_bindgen::JsCast::unchecked_into(get(&get(&frame, "payload"), "data"));
+        assert_eq!(data.to_vec(), (i as u32).to_le_bytes());
+    }
+}
+
+#[wasm_bindgen_test]
+fn frame_batches_use_frame_codec_framing() {
+    use nagare::serialization::{FrameCodec, FrameDecoder};
+    let frames: js_sys::Array = (0..3).map(data_frame).collect();
+    let bytes = nagare::encode_frames(&frames).unwrap().to_vec();
+    let (decoded, error) = FrameDecoder::new().push(&bytes);
+    assert_eq!(error, None);
+    assert_eq!(sequences(&decoded), vec![0, 1, 2]);
+
+    let wire: Vec<u8> = sample_frames().iter().flat_map(|f| FrameCodec::encode(f).unwrap()).collect();
+    assert_eq!(nagare::decode_frames(&js_sys::Uint8Array::from(&wire[..])).unwrap().length(), 4);
+}
+
+#[wasm_bindgen_test]
+fn truncated_frame_batch_is_an_error() {
+    let frames: js_sys::Array = (0..3).map(data_frame).collect();
+    let bytes = nagare::encode_frames(&frames).unwrap();
+    for len in [2, bytes.length() - 1] {
+        let err = nagare::decode_frames(&bytes.slice(0, len)).unwrap_err();
+        assert!(err.as_string().unwrap().contains("Truncated"));
+    }
+}
+
+fn sample_frames() -> Vec<nagare::serialization::Frame> {
+    use nagare::serialization::{ControlMessage, Frame, Payload};
+    vec![
+        Frame { sequence: 1, timestamp: 10, payload: Payload::Data(vec![1, 2, 3]) },
+        Frame { sequence: 2, timestamp: 20, payload: Payload::Float32Array(vec![0.5; 40]) },
+        Frame { sequence: 3, timestamp: 30, payload: Payload::Control(ControlMessage::Credit(8)) },
+        Frame { sequence: 4, timestamp: 40, payload: Payload::Int32Array(vec![-7, 7]) },
+    ]
+}
+
+fn sequences(frames: &[nagare::serialization::Frame]) -> Vec<u64> {
+    frames.iter().map(|frame| frame.sequence).collect()
+}
+
+#[wasm_bindgen_test]
+fn frame_decoder_reassembles_any_split() {
+    use nagare::serialization::{FrameCodec, FrameDecoder};
+    let frames = sample_frames();
+    let wire: Vec<u8> = frames.iter().flat_map(|f| FrameCodec::encode(f).unwrap()).collect();
+
+    for chunk_size in [1, 2, 3, 5, 7, 64, wire.len()] {
+        let mut decoder = FrameDecoder::new();
+        let mut decoded = Vec::new();
+        for chunk in wire.chunks(chunk_size) {
+            let (frames, error) = decoder.push(chunk);
+            assert_eq!(error, None);
+            decoded.extend(frames);
+        }
+        assert_eq!(sequences(&decoded), vec![1, 2, 3, 4], "chunk size {}", chunk_size);
+        assert_eq!(decoder.buffered(), 0);
+    }
+}
+
+#[wasm_bindgen_test]
+fn frame_decoder_holds_partial_frames() {
+    use nagare::serialization::{FrameCodec, FrameDecoder};
+    let encoded = FrameCodec::encode(&sample_frames()[1]).unwrap();
+    assert_eq!(u32::from_be_bytes(encoded[..4].try_into().unwrap()) as usize, encoded.len() - 4);
+
+    let mut decoder = FrameDecoder::new();
+    assert!(decoder.push(&encoded[..10]).0.is_empty());
+    assert_eq!(decoder.buffered(), 10);
+    assert_eq!(sequences(&decoder.push(&encoded[10..]).0), vec![2]);
+}
+
+#[wasm_bindgen_test]
+fn frame_decoder_keeps_frames_ahead_of_a_corrupt_one() {
+    use nagare::serialization::{FrameCodec, FrameDecoder};
+    let frames = sample_frames();
+    let mut wire = FrameCodec::encode(&frames[0]).unwrap();
+    // A frame whose payload variant tag is cut off mid-varint.
+    wire.extend_from_slice(&[0, 0, 0, 3, 1, 1, 0xff]);
+    let tail = FrameCodec::encode(&frames[3]).unwrap();
+    wire.extend_from_slice(&tail);
+
+    let mut decoder = FrameDecoder::new();
+    let (decoded, error) = decoder.push(&wire);
+    assert_eq!(sequences(&decoded), vec![1]);
+    assert!(error.unwrap().contains("Deserialization error"));
+    assert_eq!(decoder.buffered(), tail.len());
+}
+
+#[wasm_bindgen_test]
+fn oversized_length_prefixes_are_rejected_before_buffering() {
+    use nagare::serialization::{FrameCodec, FrameDecoder};
+    // A prefix near 4 GiB followed by a few bytes of its "frame".
+    let prefix = [0xff, 0xff, 0xff, 0xf0, 1, 1, 1];
+
+    let mut decoder = FrameDecoder::new();
+    let (frames, error) = decoder.push(&prefix);
+    assert!(frames.is_empty());
+    assert!(error.unwrap().contains("exceeds limit"));
+    assert_eq!(decoder.buffered(), 0);
+
+    let err = nagare::decode_frames(&js_sys::Uint8Array::from(&prefix[..])).unwrap_err();
+    assert!(err.as_string().unwrap().contains("exceeds limit"));
+
+    // The limit is configurable; frames within it still decode.
+    let encoded = FrameCodec::encode(&sample_frames()[1]).unwrap();
+    assert!(FrameDecoder::with_max_payload_bytes(160).push(&encoded).1.is_none());
+    assert!(FrameDecoder::with_max_payload_bytes(100).push(&encoded).1.unwrap().contains("exceeds limit"));
+
+    let payload = typed_payload("float32", &js_sys::Float32Array::new_with_length(100));
+    let frames: js_sys::Array = [frame(&payload)].into_iter().collect();
+    let bytes = nagare::encode_frames(&frames).unwrap();
+    assert!(nagare::decode_frames_with_limit(&bytes, 400).is_ok());
+    assert!(nagare::decode_frames_with_limit(&bytes, 300).is_err());
+}
+
+#[wasm_bindgen_test]
+fn v2_frames_decode_alongside_v1() {
+    let payload = typed_payload("int32", &js_sys::Int32Array::from(&[1, 2, 3][..]));
+    let v1 = nagare::encode_postcard(frame(&payload)).unwrap();
+    let v2 = nagare::encode_postcard_v2(frame(&payload)).unwrap();
+    assert_eq!(v2.length(), v1.length() + 14);
+
+    for bytes in [v1, v2] {
+        let decoded = nagare::decode_postcard(&bytes).unwrap();
+        assert_eq!(get(&decoded, "sequence"), 7.0);
+    }
+}
+
+#[wasm_bindgen_test]
+fn corrupted_v2_frame_fails_checksum() {
+    let payload = typed_payload("int32", &js_sys::Int32Array::from(&[1, 2, 3][..]));
+    let bytes = nagare::encode_postcard_v2(frame(&payload)).unwrap();
+    let last = bytes.length() - 1;
+    bytes.set_index(last, bytes.get_index(last) ^ 0x01);
+
+    let err = nagare::decode_postcard(&bytes).unwrap_err();
+    assert_eq!(get(&err, "type"), "error");
+    assert_eq!(get(&err, "code"), nagare::serialization::CHECKSUM_MISMATCH as f64);
+    assert!(get(&err, "message").as_string().unwrap().contains("Checksum mismatch"));
+}
+
+#[wasm_bindgen_test]
+fn v2_marker_is_never_a_valid_v1_frame() {
+    let mut bytes = vec![0xFF; 9];
+    bytes.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0]);
+    assert!(postcard::from_bytes::<nagare::serialization::Frame>(&bytes).is_err());
+}
+
+#[cfg(feature = "compression")]
+#[wasm_bindgen_test]
+fn compressed_frames_round_trip() {
+    let values: Vec<f32> = (0..4096).map(|i| (i % 16) as f32).collect();
+    let payload = typed_payload("float32", &js_sys::Float32Array::from(&values[..]));
+    let plain = nagare::encode_postcard(frame(&payload)).unwrap();
+
+    for algo in ["none", "lz4", "deflate"] {
+        let bytes = nagare::encode_postcard_compressed(frame(&payload), algo).unwrap();
+        if algo != "none" {
+            assert!(bytes.length() < plain.length() / 4, "{} did not compress", algo);
+        }
+        let decoded = nagare::decode_postcard(&bytes).unwrap();
+        let data: js_sys::Float32Array = wasm_bindgen::JsCast::unchecked_into(get(&get(&decoded, "payload"), "data"));
+        assert_eq!(data.to_vec(), values, "{}", algo);
+    }
+}
+
+#[cfg(feature = "compression")]
+#[wasm_bindgen_test]
+fn incompressible_frames_are_stored_uncompressed() {
+    let payload = typed_payload("data", &js_sys::Uint8Array::from(&[9, 4, 1][..]));
+    let plain = nagare::encode_postcard(frame(&payload)).unwrap();
+    let bytes = nagare::encode_postcard_compressed(frame(&payload), "deflate").unwrap();
+    assert_eq!(bytes.length(), plain.length() + 11);
+    assert_eq!(bytes.get_index(10), 0);
+    assert!(nagare::encode_postcard_compressed(frame(&payload), "zstd").is_err());
+}
+
+#[cfg(feature = "json")]
+#[wasm_bindgen_test]
+fn json_matches_postcard() {
+    let payload = typed_payload("float64", &js_sys::Float64Array::from(&[0.25f64, -8.5][..]));
+    let value = frame(&payload);
+
+    let json = nagare::encode_json(value.clone()).unwrap();
+    assert!(json.contains(r#""Float64Array":[0.25,-8.5]"#), "{}", json);
+
+    let from_json = nagare::decode_json(&json).unwrap();
+    let from_postcard = nagare::decode_postcard(&nagare::encode_postcard(value).unwrap()).unwrap();
+    assert_eq!(
+        js_sys::JSON::stringify(&from_json).unwrap(),
+        js_sys::JSON::stringify(&from_postcard).unwrap()
+    );
+    assert!(nagare::decode_json("{\"sequence\":1}").is_err());
+}
+
+fn control_round_trip(message: &Object) -> JsValue {
+    let payload = Object::new();
+    Reflect::set(&payload, &"type".into(), &"control".into()).unwrap();
+    Reflect::set(&payload, &"message".into(), message).unwrap();
+    let bytes = nagare::encode_postcard(frame(&payload)).unwrap();
+    get(&get(&nagare::decode_postcard(&bytes).unwrap(), "payload"), "message")
+}
+
+fn control(kind: &str, field: Option<(&str, f64)>) -> Object {
+    let message = Object::new();
+    Reflect::set(&message, &"type".into(), &kind.into()).unwrap();
+    if let Some((key, value)) = field {
+        Reflect::set(&message, &key.into(), &JsValue::from(value)).unwrap();
+    }
+    message
+}
+
+#[wasm_bindgen_test]
+fn heartbeat_flush_and_seek_round_trip() {
+    let heartbeat = control_round_trip(&control("heartbeat", Some(("timestamp", 1_700_000_000_000.0))));
+    assert_eq!(get(&heartbeat, "type"), "heartbeat");
+    assert_eq!(get(&heartbeat, "timestamp"), 1_700_000_000_000.0);
+
+    let flush = control_round_trip(&control("flush", None));
+    assert_eq!(get(&flush, "type"), "flush");
+
+    let seek = control_round_trip(&control("seek", Some(("sequence", 42.0))));
+    assert_eq!(get(&seek, "type"), "seek");
+    assert_eq!(get(&seek, "sequence"), 42.0);
+}
+
+#[wasm_bindgen_test]
+fn seek_requires_a_sequence() {
+    let payload = Object::new();
+    Reflect::set(&payload, &"type".into(), &"control".into()).unwrap();
+    Reflect::set(&payload, &"message".into(), &control("seek", None)).unwrap();
+    assert!(nagare::encode_postcard(frame(&payload)).is_err());
+}
+
+#[wasm_bindgen_test]
+fn missing_timestamp_defaults_to_now() {
+    let payload = typed_payload("data", &js_sys::Uint8Array::new_with_length(1));
+    let before = js_sys::Date::now();
+
+    let implicit = frame(&payload);
+    Reflect::delete_property(implicit.unchecked_ref::<Object>(), &"timestamp".into()).unwrap();
+    let zero = frame(&payload);
+    Reflect::set(&zero, &"timestamp".into(), &JsValue::from(0)).unwrap();
+
+    for value in [implicit, zero] {
+        let decoded = nagare::decode_postcard(&nagare::encode_postcard(value).unwrap()).unwrap();
+        let timestamp = get(&decoded, "timestamp").as_f64().unwrap();
+        assert!(timestamp >= before.floor() && timestamp <= js_sys::Date::now());
+    }
+
+    let explicit = nagare::decode_postcard(&nagare::encode_postcard(frame(&payload)).unwrap()).unwrap();
+    assert_eq!(get(&explicit, "timestamp"), 1234.0);
+}
+
+#[wasm_bindgen_test]
+fn absurd_declared_length_is_rejected_up_front() {
+    // sequence 1, timestamp 1, Float32Array claiming u32::MAX elements, no data.
+    let bytes = js_sys::Uint8Array::from(&[1, 1, 1, 0xff, 0xff, 0xff, 0xff, 0x0f][..]);
+    for result in [nagare::decode_postcard(&bytes), nagare::decode_postcard_with_limit(&bytes, 1024)] {
+        let err = result.unwrap_err().as_string().unwrap();
+        assert!(err.contains("exceeds limit"), "{}", err);
+    }
+}
+
+#[wasm_bindgen_test]
+fn payload_limit_applies_to_real_frames() {
+    let payload = typed_payload("float32", &js_sys::Float32Array::new_with_length(100));
+    let bytes = nagare::encode_postcard(frame(&payload)).unwrap();
+    assert!(nagare::decode_postcard_with_limit(&bytes, 400).is_ok());
+    assert!(nagare::decode_postcard_with_limit(&bytes, 399).is_err());
+
+    let v2 = nagare::encode_postcard_v2(frame(&payload)).unwrap();
+    assert!(nagare::decode_postcard_with_limit(&v2, 399).is_err());
+}
+
+#[cfg(feature = "compression")]
+#[wasm_bindgen_test]
+fn payload_limit_applies_after_decompression() {
+    let payload = typed_payload("float32", &js_sys::Float32Array::new_with_length(10_000));
+    let bytes = nagare::encode_postcard_compressed(frame(&payload), "deflate").unwrap();
+    assert!(bytes.length() < 1000);
+    assert!(nagare::decode_postcard_with_limit(&bytes, 1000).is_err());
+    assert!(nagare::decode_postcard_with_limit(&bytes, 40_000).is_ok());
+}
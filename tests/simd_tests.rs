@@ -0,0 +1,434 @@
+#![cfg(all(target_arch = "wasm32", feature = "simd"))]
+
+use nagare::dispatch::ConvMode;
+use nagare::simd_ops::*;
+use wasm_bindgen_test::*;
+
+fn activation_inputs() -> Vec<f32> {
+    (0..2003).map(|i| (i as f32 - 1001.0) * 0.02).collect()
+}
+
+#[wasm_bindgen_test]
+fn clamp_keeps_bounds_and_handles_the_tail() {
+    // Seven values: one full vector plus a scalar remainder of three.
+    let data = [-2.0, -1.0, 0.5, 1.0, 2.0, -1.0, 1.0];
+    assert_eq!(f32x_clamp(&data, -1.0, 1.0), vec![-1.0, -1.0, 0.5, 1.0, 1.0, -1.0, 1.0]);
+    assert_eq!(f32x_clamp(&data, 0.5, 0.5), vec![0.5; 7]);
+    let with_nan = f32x_clamp(&[f32::NAN, 3.0, 0.0, 0.5, f32::NAN], 0.0, 1.0);
+    assert!(with_nan[0].is_nan() && with_nan[4].is_nan());
+    assert_eq!(with_nan[1..4], [1.0, 0.0, 0.5]);
+
+    let inputs = activation_inputs();
+    let scalar: Vec<f32> = inputs.iter().map(|x| x.clamp(-3.0, 7.5)).collect();
+    assert_eq!(f32x_clamp(&inputs, -3.0, 7.5), scalar);
+}
+
+#[wasm_bindgen_test]
+fn relu_clamps_negatives() {
+    let data = [-1.0, 2.0, -3.0, 4.0, -5.0, 0.0];
+    assert_eq!(f32x_relu(&data), vec![0.0, 2.0, 0.0, 4.0, 0.0, 0.0]);
+    assert_eq!(f32x_leaky_relu(&data, 0.1), vec![-0.1, 2.0, -0.3, 4.0, -0.5, 0.0]);
+
+    // NaN maps to 0 both in the vector body and in the scalar tail.
+    let with_nan = [1.0, f32::NAN, -1.0, 2.0, 3.0, f32::NAN];
+    assert_eq!(f32x_relu(&with_nan), vec![1.0, 0.0, 0.0, 2.0, 3.0, 0.0]);
+}
+
+#[wasm_bindgen_test]
+fn vector_min_max_ignore_a_nan_operand_everywhere() {
+    let nan = f32::NAN;
+    // NaNs in both the vector body and the scalar tail, on either side.
+    let a = [nan, 1.0, 5.0, nan, 2.0, nan, 7.0];
+    let b = [3.0, nan, 4.0, nan, nan, 6.0, 8.0];
+    let min = f32x_vector_min(&a, &b);
+    let max = f32x_vector_max(&a, &b);
+    for (i, (x, y)) in a.iter().zip(&b).enumerate() {
+        assert_eq!(min[i].to_bits(), x.min(*y).to_bits(), "min[{i}]");
+        assert_eq!(max[i].to_bits(), x.max(*y).to_bits(), "max[{i}]");
+    }
+    assert_eq!(&min[..3], &[3.0, 1.0, 4.0]);
+    assert!(min[3].is_nan());
+}
+
+#[wasm_bindgen_test]
+fn sigmoid_within_tolerance() {
+    let data = activation_inputs();
+    for (actual, x) in f32x_sigmoid(&data).iter().zip(&data) {
+        let expected = 1.0 / (1.0 + (-x).exp());
+        assert!((actual - expected).abs() < 1e-6, "sigmoid({x}) = {actual}, expected {expected}");
+    }
+}
+
+#[wasm_bindgen_test]
+fn tanh_within_tolerance() {
+    let data = activation_inputs();
+    for (actual, x) in f32x_tanh(&data).iter().zip(&data) {
+        assert!((actual - x.tanh()).abs() < 1e-5, "tanh({x}) = {actual}, expected {}", x.tanh());
+    }
+}
+
+#[wasm_bindgen_test]
+fn argmax_argmin_prefer_lowest_index() {
+    // Duplicate extrema in different lanes and in the scalar tail.
+    let data = [1.0, 9.0, -4.0, 3.0, 9.0, -4.0, 0.0, 2.0, 9.0, -4.0];
+    assert_eq!(f32x_argmax(&data), 1);
+    assert_eq!(f32x_argmin(&data), 2);
+
+    let data = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0];
+    assert_eq!(f32x_argmax(&data), 10);
+    assert_eq!(f32x_argmin(&data), 0);
+
+    assert_eq!(f32x_argmax(&[2.0, 5.0, 5.0]), 1);
+    assert_eq!(f32x_argmin(&[]), 0);
+
+    // The JS exports have no index for an empty array, like the registry kernels.
+    let empty = js_sys::Float32Array::new_with_length(0);
+    assert!(nagare::f32x_argmax(&empty).is_err());
+    assert!(nagare::f32x_argmin(&empty).is_err());
+    assert_eq!(nagare::f32x_argmax(&js_sys::Float32Array::from(&[2.0f32, 5.0, 5.0][..])).unwrap(), 1);
+}
+
+#[wasm_bindgen_test]
+fn argmax_matches_scalar_on_odd_lengths() {
+    for len in 1..23 {
+        let data: Vec<f32> = (0..len).map(|i| ((i * 7919) % 13) as f32).collect();
+        let max = data.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let min = data.iter().cloned().fold(f32::INFINITY, f32::min);
+        assert_eq!(f32x_argmax(&data), data.iter().position(|&x| x == max).unwrap());
+        assert_eq!(f32x_argmin(&data), data.iter().position(|&x| x == min).unwrap());
+    }
+}
+
+fn conv1d_reference(signal: &[f32], kernel: &[f32]) -> Vec<f32> {
+    // Full convolution; "valid" and "same" are slices of it.
+    let n = signal.len() + kernel.len() - 1;
+    (0..n)
+        .map(|t| {
+            (0..kernel.len())
+                .filter(|&j| t >= j && t - j < signal.len())
+                .map(|j| signal[t - j] * kernel[j])
+                .sum()
+        })
+        .collect()
+}
+
+#[wasm_bindgen_test]
+fn conv1d_matches_reference() {
+    let signal: Vec<f32> = (0..37).map(|i| ((i * 31) % 11) as f32 - 5.0).collect();
+    for taps in [1, 2, 3, 4, 5, 8, 15] {
+        let kernel: Vec<f32> = (0..taps).map(|j| 0.5 - j as f32 * 0.1).collect();
+        let full = conv1d_reference(&signal, &kernel);
+
+        let valid = f32x_conv1d(&signal, &kernel, ConvMode::Valid);
+        let expected = &full[taps - 1..signal.len()];
+        assert_eq!(valid.len(), expected.len());
+        for (a, b) in valid.iter().zip(expected) {
+            assert!((a - b).abs() < 1e-4, "valid taps={taps}: {a} vs {b}");
+        }
+
+        let same = f32x_conv1d(&signal, &kernel, ConvMode::Same);
+        let start = (taps - 1) / 2;
+        let expected = &full[start..start + signal.len()];
+        assert_eq!(same.len(), signal.len());
+        for (a, b) in same.iter().zip(expected) {
+            assert!((a - b).abs() < 1e-4, "same taps={taps}: {a} vs {b}");
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+fn conv1d_kernel_longer_than_signal() {
+    let signal = [1.0, 2.0, 3.0];
+    let kernel = [1.0, 1.0, 1.0, 1.0, 1.0];
+    assert!(f32x_conv1d(&signal, &kernel, ConvMode::Valid).is_empty());
+
+    let js_signal = js_sys::Float32Array::from(&signal[..]);
+    let js_kernel = js_sys::Float32Array::from(&kernel[..]);
+    assert_eq!(nagare::f32x_conv1d(&js_signal, &js_kernel, "valid").unwrap().length(), 0);
+    assert!(nagare::f32x_conv1d(&js_signal, &js_kernel, "same").is_err());
+}
+
+fn pseudo_random(seed: &mut u32, len: usize) -> Vec<f32> {
+    (0..len)
+        .map(|_| {
+            *seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            (*seed >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0
+        })
+        .collect()
+}
+
+// Lengths covering empty input, a lone tail, whole vectors and every tail size.
+const TAIL_LENGTHS: [usize; 9] = [0, 1, 3, 4, 5, 7, 8, 9, 1027];
+
+#[wasm_bindgen_test]
+fn cumsum_matches_scalar_including_the_tail() {
+    let mut seed = 5;
+    for len in TAIL_LENGTHS {
+        let data = pseudo_random(&mut seed, len);
+        let mut sum = 0.0f64;
+        let expected: Vec<f64> = data.iter().map(|&x| {
+            sum += x as f64;
+            sum
+        }).collect();
+        let actual = f32x_cumsum(&data);
+        assert_eq!(actual.len(), len);
+        for (i, (a, e)) in actual.iter().zip(&expected).enumerate() {
+            assert!((*a as f64 - e).abs() < 1e-3, "len {len}, [{i}]: {a} vs {e}");
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+fn normalize_matches_scalar_including_the_tail() {
+    let mut seed = 9;
+    for len in TAIL_LENGTHS {
+        let data = pseudo_random(&mut seed, len);
+        let actual = f32x_normalize(&data, 1e-8);
+        assert_eq!(actual.len(), len);
+        if len < 2 {
+            assert!(actual.iter().all(|&x| x == 0.0), "len {len}: {actual:?}");
+            continue;
+        }
+        let mean = data.iter().map(|&x| x as f64).sum::<f64>() / len as f64;
+        let std = (data.iter().map(|&x| (x as f64 - mean).powi(2)).sum::<f64>() / len as f64).sqrt();
+        for (i, (a, x)) in actual.iter().zip(&data).enumerate() {
+            let expected = (*x as f64 - mean) / (std + 1e-8);
+            assert!((*a as f64 - expected).abs() < 1e-4, "len {len}, [{i}]: {a} vs {expected}");
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+fn scalar_fallbacks_match_the_simd_kernels() {
+    use nagare::dispatch;
+    let mut seed = 21;
+    let a = pseudo_random(&mut seed, 1027);
+    let b = pseudo_random(&mut seed, 1027);
+    let run = |simd: bool| {
+        dispatch::set_simd_enabled(simd);
+        let used_simd = dispatch::simd_available();
+        let (min, max) = dispatch::min_max(&a);
+        let reductions = vec![
+            dispatch::sum(&a),
+            dispatch::mean(&a),
+            dispatch::dot_product(&a, &b),
+            dispatch::argmax(&a) as f32,
+            dispatch::argmin(&a) as f32,
+            min,
+            max,
+        ];
+        let outputs = vec![
+            ("map_mul_add", dispatch::map_mul_add(&a, 1.5, -0.5)),
+            ("scalar_mul", dispatch::scalar_mul(&a, 3.0)),
+            ("vector_add", dispatch::vector_add(&a, &b)),
+            ("vector_mul", dispatch::vector_mul(&a, &b)),
+            ("rolling_mean", dispatch::rolling_mean(&a, 7)),
+            ("cumsum", dispatch::cumsum(&a)),
+            ("diff", dispatch::diff(&a)),
+            ("normalize", dispatch::normalize(&a, 1e-8)),
+            ("relu", dispatch::relu(&a)),
+            ("sigmoid", dispatch::sigmoid(&a)),
+            ("tanh", dispatch::tanh(&a)),
+            ("abs", dispatch::abs(&a)),
+            ("clamp", dispatch::clamp(&a, -0.5, 0.5)),
+            ("autocorr", dispatch::autocorr(&a, 5)),
+            ("conv1d", dispatch::conv1d(&a, &b[..5], ConvMode::Same)),
+            ("matmul", dispatch::matmul(&a[..12], &b[..12], 3, 4, 3)),
+            ("reductions", reductions),
+        ];
+        (used_simd, outputs)
+    };
+    let (simd_used, simd) = run(true);
+    let (scalar_used, scalar) = run(false);
+    dispatch::set_simd_enabled(true);
+
+    assert!(simd_used && !scalar_used);
+    for ((name, simd), (_, scalar)) in simd.iter().zip(&scalar) {
+        assert_eq!(simd.len(), scalar.len(), "{name}");
+        for (i, (s, x)) in simd.iter().zip(scalar).enumerate() {
+            assert!((s - x).abs() <= 1e-4 * x.abs().max(1.0), "{name}[{i}]: simd {s}, scalar {x}");
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+fn matmul_matches_naive_triple_loop() {
+    let mut seed = 7;
+    for (m, k, n) in [(1, 1, 1), (2, 3, 4), (5, 7, 9), (8, 16, 3), (3, 4, 13)] {
+        let a = pseudo_random(&mut seed, m * k);
+        let b = pseudo_random(&mut seed, k * n);
+        let result = f32x_matmul(&a, &b, m, k, n);
+        for i in 0..m {
+            for j in 0..n {
+                let expected: f32 = (0..k).map(|p| a[i * k + p] * b[p * n + j]).sum();
+                assert!((result[i * n + j] - expected).abs() < 1e-4, "{m}x{k}x{n} at ({i},{j})");
+            }
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+fn matmul_rejects_mismatched_dimensions() {
+    let a = js_sys::Float32Array::new_with_length(6);
+    let b = js_sys::Float32Array::new_with_length(6);
+    assert!(nagare::f32x_matmul(&a, &b, 2, 3, 2).is_ok());
+    assert!(nagare::f32x_matmul(&a, &b, 3, 3, 2).is_err());
+    assert!(nagare::f32x_matmul(&a, &b, 2, 3, 3).is_err());
+}
+
+#[wasm_bindgen_test]
+fn rolling_mean_matches_scalar_including_a_zero_window() {
+    let data: Vec<f32> = (0..11).map(|i| i as f32 * 0.5).collect();
+    for window in [0, 1, 3, 4, 5, 11, 12] {
+        let expected: Vec<f32> = match window {
+            0 => vec![],
+            _ => data.windows(window).map(|w| w.iter().sum::<f32>() / window as f32).collect(),
+        };
+        let actual = f32x_rolling_mean(&data, window);
+        assert_eq!(actual.len(), expected.len(), "window {}", window);
+        for (a, e) in actual.iter().zip(&expected) {
+            assert!((a - e).abs() < 1e-5, "window {}: {} vs {}", window, a, e);
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+fn autocorr_peaks_at_the_sine_period() {
+    let period = 20;
+    let data: Vec<f32> = (0..200)
+        .map(|i| (2.0 * std::f32::consts::PI * i as f32 / period as f32).sin() + 0.5)
+        .collect();
+    let r = f32x_autocorr(&data, 30);
+    assert_eq!(r.len(), 31);
+    assert!((r[0] - 1.0).abs() < 1e-6);
+    assert!(r.iter().all(|x| (-1.0..=1.0).contains(x)));
+
+    // Skip the lags that are trivially close to 0.
+    let peak = (5..=30).max_by(|&a, &b| r[a].total_cmp(&r[b])).unwrap();
+    assert_eq!(peak, period);
+    assert!(r[period / 2] < -0.8);
+}
+
+#[wasm_bindgen_test]
+fn autocorr_handles_degenerate_inputs() {
+    assert!(f32x_autocorr(&[], 4).is_empty());
+    assert_eq!(f32x_autocorr(&[3.0; 6], 2), vec![0.0; 3]);
+    // Constant but not exactly representable, so the f32 mean is off by an ulp.
+    assert_eq!(f32x_autocorr(&[0.1; 8], 3), vec![0.0; 4]);
+    // Lags are capped at len - 1.
+    assert_eq!(f32x_autocorr(&[1.0, -1.0, 1.0], 10).len(), 3);
+}
+
+#[wasm_bindgen_test]
+fn quantize_i8_round_trips_within_half_a_step() {
+    let mut seed = 11;
+    let data: Vec<f32> = pseudo_random(&mut seed, 1027).iter().map(|x| x * 3.0).collect();
+    let (values, scale) = f32x_quantize_i8(&data);
+    let max_abs = data.iter().fold(0.0f32, |m, x| m.max(x.abs()));
+    assert_eq!(scale, max_abs / 127.0);
+    assert!(values.iter().any(|&q| q.abs() == 127));
+
+    let restored = nagare::dispatch::dequantize_i8(&values, scale);
+    for (x, y) in data.iter().zip(&restored) {
+        assert!((x - y).abs() <= scale / 2.0 + 1e-6, "{x} -> {y}");
+    }
+}
+
+#[wasm_bindgen_test]
+fn quantize_i8_of_zeros_has_zero_scale() {
+    assert_eq!(f32x_quantize_i8(&[0.0; 5]), (vec![0; 5], 0.0));
+    assert_eq!(f32x_quantize_i8(&[-2.0, 1.0, 0.5]), (vec![-127, 64, 32], 2.0 / 127.0));
+}
+
+#[wasm_bindgen_test]
+fn abs_matches_scalar_bit_for_bit() {
+    let mut data = activation_inputs();
+    data.extend([-0.0, f32::NEG_INFINITY, f32::INFINITY, -f32::MIN_POSITIVE, f32::NAN]);
+    let actual = f32x_abs(&data);
+    assert_eq!(actual.len(), data.len());
+    for (a, x) in actual.iter().zip(&data) {
+        assert_eq!(a.to_bits(), x.abs().to_bits(), "abs({x})");
+    }
+}
+
+#[wasm_bindgen_test]
+fn scalar_ops_match_scalar_reference() {
+    let data = activation_inputs();
+    for scalar in [2.5f32, -0.75, 0.0] {
+        let kernels: [(fn(&[f32], f32) -> Vec<f32>, fn(f32, f32) -> f32); 4] = [
+            (f32x_scalar_add, |x, s| x + s),
+            (f32x_scalar_sub, |x, s| x - s),
+            (f32x_scalar_mul, |x, s| x * s),
+            (f32x_scalar_div, |x, s| x / s),
+        ];
+        for (kernel, reference) in kernels {
+            let actual = kernel(&data, scalar);
+            assert_eq!(actual.len(), data.len());
+            for (a, x) in actual.iter().zip(&data) {
+                let expected = reference(*x, scalar);
+                assert!(a.to_bits() == expected.to_bits() || (a.is_nan() && expected.is_nan()), "{x} by {scalar}: {a} vs {expected}");
+            }
+        }
+    }
+    // IEEE division by zero.
+    let quotients = f32x_scalar_div(&[1.0, -1.0, 0.0, 2.0, 3.0], 0.0);
+    assert_eq!(&quotients[..2], &[f32::INFINITY, f32::NEG_INFINITY]);
+    assert!(quotients[2].is_nan());
+    assert_eq!(quotients[4], f32::INFINITY);
+}
+
+#[wasm_bindgen_test]
+fn sum_and_mean_track_an_f64_reference() {
+    // Long enough that naive f32 accumulation loses several digits.
+    let data: Vec<f32> = (0..1_000_003).map(|i| 0.1 + (i % 7) as f32 * 1e-3).collect();
+    let reference: f64 = data.iter().map(|&x| x as f64).sum();
+    let sum = f32x_sum(&data) as f64;
+    assert!(((sum - reference) / reference).abs() < 1e-7, "sum {sum} vs {reference}");
+    let mean = f32x_mean(&data) as f64;
+    let expected = reference / data.len() as f64;
+    assert!(((mean - expected) / expected).abs() < 1e-7, "mean {mean} vs {expected}");
+
+    assert_eq!(f32x_sum(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]), 21.0);
+    assert_eq!(f32x_sum(&[]), 0.0);
+    assert!(f32x_mean(&[]).is_nan());
+
+    // Infinities inside a SIMD chunk survive the compensation.
+    let mut infinite = [0.0f32; 8];
+    infinite[0] = f32::INFINITY;
+    assert_eq!(f32x_sum(&infinite), f32::INFINITY);
+    assert_eq!(f32x_mean(&infinite), f32::INFINITY);
+    infinite[0] = f32::NEG_INFINITY;
+    assert_eq!(f32x_sum(&infinite), f32::NEG_INFINITY);
+    assert_eq!(f32x_mean(&infinite), f32::NEG_INFINITY);
+    infinite[5] = f32::INFINITY;
+    assert!(f32x_sum(&infinite).is_nan());
+}
+
+#[wasm_bindgen_test]
+fn f64_kernels_match_scalar_within_epsilon() {
+    let data: Vec<f64> = (0..2003).map(|i| (i as f64 - 1001.0) * 0.37 + (i % 5) as f64 * 1e-9).collect();
+    // Relative to `scale`, since partial sums cancel to near zero.
+    let close = |actual: &[f64], expected: &[f64], scale: f64, op: &str| {
+        assert_eq!(actual.len(), expected.len(), "{op}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() <= 1e-12 * scale, "{op}: {a} vs {e}");
+        }
+    };
+
+    let squares: Vec<f64> = data.iter().map(|x| x * x).collect();
+    assert_eq!(f64x_square(&data), squares);
+
+    let mut sum = 0.0;
+    let prefix: Vec<f64> = data.iter().map(|x| { sum += x; sum }).collect();
+    close(&f64x_cumsum(&data), &prefix, data.iter().map(|x| x.abs()).sum(), "cumsum");
+
+    let mean = data.iter().sum::<f64>() / data.len() as f64;
+    let std_dev = (data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / data.len() as f64).sqrt();
+    let normalized: Vec<f64> = data.iter().map(|x| (x - mean) / std_dev).collect();
+    close(&f64x_normalize(&data, 0.0), &normalized, 1.0, "normalize");
+
+    // Odd lengths exercise the scalar tail; a constant input stays finite.
+    assert_eq!(f64x_cumsum(&[1.0, 2.0, 3.0]), vec![1.0, 3.0, 6.0]);
+    assert_eq!(f64x_normalize(&[4.0; 3], 1e-8), vec![0.0; 3]);
+    assert!(f64x_normalize(&[], 1e-8).is_empty());
+}
@@ -0,0 +1,237 @@
+use nagare::operators::{apply_float32, EmaOperator, Histogram, Operation, ResampleMethod, Resampler, WindowedOperator};
+
+fn filled(window_size: usize, operation: &str, values: &[f32]) -> WindowedOperator<f32> {
+    let mut op = WindowedOperator::new(window_size, operation.parse().unwrap());
+    for v in values {
+        op.push(*v);
+    }
+    op
+}
+
+#[test]
+fn median_of_odd_and_even_windows() {
+    assert_eq!(filled(5, "median", &[9.0, 1.0, 5.0, 3.0, 7.0]).compute(), Some(5.0));
+    assert_eq!(filled(4, "median", &[9.0, 1.0, 5.0, 3.0]).compute(), Some(4.0));
+}
+
+#[test]
+fn percentile_interpolates_between_ranks() {
+    let values: Vec<f32> = (1..=11).map(|v| v as f32).collect();
+    assert_eq!(filled(11, "p0", &values).compute(), Some(1.0));
+    assert_eq!(filled(11, "p100", &values).compute(), Some(11.0));
+    assert_eq!(filled(11, "p95", &values).compute(), Some(10.5));
+    assert_eq!(filled(4, "p50", &[4.0, 1.0, 3.0, 2.0]).compute(), Some(2.5));
+}
+
+#[test]
+fn order_statistics_follow_option_contract() {
+    // Not ready yet.
+    assert_eq!(filled(4, "median", &[1.0, 2.0]).compute(), None);
+    // Elementwise operations have no window result.
+    assert_eq!(filled(2, "square", &[1.0, 2.0]).compute(), None);
+    // Unparseable or out-of-range percentiles are rejected up front.
+    assert!("pxx".parse::<Operation>().is_err());
+    assert!("p101".parse::<Operation>().is_err());
+}
+
+#[test]
+fn partial_windows_emit_from_the_first_push() {
+    let mut full = WindowedOperator::<f32>::new(4, Operation::Mean);
+    let mut partial = WindowedOperator::<f32>::new(4, Operation::Mean).with_emit_partial(true);
+    assert_eq!(partial.compute(), None);
+
+    let mut means = Vec::new();
+    for v in [2.0, 4.0, 6.0, 8.0, 10.0] {
+        full.push(v);
+        partial.push(v);
+        means.push((full.compute(), partial.compute()));
+    }
+    assert_eq!(
+        means,
+        vec![
+            (None, Some(2.0)),
+            (None, Some(3.0)),
+            (None, Some(4.0)),
+            (Some(5.0), Some(5.0)),
+            (Some(7.0), Some(7.0)),
+        ]
+    );
+
+    let mut variance = WindowedOperator::<f64>::new(3, Operation::Variance).with_emit_partial(true);
+    variance.push(1.0);
+    assert_eq!(variance.compute(), Some(0.0));
+    variance.push(3.0);
+    assert_eq!(variance.compute(), Some(1.0));
+}
+
+#[test]
+fn ema_seeds_with_first_value() {
+    let mut ema = EmaOperator::new(0.5).unwrap();
+    assert_eq!(ema.current(), None);
+    assert_eq!(ema.push(10.0), 10.0);
+    assert_eq!(ema.push(20.0), 15.0);
+    assert_eq!(ema.push(15.0), 15.0);
+
+    ema.reset();
+    assert_eq!(ema.push(4.0), 4.0);
+}
+
+#[test]
+fn ema_validates_alpha() {
+    assert!(EmaOperator::new(0.0).is_err());
+    assert!(EmaOperator::new(1.5).is_err());
+    assert!(EmaOperator::new(f32::NAN).is_err());
+    assert!(EmaOperator::new(1.0).is_ok());
+}
+
+#[test]
+fn f64_window_supports_all_operations() {
+    let values = [1.0, 2.0, 3.0, 6.0];
+    let compute = |operation: &str| {
+        let mut op = WindowedOperator::<f64>::new(4, operation.parse().unwrap());
+        values.iter().for_each(|v| op.push(*v));
+        op.compute()
+    };
+    assert_eq!(compute("mean"), Some(3.0));
+    assert_eq!(compute("sum"), Some(12.0));
+    assert_eq!(compute("min"), Some(1.0));
+    assert_eq!(compute("max"), Some(6.0));
+    assert_eq!(compute("variance"), Some(3.5));
+    assert_eq!(compute("std"), Some(3.5f64.sqrt()));
+    assert_eq!(compute("median"), Some(2.5));
+}
+
+#[test]
+fn f64_variance_is_more_precise_for_large_magnitudes() {
+    // Large offset plus small spread: the exact variance is 1.25.
+    let offset = 1.0e7;
+    let spread = [0.0, 1.0, 2.0, 3.0];
+
+    let mut narrow = WindowedOperator::<f32>::new(4, Operation::Variance);
+    let mut wide = WindowedOperator::<f64>::new(4, Operation::Variance);
+    for s in spread {
+        narrow.push((offset + s) as f32);
+        wide.push(offset + s);
+    }
+
+    let f32_error = (narrow.compute().unwrap() as f64 - 1.25).abs();
+    let f64_error = (wide.compute().unwrap() - 1.25).abs();
+    assert!(f64_error < 1e-9);
+    assert!(f64_error < f32_error);
+}
+
+#[test]
+fn histogram_bins_boundary_values() {
+    let mut histogram = Histogram::new(0.0, 10.0, 5).unwrap();
+    for v in [0.0, 1.999, 2.0, 5.0, 9.999, 10.0, -0.001, 10.001, f64::NAN] {
+        histogram.push(v);
+    }
+    // Lower edges belong to their bin; `max` lands in the last one.
+    assert_eq!(histogram.counts(), &[2, 1, 1, 0, 2]);
+    assert_eq!(histogram.underflow(), 1);
+    assert_eq!(histogram.overflow(), 1);
+    assert_eq!(histogram.bin_range(1), (2.0, 4.0));
+
+    histogram.reset();
+    assert_eq!(histogram.counts(), &[0; 5]);
+    assert!(Histogram::new(1.0, 1.0, 3).is_err());
+    assert!(Histogram::new(0.0, 1.0, 0).is_err());
+}
+
+#[test]
+fn stable_normalize_and_variance_track_an_f64_reference() {
+    // Large values with small perturbations: summing first swamps the spread.
+    let input: Vec<f32> = (0..100_000).map(|i| 1.0e6 + (i % 7) as f32 * 0.25).collect();
+    let wide: Vec<f64> = input.iter().map(|&x| x as f64).collect();
+    let mean = wide.iter().sum::<f64>() / wide.len() as f64;
+    let variance = wide.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / wide.len() as f64;
+    let reference: Vec<f64> = wide.iter().map(|x| (x - mean) / variance.sqrt()).collect();
+    let max_error = |output: Vec<f32>| {
+        output.iter().zip(&reference).map(|(&x, r)| (x as f64 - r).abs()).fold(0.0, f64::max)
+    };
+
+    let naive = max_error(apply_float32(&input, Operation::Normalize).unwrap());
+    let stable = max_error(apply_float32(&input, "normalize_stable".parse().unwrap()).unwrap());
+    assert!(stable < 1e-5, "stable error {}", stable);
+    assert!(stable * 1000.0 < naive, "stable {} vs naive {}", stable, naive);
+
+    let mut naive = WindowedOperator::<f32>::new(input.len(), Operation::Variance);
+    let mut stable = WindowedOperator::<f32>::new(input.len(), Operation::VarianceStable);
+    for &x in &input {
+        naive.push(x);
+        stable.push(x);
+    }
+    let naive = (naive.compute().unwrap() as f64 - variance).abs();
+    let stable = (stable.compute().unwrap() as f64 - variance).abs();
+    assert!(stable < variance * 1e-6);
+    assert!(stable * 1000.0 < naive);
+}
+
+fn resampled(method: &str, samples: &[(u64, f64)]) -> Vec<(u64, f64)> {
+    let mut resampler = Resampler::new(10, method.parse().unwrap()).unwrap();
+    let mut points: Vec<_> = samples.iter().flat_map(|&(t, v)| resampler.push(t, v)).collect();
+    points.extend(resampler.finish());
+    points
+}
+
+#[test]
+fn resample_interpolates_between_known_points() {
+    // Grid starts at the first multiple of 10 on or after t = 5; the gap
+    // from 5 to 45 is filled at every point in it.
+    let samples = [(5, 0.0), (45, 8.0)];
+    assert_eq!(resampled("linear", &samples), vec![(10, 1.0), (20, 3.0), (30, 5.0), (40, 7.0)]);
+    assert_eq!(resampled("hold", &samples), vec![(10, 0.0), (20, 0.0), (30, 0.0), (40, 0.0)]);
+    assert_eq!(resampled("nearest", &samples), vec![(10, 0.0), (20, 0.0), (30, 8.0), (40, 8.0)]);
+}
+
+#[test]
+fn resample_edges() {
+    // A sample on the grid is emitted as is, the last one from `finish`;
+    // a sample that goes back in time is dropped.
+    assert_eq!(resampled("linear", &[(0, 1.0), (20, 3.0), (15, 9.0)]), vec![(0, 1.0), (10, 2.0), (20, 3.0)]);
+    assert_eq!(resampled("linear", &[(3, 1.0)]), vec![]);
+    assert!("cubic".parse::<ResampleMethod>().is_err());
+    assert!(Resampler::new(0, ResampleMethod::Hold).is_err());
+}
+
+#[test]
+fn operation_names_round_trip() {
+    for name in ["square", "cumsum", "diff_prepend_zero", "tanh", "mean", "std", "median", "p95", "p12.5"] {
+        let op: Operation = name.parse().unwrap();
+        assert_eq!(op.to_string(), name);
+    }
+    assert_eq!("p50".parse::<Operation>(), Ok(Operation::Percentile(50.0)));
+    assert!("Square".parse::<Operation>().is_err());
+}
+
+#[test]
+fn typed_batch_rejects_window_operations() {
+    use nagare::operators::{apply_float32, apply_float64};
+
+    assert_eq!(apply_float32(&[1.0, 3.0, 6.0], Operation::Diff), Ok(vec![2.0, 3.0]));
+    assert_eq!(apply_float64(&[-1.0, 2.0], Operation::Relu), Ok(vec![0.0, 2.0]));
+    assert!(apply_float32(&[1.0], Operation::Mean).is_err());
+    assert!(apply_float64(&[1.0], Operation::Percentile(50.0)).is_err());
+}
+
+#[test]
+fn unary_math_operations_follow_ieee_domains() {
+    use nagare::operators::{apply_float32, apply_float64};
+
+    let input = [-2.0f32, -0.0, 0.0, 4.0];
+    assert_eq!(apply_float32(&input, Operation::Abs), Ok(vec![2.0, 0.0, 0.0, 4.0]));
+    assert_eq!(apply_float32(&input, Operation::Reciprocal), Ok(vec![-0.5, f32::NEG_INFINITY, f32::INFINITY, 0.25]));
+    let sign = apply_float32(&input, Operation::Sign).unwrap();
+    assert_eq!(sign, vec![-1.0, 0.0, 0.0, 1.0]);
+    assert!(sign[1].is_sign_negative() && sign[2].is_sign_positive());
+    assert!(apply_float32(&[f32::NAN], Operation::Sign).unwrap()[0].is_nan());
+
+    let log = apply_float64(&[-1.0, 0.0, std::f64::consts::E], Operation::Log).unwrap();
+    assert!(log[0].is_nan());
+    assert_eq!(&log[1..], &[f64::NEG_INFINITY, 1.0]);
+    assert_eq!(apply_float64(&[8.0], Operation::Log2), Ok(vec![3.0]));
+    assert_eq!(apply_float64(&[0.0], Operation::Exp), Ok(vec![1.0]));
+    for name in ["abs", "sign", "log", "log2", "exp", "reciprocal"] {
+        assert!(name.parse::<Operation>().unwrap().is_elementwise());
+    }
+}